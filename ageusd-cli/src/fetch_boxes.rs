@@ -1,36 +1,126 @@
+use ageusd_headless::input_boxes::ErgsBox;
+use ageusd_headless::parameters::{Network, ProtocolConfig};
 use ageusd_headless::update::{BallotBox, UpdateBox};
 use ergo_headless_dapp_framework::{ExplorerFindable, SpecifiedBox};
 use reqwest::blocking::get;
 
 /// Fetch the user's `BallotBox` from the public Ergo Explorer API
-pub fn fetch_user_ballot_box(user_address: &str) -> BallotBox {
-    let box_spec = BallotBox::box_spec().modified_address(Some(user_address.to_string()));
+#[allow(dead_code)]
+pub fn fetch_user_ballot_box(user_address: &str, config: &ProtocolConfig) -> BallotBox {
+    let box_spec = BallotBox::box_spec_for(config).modified_address(Some(user_address.to_string()));
     let ballot_box_url = box_spec
         .explorer_endpoint("https://api.ergoplatform.com/api")
         .unwrap();
     println!("URL: {}", ballot_box_url);
     let response = get(&ballot_box_url).unwrap().text().unwrap();
-    let bbs = BallotBox::process_explorer_response(&response).unwrap();
-    if bbs.len() == 0 {
+    let bbs: Vec<BallotBox> = box_spec
+        .process_explorer_response(&response)
+        .unwrap()
+        .iter()
+        .filter_map(|b| BallotBox::new_with_config(b, config).ok())
+        .collect();
+    if bbs.is_empty() {
         println!("Response Bad: {}", response);
     }
     bbs[0].clone()
 }
 
 /// Fetch all `BallotBox`es from the public Ergo Explorer API
-pub fn fetch_all_ballot_boxes() -> Vec<BallotBox> {
-    let ballot_box_url = BallotBox::box_spec()
+#[allow(dead_code)]
+pub fn fetch_all_ballot_boxes(config: &ProtocolConfig) -> Vec<BallotBox> {
+    let box_spec = BallotBox::box_spec_for(config);
+    let ballot_box_url = box_spec
         .explorer_endpoint("https://api.ergoplatform.com/api")
         .unwrap();
     let response = get(&ballot_box_url).unwrap().text().unwrap();
-    BallotBox::process_explorer_response(&response).unwrap()
+    box_spec
+        .process_explorer_response(&response)
+        .unwrap()
+        .iter()
+        .filter_map(|b| BallotBox::new_with_config(b, config).ok())
+        .collect()
+}
+
+/// Fetch the unspent boxes held by an address, which may be outside of the
+/// user's wallet, from the public Ergo Explorer API of the network
+pub fn fetch_ergs_boxes(address: &str, network: Network) -> Result<Vec<ErgsBox>, String> {
+    let url = ErgsBox::box_spec()
+        .modified_address(Some(address.to_string()))
+        .explorer_endpoint(explorer_api_url(network)?)
+        .map_err(|e| format!("{:?}", e))?;
+    let response = get_text(&url)?;
+    ErgsBox::process_explorer_response(&response).map_err(|e| format!("{:?}", e))
 }
 
 /// Fetch the `UpdateBox` from the public Ergo Explorer API
-pub fn fetch_update_box() -> UpdateBox {
-    let url = UpdateBox::box_spec()
+#[allow(dead_code)]
+pub fn fetch_update_box(config: &ProtocolConfig) -> UpdateBox {
+    let box_spec = UpdateBox::box_spec_for(config);
+    let url = box_spec
         .explorer_endpoint("https://api.ergoplatform.com/api")
         .unwrap();
     let response = get(&url).unwrap().text().unwrap();
-    UpdateBox::process_explorer_response(&response).unwrap()[0].clone()
+    let update_box = &box_spec.process_explorer_response(&response).unwrap()[0];
+    UpdateBox::new_with_config(update_box, config).unwrap()
+}
+
+/// The number of boxes requested per page from the Ergo Explorer API
+const EXPLORER_PAGE_SIZE: usize = 100;
+
+/// Fetch the JSON of every transaction which created an `UpdateBox` from
+/// the public Ergo Explorer API of the network, paging through every box
+/// which ever held the Update NFT
+pub fn fetch_update_box_transactions(
+    update_nft_id: &str,
+    network: Network,
+) -> Result<Vec<String>, String> {
+    let explorer_url = explorer_api_url(network)?;
+    let mut tx_ids: Vec<String> = vec![];
+    let mut offset = 0;
+    loop {
+        let url = format!(
+            "{}/v1/boxes/byTokenId/{}?offset={}&limit={}",
+            explorer_url, update_nft_id, offset, EXPLORER_PAGE_SIZE
+        );
+        let page: serde_json::Value =
+            serde_json::from_str(&get_text(&url)?).map_err(|e| e.to_string())?;
+        let items = page["items"]
+            .as_array()
+            .ok_or_else(|| format!("Unexpected response from {}", url))?;
+        tx_ids.extend(
+            items
+                .iter()
+                .filter_map(|b| b["transactionId"].as_str().map(|id| id.to_string())),
+        );
+        offset += items.len();
+        let total = page["total"].as_u64().unwrap_or(0) as usize;
+        if items.is_empty() || offset >= total {
+            break;
+        }
+    }
+    tx_ids.sort();
+    tx_ids.dedup();
+    tx_ids
+        .iter()
+        .map(|tx_id| get_text(&format!("{}/v1/transactions/{}", explorer_url, tx_id)))
+        .collect()
+}
+
+/// The public Ergo Explorer API of the network
+fn explorer_api_url(network: Network) -> Result<&'static str, String> {
+    network.explorer_api_url().ok_or_else(|| {
+        format!(
+            "The {} network has no public Ergo Explorer API.",
+            network.name()
+        )
+    })
+}
+
+/// Fetch the body of a URL, erroring if the request did not succeed
+fn get_text(url: &str) -> Result<String, String> {
+    let response = get(url).map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("{} responded with {}", url, response.status()));
+    }
+    response.text().map_err(|e| e.to_string())
 }
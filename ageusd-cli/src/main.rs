@@ -18,27 +18,64 @@ use scan::{
     register_user_ballot_token_scan,
 };
 use serde::Deserialize;
+use std::path::Path;
 
-use ageusd_headless::parameters::*;
+use ageusd_headless::block_time::BlockTimeEstimator;
+use ageusd_headless::capacity::MintCapacity;
+use ageusd_headless::coin_selection::{
+    select_ergs_box_for_fee, wallet_ergs_boxes, SelectionStrategy,
+};
+use ageusd_headless::contract_diff::{BankContractDiff, TreeConstant};
+use ageusd_headless::governance::GovernanceHistoryBuilder;
+use ageusd_headless::parameters::{ContractVersion, Network, ProtocolConfig};
 use ageusd_headless::protocol::StableCoinProtocol;
-use ageusd_headless::update::{BallotBox, UpdateBox};
-use ergo_headless_dapp_framework::{nano_erg_to_erg, NanoErg, P2PKAddressString, P2SAddressString};
+use ageusd_headless::scenario::MAX_SWEEP_STEPS;
+use ageusd_headless::update::{BallotBox, ProposalRegistry, UpdateBox};
+use ageusd_headless::voting::{stale_ballots, VoteTally};
+use ergo_headless_dapp_framework::{
+    nano_erg_to_erg, NanoErg, P2PKAddressString, P2SAddressString, WrappedBox,
+};
 
 pub type Result<T> = std::result::Result<T, anyhow::Error>;
 
-const USAGE: &'static str = r#"
+const USAGE: &str = r#"
 Usage:
-        stablecoin_cli status
-        stablecoin_cli parameters
-        stablecoin_cli scans register
-        stablecoin_cli scans check
-        stablecoin_cli mint ageusd <dollar-amount>
-        stablecoin_cli mint reservecoin <amount>
-        stablecoin_cli redeem ageusd <dollar-amount>
-        stablecoin_cli redeem reservecoin <amount>
-        stablecoin_cli vote collect <address>
-        stablecoin_cli vote <address>
-        stablecoin_cli update <address>
+        stablecoin_cli [options] status
+        stablecoin_cli [options] parameters
+        stablecoin_cli [options] scans register
+        stablecoin_cli [options] scans check
+        stablecoin_cli [options] mint ageusd <dollar-amount>
+        stablecoin_cli [options] mint reservecoin <amount>
+        stablecoin_cli [options] redeem ageusd <dollar-amount>
+        stablecoin_cli [options] redeem reservecoin <amount>
+        stablecoin_cli [options] vote collect <address>
+        stablecoin_cli [options] vote status [<address>]
+        stablecoin_cli [options] vote retract
+        stablecoin_cli [options] vote refresh [--all]
+        stablecoin_cli [options] vote <address> [--delegate=<address>] [--unsigned-tx=<path>]
+        stablecoin_cli [options] ballot transfer <address> <amount>
+        stablecoin_cli [options] ballot merge
+        stablecoin_cli [options] update diff <address>
+        stablecoin_cli [options] update <address>
+        stablecoin_cli [options] proposal add <address> <title> [<description>] [--contract-version=<version>]
+        stablecoin_cli [options] proposal list
+        stablecoin_cli [options] proposal show <address>
+        stablecoin_cli [options] governance history [--json] [--transactions=<path>]
+        stablecoin_cli [options] scenario [<min-price> <max-price>] [--steps=<n>] [--csv | --json]
+
+Options:
+        --network=<name>    Use the network of the deployment: mainnet, testnet or devnet. Only mainnet has a parameters preset, other networks require --config. [default: mainnet]
+        --config=<path>     Override the preset parameters with those in a TOML or JSON file.
+        --proposals=<path>  The local file the proposal registry is stored in. [default: proposals.json]
+        --contract-version=<version>  The contract version of the proposed Bank contract: v0.1, v0.2, v0.3 or v0.4.
+        --transactions=<path>  Read the transactions from a JSON file rather than the Ergo Explorer.
+        --json              Print the output as JSON.
+        --csv               Print the output as CSV.
+        --steps=<n>         The number of ERG/USD prices to evaluate the scenario at. [default: 20]
+        --coin-selection=<strategy>  The strategy used to select the wallet boxes to spend: largest-first, smallest-sufficient, minimize-change or avoid-foreign-tokens. [default: largest-first]
+        --all               Refresh every stale Ballot box of the wallet rather than only the first.
+        --delegate=<address>  Vote with the Ballot boxes of a delegate, producing an unsigned transaction for them to sign.
+        --unsigned-tx=<path>  Write the unsigned transaction of a delegated vote to a file rather than printing it.
 "#;
 
 #[derive(Debug, Deserialize)]
@@ -54,10 +91,39 @@ struct Args {
     cmd_reservecoin: bool,
     cmd_vote: bool,
     cmd_collect: bool,
+    cmd_retract: bool,
+    cmd_refresh: bool,
+    cmd_ballot: bool,
+    cmd_transfer: bool,
+    cmd_merge: bool,
     cmd_update: bool,
+    cmd_diff: bool,
+    cmd_proposal: bool,
+    cmd_add: bool,
+    cmd_list: bool,
+    cmd_show: bool,
+    cmd_governance: bool,
+    cmd_history: bool,
+    cmd_scenario: bool,
     arg_amount: u64,
     arg_dollar_amount: String,
     arg_address: String,
+    arg_title: String,
+    arg_description: String,
+    arg_min_price: String,
+    arg_max_price: String,
+    flag_network: Option<String>,
+    flag_config: Option<String>,
+    flag_proposals: String,
+    flag_contract_version: Option<String>,
+    flag_transactions: Option<String>,
+    flag_json: bool,
+    flag_csv: bool,
+    flag_steps: u64,
+    flag_all: bool,
+    flag_coin_selection: String,
+    flag_delegate: Option<String>,
+    flag_unsigned_tx: Option<String>,
 }
 
 /// A struct which holds all of the StableCoin Protocol `Scan`s
@@ -83,18 +149,22 @@ fn main() {
         .and_then(|d| d.deserialize())
         .unwrap_or_else(|e| e.exit());
 
+    // Load the protocol deployment parameters
+    let (network, config) = get_protocol_config(&args);
+
     // The user's first address in their Ergo Node wallet
     let user_address = node.wallet_addresses().unwrap()[0].clone();
 
     // Print the current status of the StableCoin Protocol
-    if args.cmd_status {
+    if args.cmd_status && !args.cmd_vote {
         // Acquiring Scans
         let scans = get_protocol_scans(&node);
         // Create `ErgUsdOraclePoolBox`
         let oracle_box =
             ErgUsdOraclePoolBox::new(&scans.oracle_pool_scan.get_box().unwrap()).unwrap();
         // Create the Bank Box
-        let bank_box = BankBox::new(&scans.bank_scan.get_box().unwrap()).unwrap();
+        let bank_box =
+            BankBox::new_with_config(&scans.bank_scan.get_box().unwrap(), &config).unwrap();
 
         println!("Circulating Status\n===================");
         println!(
@@ -109,76 +179,185 @@ fn main() {
         println!("\nPrices\n=======");
         println!(
             "AgeUSD Nominal Price: {} Ergs",
-            nano_erg_to_erg(bank_box.stablecoin_nominal_price(&oracle_box) * 100)
+            nano_erg_to_erg(bank_box.stablecoin_nominal_price(&oracle_box, &config) * 100)
         );
         println!(
             "ReserveCoin Nominal Price: {} Ergs",
-            nano_erg_to_erg(bank_box.reservecoin_nominal_price(&oracle_box))
+            nano_erg_to_erg(bank_box.reservecoin_nominal_price(&oracle_box, &config))
         );
 
         println!("\nBank Status\n============");
+        match bank_box.detect_contract_version(&config) {
+            Some(version) if version != config.contract_version => println!(
+                "Contract Version: {} (detected {} from the Bank box)",
+                config.contract_version.name(),
+                version.name()
+            ),
+            _ => println!("Contract Version: {}", config.contract_version.name()),
+        }
+        let reserve_ratio_bps = bank_box.current_reserve_ratio_bps(&oracle_box, &config);
         println!(
-            "Current Reserve Ratio: {}%",
-            bank_box.current_reserve_ratio(&oracle_box)
+            "Current Reserve Ratio: {}.{:02}%",
+            reserve_ratio_bps / 100,
+            reserve_ratio_bps % 100
         );
         println!(
             "Base Reserves: {} Ergs",
-            nano_erg_to_erg(bank_box.base_reserves())
+            nano_erg_to_erg(bank_box.base_reserves(&config))
         );
         println!(
             "AgeUSD Liabilities: {} Ergs",
-            nano_erg_to_erg(bank_box.liabilities(&oracle_box))
+            nano_erg_to_erg(bank_box.liabilities(&oracle_box, &config))
         );
         println!(
             "Equity: {} Ergs",
-            nano_erg_to_erg(bank_box.equity(&oracle_box))
+            nano_erg_to_erg(bank_box.equity(&oracle_box, &config))
         );
 
-        println!("\nAvailable To Mint\n============");
+        let solvency = bank_box.solvency_status(&oracle_box, &config);
+        println!("\nSolvency\n=========");
+        println!("Status: {}", solvency.state.name());
+        println!("Min Reserve Ratio: {}%", solvency.min_reserve_ratio);
         println!(
-            "{} AgeUSD",
-            (bank_box.num_able_to_mint_stablecoin(&oracle_box) as f64 / 100.0)
+            "Liabilities At Oracle Rate: {} Ergs",
+            nano_erg_to_erg(solvency.liabilities_at_oracle_rate)
+        );
+        println!(
+            "Reserve Shortfall: {} Ergs",
+            nano_erg_to_erg(solvency.shortfall)
+        );
+        println!(
+            "AgeUSD Redemption Haircut: {} Ergs per AgeUSD ({}%)",
+            nano_erg_to_erg(solvency.haircut_per_coin * 100),
+            solvency.haircut_bps as f64 / 100.0
         );
+
+        println!("\nAvailable To Mint\n============");
         println!(
-            "{} ReserveCoins",
-            bank_box
-                .num_able_to_mint_reservecoin(&oracle_box, node.current_block_height().unwrap())
+            "{} AgeUSD",
+            (bank_box.num_able_to_mint_stablecoin(&oracle_box, &config) as f64 / 100.0)
         );
+        let current_height = node.current_block_height().unwrap();
+        match bank_box.reservecoin_mint_capacity(&oracle_box, current_height, &config) {
+            MintCapacity::Unlimited { until_height } => {
+                println!("Unlimited ReserveCoins (cooling off period)");
+                println!(
+                    "Max reserve ratio enforcement starts at block {} ({})",
+                    until_height,
+                    get_block_time_estimator(&node)
+                        .describe_time_until_height(current_height, until_height)
+                );
+            }
+            MintCapacity::Limited(amount) => println!("{} ReserveCoins", amount),
+        }
 
         println!("\nUser Wallet Status\n======================");
         println!(
             "Node Wallet Balance: {} Ergs",
             nano_erg_to_erg(node.wallet_nano_ergs_balance().unwrap())
         );
-        let rc_boxes = get_reservecoin_boxes(&node);
-        let rc_total = ReserveCoinBox::sum_token_amount(&rc_boxes);
+        let rc_boxes = get_reservecoin_boxes(&node, &config);
+        let rc_total = ReserveCoinBox::sum_token_amount(&rc_boxes, &config);
         println!("ReserveCoins Owned: {}", rc_total);
-        let sc_boxes = get_stablecoin_boxes(&node);
-        let sc_total = StableCoinBox::sum_token_amount(&sc_boxes);
+        let sc_boxes = get_stablecoin_boxes(&node, &config);
+        let sc_total = StableCoinBox::sum_token_amount(&sc_boxes, &config);
         println!("AgeUSD Owned: ${}", sc_total as f64 / 100.0);
     }
 
     // Print out the protocol parameters
     if args.cmd_parameters {
-        println!("Minimum Box Value: {}\nMinimum Reserve Ratio: {}\nMaximum Reserve Ratio: {}\nReserveCoin Default Price: {}\nStableCoin Token ID: {}\nReserveCoin Token ID: {}\nBank NFT ID: {}\nOracle Pool NFT ID: {}\nUpdate Ballot Token ID: {}\nUpdate NFT ID: {}",
-    MIN_BOX_VALUE, MIN_RESERVE_RATIO, MAX_RESERVE_RATIO, RESERVECOIN_DEFAULT_PRICE, STABLECOIN_TOKEN_ID, RESERVECOIN_TOKEN_ID, BANK_NFT_ID, ORACLE_POOL_NFT_ID, UPDATE_BALLOT_TOKEN_ID, UPDATE_NFT_ID);
+        println!("Network: {}", network.name());
+        let base_config =
+            ProtocolConfig::preset(network).unwrap_or_else(|_| ProtocolConfig::template());
+        let differences = config.differences(&base_config);
+        let parameters = vec![
+            (
+                "Minimum Box Value",
+                "min_box_value",
+                config.min_box_value.to_string(),
+            ),
+            (
+                "Minimum Reserve Ratio",
+                "min_reserve_ratio",
+                config.min_reserve_ratio.to_string(),
+            ),
+            (
+                "Maximum Reserve Ratio",
+                "max_reserve_ratio",
+                config.max_reserve_ratio.to_string(),
+            ),
+            (
+                "Cooling Off Height",
+                "cooling_off_height",
+                config.cooling_off_height.to_string(),
+            ),
+            (
+                "Contract Version",
+                "contract_version",
+                config.contract_version.name().to_string(),
+            ),
+            ("Fee Percent", "fee_percent", config.fee_percent.to_string()),
+            ("Minimum Votes", "min_votes", config.min_votes.to_string()),
+            (
+                "Implementor Fee (Basis Points)",
+                "implementor_fee_bps",
+                config.implementor_fee_bps.to_string(),
+            ),
+            (
+                "ReserveCoin Default Price",
+                "reservecoin_default_price",
+                config.reservecoin_default_price.to_string(),
+            ),
+            (
+                "StableCoin Token ID",
+                "stablecoin_token_id",
+                config.stablecoin_token_id.clone(),
+            ),
+            (
+                "ReserveCoin Token ID",
+                "reservecoin_token_id",
+                config.reservecoin_token_id.clone(),
+            ),
+            ("Bank NFT ID", "bank_nft_id", config.bank_nft_id.clone()),
+            (
+                "Oracle Pool NFT ID",
+                "oracle_pool_nft_id",
+                config.oracle_pool_nft_id.clone(),
+            ),
+            (
+                "Update Ballot Token ID",
+                "update_ballot_token_id",
+                config.update_ballot_token_id.clone(),
+            ),
+            (
+                "Update NFT ID",
+                "update_nft_id",
+                config.update_nft_id.clone(),
+            ),
+        ];
+        for (label, field, value) in parameters {
+            match differences.iter().find(|d| d.field == field) {
+                Some(d) => println!("{}: {} (differs from preset: {})", label, value, d.expected),
+                None => println!("{}: {}", label, value),
+            }
+        }
     }
 
     // Register UTXO-set scans with the provided Ergo Node
     if args.cmd_scans && args.cmd_register {
-        let bank_scan = register_bank_scan(&node).unwrap();
+        let bank_scan = register_bank_scan(&node, &config).unwrap();
         println!("Bank Scan Registered.");
-        let user_rc_scan = register_reservecoin_scan(&node).unwrap();
+        let user_rc_scan = register_reservecoin_scan(&node, &config).unwrap();
         println!("User ReserveCoins Scan Registered.");
-        let user_sc_scan = register_stablecoin_scan(&node).unwrap();
+        let user_sc_scan = register_stablecoin_scan(&node, &config).unwrap();
         println!("User StableCoins Scan Registered.");
-        let oracle_pool_scan = register_oracle_pool_scan(&node).unwrap();
+        let oracle_pool_scan = register_oracle_pool_scan(&node, &config).unwrap();
         println!("Oracle Pool Scan Registered.");
-        let user_update_ballot_scan = register_user_ballot_token_scan(&node).unwrap();
+        let user_update_ballot_scan = register_user_ballot_token_scan(&node, &config).unwrap();
         println!("User Update Ballot Scan Registered.");
-        let all_update_ballots_scan = register_all_ballot_tokens_scan(&node).unwrap();
+        let all_update_ballots_scan = register_all_ballot_tokens_scan(&node, &config).unwrap();
         println!("All Update Ballots Scan Registered.");
-        let update_box_scan = register_update_box_scan(&node).unwrap();
+        let update_box_scan = register_update_box_scan(&node, &config).unwrap();
         println!("Update Box Scan Registered.");
 
         let scans = vec![
@@ -232,41 +411,373 @@ fn main() {
         println!("---------------------------------------------");
     }
 
+    // The strategy used to select the wallet boxes spent by Actions
+    let strategy = match SelectionStrategy::from_name(&args.flag_coin_selection) {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(0);
+        }
+    };
+
     // Mint StableCoins Action
     if args.cmd_mint && args.cmd_ageusd {
         let us_cent_amount = ((args.arg_dollar_amount.parse::<f64>().unwrap()) * 100.0) as u64;
-        mint_stablecoins(us_cent_amount, user_address.clone(), &node);
+        mint_stablecoins(
+            us_cent_amount,
+            user_address.clone(),
+            strategy,
+            &node,
+            &config,
+        );
     }
 
     // Mint ReserveCoins Action
     if args.cmd_mint && args.cmd_reservecoin {
-        mint_reservecoins(args.arg_amount, user_address.clone(), &node);
+        mint_reservecoins(
+            args.arg_amount,
+            user_address.clone(),
+            strategy,
+            &node,
+            &config,
+        );
     }
 
     // Redeem StableCoins Action
     if args.cmd_redeem && args.cmd_ageusd {
         let us_cent_amount = ((args.arg_dollar_amount.parse::<f64>().unwrap()) * 100.0) as u64;
-        redeem_stablecoins(us_cent_amount, user_address.clone(), &node);
+        redeem_stablecoins(
+            us_cent_amount,
+            user_address.clone(),
+            strategy,
+            &node,
+            &config,
+        );
     }
 
     // Redeem ReserveCoins Action
     if args.cmd_redeem && args.cmd_reservecoin {
-        redeem_reservecoins(args.arg_amount, user_address.clone(), &node);
+        redeem_reservecoins(
+            args.arg_amount,
+            user_address.clone(),
+            strategy,
+            &node,
+            &config,
+        );
     }
 
     // Collects votes and updates the `Update Box` with the results
     // of the vote.
     if args.cmd_vote && args.cmd_collect {
-        collect_votes_for_update(&args.arg_address, &user_address, &node);
+        collect_votes_for_update(&args.arg_address, &user_address, &node, &config);
+    }
+    // Print the current tally of votes for updating the protocol
+    else if args.cmd_vote && args.cmd_status {
+        let registry = load_proposal_registry(&args.flag_proposals);
+        print_vote_status(&args.arg_address, &registry, &node, &config);
+    }
+    // Retract the vote held in the user's ballot box
+    else if args.cmd_vote && args.cmd_retract {
+        retract_vote(&user_address, &node, &config);
+    }
+    // Re-cast the user's stale votes against the current Update box
+    else if args.cmd_vote && args.cmd_refresh {
+        refresh_votes(args.flag_all, &user_address, &node, &config);
+    }
+    // Build a vote for updating the protocol for a delegate to sign
+    else if args.cmd_vote && args.flag_delegate.is_some() {
+        build_delegated_vote(&args, &node, network, &config);
     }
     // Issue a vote for updating the protocol
     else if args.cmd_vote {
-        vote_for_update(&args.arg_address, &user_address, &node);
+        vote_for_update(&args.arg_address, &user_address, &node, &config);
+    }
+
+    // Transfer ballot tokens to a new holder
+    if args.cmd_ballot && args.cmd_transfer {
+        transfer_ballot(
+            &args.arg_address,
+            args.arg_amount,
+            &user_address,
+            &node,
+            &config,
+        );
+    }
+    // Merge all of the user's ballot boxes into one
+    else if args.cmd_ballot && args.cmd_merge {
+        merge_ballots(&user_address, &node, &config);
     }
 
     // Issue a vote for updating the protocol
-    if args.cmd_update {
-        update_protocol(&args.arg_address, &user_address, &node);
+    if args.cmd_update && args.cmd_diff {
+        let registry = load_proposal_registry(&args.flag_proposals);
+        print_update_diff(&args.arg_address, &registry, &node, &config);
+    } else if args.cmd_update {
+        update_protocol(&args.arg_address, &user_address, &node, &config);
+    }
+
+    // Register a new proposal for updating the protocol
+    if args.cmd_proposal && args.cmd_add {
+        add_proposal(&args);
+    }
+
+    // Print out all registered proposals
+    if args.cmd_proposal && args.cmd_list {
+        let registry = load_proposal_registry(&args.flag_proposals);
+        if registry.proposals.is_empty() {
+            println!("No proposals have been registered.");
+        }
+        for proposal in &registry.proposals {
+            println!("{}: {}", proposal.title, proposal.address_hash);
+        }
+    }
+
+    // Print out a single registered proposal
+    if args.cmd_proposal && args.cmd_show {
+        let registry = load_proposal_registry(&args.flag_proposals);
+        match registry.find(&args.arg_address) {
+            Some(proposal) => {
+                println!("Title: {}", proposal.title);
+                println!("Description: {}", proposal.description);
+                println!("Address: {}", proposal.address);
+                println!("Address Hash: {}", proposal.address_hash);
+                match proposal.contract_version {
+                    Some(version) => println!("Contract Version: {}", version.name()),
+                    None => println!("Contract Version: Unknown"),
+                }
+            }
+            None => println!("No proposal found for `{}`.", args.arg_address),
+        }
+    }
+
+    // Print out the history of governance actions on the Update box
+    if args.cmd_governance && args.cmd_history {
+        let registry = load_proposal_registry(&args.flag_proposals);
+        print_governance_history(&args, network, &config, &registry);
+    }
+
+    // Print out how the protocol fares over a range of ERG/USD prices
+    if args.cmd_scenario {
+        print_price_shock_scenario(&args, &node, &config);
+    }
+}
+
+/// Print the ERG/USD prices at which the protocol crosses its risk
+/// thresholds, and the state of the protocol over a range of prices
+fn print_price_shock_scenario(args: &Args, node: &NodeInterface, config: &ProtocolConfig) {
+    let scans = get_protocol_scans(node);
+    let oracle_box = ErgUsdOraclePoolBox::new(&scans.oracle_pool_scan.get_box().unwrap()).unwrap();
+    let bank_box = BankBox::new_with_config(&scans.bank_scan.get_box().unwrap(), config).unwrap();
+
+    // Default to a crash down to 10% of the current price
+    let current_price = rate_to_usd_price(bank_box.oracle_rate(&oracle_box, config));
+    let (min_price, max_price) = if args.arg_min_price.is_empty() {
+        (current_price * 0.1, current_price)
+    } else {
+        match (
+            args.arg_min_price.parse::<f64>(),
+            args.arg_max_price.parse::<f64>(),
+        ) {
+            (Ok(min), Ok(max)) if min > 0.0 && max >= min => (min, max),
+            _ => {
+                println!("The prices must be positive ERG/USD prices, from lowest to highest.");
+                std::process::exit(0);
+            }
+        }
+    };
+    if args.flag_steps > MAX_SWEEP_STEPS {
+        println!("At most {} steps can be evaluated.", MAX_SWEEP_STEPS);
+        std::process::exit(0);
+    }
+    let steps = std::cmp::max(args.flag_steps, 2);
+    let rates: Vec<NanoErg> = (0..steps)
+        .map(|step| {
+            let price = max_price - (max_price - min_price) * step as f64 / (steps - 1) as f64;
+            usd_price_to_rate(price)
+        })
+        .collect();
+    let report = bank_box.price_shock_report(&oracle_box, &rates, config);
+
+    if args.flag_json {
+        match report.to_json() {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("Could not serialize the scenario.\nError: {}", e),
+        }
+        return;
+    }
+    if args.flag_csv {
+        println!("{}", report.to_csv());
+        return;
+    }
+
+    let thresholds = report.thresholds;
+    let describe = |rate: Option<NanoErg>| match rate {
+        Some(rate) => format!("${:.4} (oracle rate {})", rate_to_usd_price(rate), rate),
+        None => "Never (no AgeUSD circulating)".to_string(),
+    };
+    println!("Price Shock Thresholds\n======================");
+    println!("Current ERG/USD Price: ${:.4}", current_price);
+    println!(
+        "Reserve Ratio Reaches {}%: {}",
+        thresholds.min_reserve_ratio,
+        describe(thresholds.min_reserve_ratio_rate)
+    );
+    println!(
+        "Reserve Ratio Reaches 100%: {}",
+        describe(thresholds.full_backing_rate)
+    );
+    println!(
+        "ReserveCoin Equity Reaches Zero: {}",
+        describe(thresholds.zero_equity_rate)
+    );
+
+    println!("\nScenarios\n=========");
+    println!(
+        "{:<12} {:<16} {:<22} {:<26} Equity (Ergs)",
+        "ERG/USD", "Reserve Ratio", "AgeUSD Price (Ergs)", "ReserveCoin Price (Ergs)"
+    );
+    for point in &report.points {
+        println!(
+            "{:<12} {:<16} {:<22} {:<26} {}",
+            format!("${:.4}", rate_to_usd_price(point.oracle_rate)),
+            format!(
+                "{}.{:02}%",
+                point.reserve_ratio_bps / 100,
+                point.reserve_ratio_bps % 100
+            ),
+            nano_erg_to_erg(point.stablecoin_nominal_price * 100),
+            nano_erg_to_erg(point.reservecoin_nominal_price),
+            nano_erg_to_erg(point.equity)
+        );
+    }
+}
+
+/// The ERG/USD price of an oracle rate, being the nanoErg price of 1 cent
+fn rate_to_usd_price(rate: NanoErg) -> f64 {
+    if rate == 0 {
+        return 0.0;
+    }
+    10_000_000.0 / rate as f64
+}
+
+/// The oracle rate (the nanoErg price of 1 cent) of an ERG/USD price
+fn usd_price_to_rate(price: f64) -> NanoErg {
+    (10_000_000.0 / price) as NanoErg
+}
+
+/// Print the timeline of every move of the Update box
+fn print_governance_history(
+    args: &Args,
+    network: Network,
+    config: &ProtocolConfig,
+    registry: &ProposalRegistry,
+) {
+    let mut builder = GovernanceHistoryBuilder::new(config);
+    let res = match &args.flag_transactions {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                builder
+                    .add_transactions_json(&json)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }),
+        None => fetch_boxes::fetch_update_box_transactions(&config.update_nft_id, network)
+            .and_then(|transactions| {
+                transactions.iter().try_for_each(|json| {
+                    builder
+                        .add_transaction_json(json)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+            }),
+    };
+    if let Err(e) = res {
+        println!("Could not read the governance transactions.\nError: {}", e);
+        std::process::exit(0);
+    }
+    let history = builder.build();
+
+    if args.flag_json {
+        match history.to_json() {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("Could not serialize the governance history.\nError: {}", e),
+        }
+        return;
+    }
+
+    if history.events.is_empty() {
+        println!("No governance transactions were found.");
+        return;
+    }
+    println!(
+        "{:<10} {:<20} {:<14} {:<66} Address Hash",
+        "Height", "Event", "Voting Power", "Transaction"
+    );
+    for event in &history.events {
+        let height = event
+            .height
+            .map(|h| h.to_string())
+            .unwrap_or("-".to_string());
+        println!(
+            "{:<10} {:<20} {:<14} {:<66} {}",
+            height,
+            event.kind.name(),
+            event.voting_power,
+            event.tx_id,
+            registry.describe_hash(&event.address_hash)
+        );
+        if !event.unresolved_data_inputs.is_empty() {
+            println!(
+                "           {} ballot data-inputs could not be resolved.",
+                event.unresolved_data_inputs.len()
+            );
+        }
+    }
+}
+
+/// Register a new proposal in the local proposal registry
+fn add_proposal(args: &Args) {
+    let mut registry = load_proposal_registry(&args.flag_proposals);
+    let contract_version = match args
+        .flag_contract_version
+        .as_deref()
+        .map(ContractVersion::from_name)
+    {
+        Some(Ok(version)) => Some(version),
+        Some(Err(e)) => {
+            println!("{}", e);
+            std::process::exit(0);
+        }
+        None => None,
+    };
+    let registered = registry
+        .register(
+            &args.arg_address,
+            &args.arg_title,
+            &args.arg_description,
+            contract_version,
+        )
+        .map(|p| p.address_hash.clone());
+    let res = registered.and_then(|hash| {
+        registry
+            .save_to_file(Path::new(&args.flag_proposals))
+            .map(|_| hash)
+    });
+    match res {
+        Ok(hash) => println!("Proposal registered with address hash: {}", hash),
+        Err(e) => println!("Could not register the proposal.\nError: {}", e),
+    }
+}
+
+/// Load the local proposal registry, exiting if the file is invalid
+fn load_proposal_registry(path: &str) -> ProposalRegistry {
+    match ProposalRegistry::load_from_file(Path::new(path)) {
+        Ok(registry) => registry,
+        Err(e) => {
+            println!("Could not load the proposal registry.\nError: {}", e);
+            std::process::exit(0);
+        }
     }
 }
 
@@ -275,22 +786,24 @@ fn update_protocol(
     update_address: &P2SAddressString,
     user_address: &P2PKAddressString,
     node: &NodeInterface,
+    config: &ProtocolConfig,
 ) -> String {
     // Acquiring Scans
-    let scans = get_protocol_scans(&node);
+    let scans = get_protocol_scans(node);
     // Acquire protocol
-    let protocol = StableCoinProtocol::new();
+    let protocol = StableCoinProtocol::new_with_config(config);
 
     // Creating the Update Box
-    let update_box = UpdateBox::new(&scans.update_box_scan.get_box().unwrap()).unwrap();
+    let update_box =
+        UpdateBox::new_with_config(&scans.update_box_scan.get_box().unwrap(), config).unwrap();
     // Create the Bank Box
-    let bank_box = BankBox::new(&scans.bank_scan.get_box().unwrap()).unwrap();
+    let bank_box = BankBox::new_with_config(&scans.bank_scan.get_box().unwrap(), config).unwrap();
 
     // Specify the tx fee
     let transaction_fee = 2000000;
 
     // Get a box for the transaction fee
-    let ergs_box_for_fee = ErgsBox::new(&node.highest_value_unspent_box().unwrap()).unwrap();
+    let ergs_box_for_fee = get_ergs_box_for_fee(transaction_fee, node, config);
 
     let unsigned_tx = protocol
         .action_update_protocol(
@@ -311,47 +824,221 @@ fn update_protocol(
     tx_id
 }
 
+/// Print the differences between the deployed Bank contract and the
+/// proposed update address
+fn print_update_diff(
+    update_address: &P2SAddressString,
+    registry: &ProposalRegistry,
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) {
+    // Acquiring Scans
+    let scans = get_protocol_scans(node);
+
+    // Creating the Update Box
+    let update_box =
+        UpdateBox::new_with_config(&scans.update_box_scan.get_box().unwrap(), config).unwrap();
+    // Create the Bank Box
+    let bank_box = BankBox::new_with_config(&scans.bank_scan.get_box().unwrap(), config).unwrap();
+
+    let diff = match BankContractDiff::new(
+        &bank_box,
+        update_address,
+        Some(&update_box),
+        node.current_block_height().unwrap(),
+        config,
+    ) {
+        Ok(diff) => diff,
+        Err(e) => {
+            println!("Could not diff the proposed update.\nError: {}", e);
+            return;
+        }
+    };
+
+    let version_names = |versions: &Vec<ContractVersion>| {
+        if versions.is_empty() {
+            return "Unknown".to_string();
+        }
+        versions
+            .iter()
+            .map(|v| v.name())
+            .collect::<Vec<&str>>()
+            .join("/")
+    };
+
+    println!("Update Diff\n============");
+    println!(
+        "Proposed Address: {}",
+        registry.describe_hash(&diff.proposed_address_hash)
+    );
+    println!(
+        "Contract Version: {} -> {}",
+        version_names(&diff.current_versions),
+        version_names(&diff.proposed_versions)
+    );
+    println!(
+        "Script Size: {} -> {} bytes ({:+})",
+        diff.current_script_size,
+        diff.proposed_script_size,
+        diff.script_size_change()
+    );
+
+    println!("\nChanged Constants\n==================");
+    match &diff.constant_changes {
+        None => println!("The constant segments of the contracts could not be read."),
+        Some(changes) if changes.is_empty() => println!("No constants changed."),
+        Some(changes) => {
+            for change in changes {
+                let show = |c: &Option<TreeConstant>| match c {
+                    Some(c) => format!("{}", c),
+                    None => "-".to_string(),
+                };
+                println!(
+                    "#{} {}: {} -> {}",
+                    change.index,
+                    change.parameter.as_deref().unwrap_or("unknown"),
+                    show(&change.current),
+                    show(&change.proposed)
+                );
+            }
+        }
+    }
+
+    let check = diff.update_path;
+    let yes_no = |b: bool| if b { "Yes" } else { "No" };
+    println!("\nUpdate Path\n============");
+    println!("Preserves Bank NFT: {}", yes_no(check.preserves_bank_nft));
+    println!("Preserves Tokens: {}", yes_no(check.preserves_tokens));
+    println!("Preserves R4/R5: {}", yes_no(check.preserves_registers));
+    println!("Preserves Value: {}", yes_no(check.preserves_value));
+    println!("Changes Contract: {}", yes_no(check.changes_proposition));
+    if let Some(matches) = check.matches_update_box {
+        println!("Collected In Update Box: {}", yes_no(matches));
+    }
+    if check.is_valid() {
+        println!("The Update contract would accept this update.");
+    } else {
+        println!("The Update contract would reject this update.");
+    }
+}
+
+/// Print the tally of the votes for an Update
+fn print_vote_status(
+    address: &P2SAddressString,
+    registry: &ProposalRegistry,
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) {
+    // Acquiring Scans
+    let scans = get_protocol_scans(node);
+
+    // Creating the Update Box
+    let update_box =
+        UpdateBox::new_with_config(&scans.update_box_scan.get_box().unwrap(), config).unwrap();
+    // Acquire all of the Ballots
+    let ballot_boxes = get_all_ballot_boxes(&scans, config);
+
+    let tally = VoteTally::new(&ballot_boxes, &update_box, config);
+    println!("Vote Status\n============");
+    println!("Update Box Id: {}", tally.update_box_id);
+    println!(
+        "Current Address: {}",
+        registry.describe_hash(&tally.current_address_hash)
+    );
+    println!("Votes Required: {}", tally.min_votes);
+    println!("Total Counted Votes: {}", tally.total_voting_power);
+    println!(
+        "Stale Ballots: {} ({} votes)",
+        tally.stale_ballots.len(),
+        tally.stale_voting_power
+    );
+
+    let user_stale_ballots = stale_ballots(&get_user_ballot_boxes(&scans, config), &update_box);
+    if !user_stale_ballots.is_empty() {
+        println!(
+            "{} of your Ballot boxes hold a stale vote which is no longer counted. Use `vote refresh --all` to re-cast them.",
+            user_stale_ballots.len()
+        );
+    }
+
+    println!("\nCandidates\n===========");
+    if tally.candidates.is_empty() {
+        println!("No votes have been cast for the current Update Box.");
+    }
+    for candidate in &tally.candidates {
+        let quorum = if candidate.voting_power >= tally.min_votes {
+            "quorum reached"
+        } else {
+            "below quorum"
+        };
+        println!(
+            "{}: {} votes from {} ballots ({})",
+            registry.describe_hash(&candidate.address_hash),
+            candidate.voting_power,
+            candidate.num_ballots,
+            quorum
+        );
+    }
+
+    if !address.is_empty() {
+        println!("\nAddress\n========");
+        match (
+            tally.votes_for_address(address),
+            tally.votes_needed(address),
+        ) {
+            (Ok(votes), Ok(needed)) => {
+                println!("Votes: {}", votes);
+                println!("Votes Still Needed: {}", needed);
+            }
+            (Err(e), _) | (_, Err(e)) => println!("Invalid address: {}", e),
+        }
+        match tally.verify_collectable(address) {
+            Ok(()) => println!("Collecting votes would succeed."),
+            Err(e) => println!("Collecting votes would fail: {}", e),
+        }
+    }
+}
+
 /// Collect Votes for an Update
 fn collect_votes_for_update(
     address_voted_for: &P2SAddressString,
     user_address: &P2PKAddressString,
     node: &NodeInterface,
+    config: &ProtocolConfig,
 ) -> String {
     // Acquiring Scans
-    let scans = get_protocol_scans(&node);
+    let scans = get_protocol_scans(node);
     // Acquire protocol
-    let protocol = StableCoinProtocol::new();
+    let protocol = StableCoinProtocol::new_with_config(config);
 
     // Creating the Update Box
-    let update_box = UpdateBox::new(&scans.update_box_scan.get_box().unwrap()).unwrap();
+    let update_box =
+        UpdateBox::new_with_config(&scans.update_box_scan.get_box().unwrap(), config).unwrap();
 
     // Acquire all of the Ballots
-    let all_boxes = scans.all_update_ballots_scan.get_boxes().unwrap();
-    // Filter out all non-valid `BallotBox`es
-    let mut filtered_ballot_boxes: Vec<BallotBox> = vec![];
-    for b in all_boxes {
-        if let Ok(bb) = BallotBox::new(&b) {
-            filtered_ballot_boxes.push(bb)
-        }
-    }
-
-    // Get a box for the transaction fee
-    let ergs_box_for_fee = ErgsBox::new(&node.highest_value_unspent_box().unwrap()).unwrap();
+    let filtered_ballot_boxes = get_all_ballot_boxes(&scans, config);
 
     // Specify the tx fee
     let transaction_fee = 2000000;
 
-    let unsigned_tx = protocol
-        .action_collect_votes(
-            address_voted_for,
-            &filtered_ballot_boxes,
-            &update_box,
-            node.current_block_height().unwrap(),
-            transaction_fee,
-            &ergs_box_for_fee,
-            user_address,
-        )
-        .unwrap();
+    // Get a box for the transaction fee
+    let ergs_box_for_fee = get_ergs_box_for_fee(transaction_fee, node, config);
+
+    let unsigned_tx = match protocol.action_collect_votes(
+        address_voted_for,
+        &filtered_ballot_boxes,
+        &update_box,
+        node.current_block_height().unwrap(),
+        transaction_fee,
+        &ergs_box_for_fee,
+        user_address,
+    ) {
+        Ok(unsigned_tx) => unsigned_tx,
+        Err(e) => {
+            println!("Could not collect the votes.\nError: {}", e);
+            std::process::exit(0);
+        }
+    };
 
     let tx_id = node.sign_and_submit_transaction(&unsigned_tx).unwrap();
 
@@ -365,79 +1052,349 @@ fn vote_for_update(
     address_to_vote_for: &P2SAddressString,
     user_address: &P2PKAddressString,
     node: &NodeInterface,
+    config: &ProtocolConfig,
 ) -> String {
     // Acquiring Scans
-    let scans = get_protocol_scans(&node);
+    let scans = get_protocol_scans(node);
     // Acquire protocol
-    let protocol = StableCoinProtocol::new();
+    let protocol = StableCoinProtocol::new_with_config(config);
 
-    // Fetch the user's "BallotBox"
-    // let user_ballot_box = fetch_user_ballot_box(user_address);
-    let user_ballot_box =
-        BallotBox::new(&scans.user_update_ballot_scan.get_box().unwrap()).unwrap();
+    // Acquire every "BallotBox" held by the addresses of the wallet
+    let user_ballot_boxes = get_user_ballot_boxes(&scans, config);
+    if user_ballot_boxes.is_empty() {
+        println!("No valid Ballot box was found in the wallet.");
+        std::process::exit(0);
+    }
 
     // Fetch the "UpdateBox"
     // let update_box = fetch_update_box();
     // Creating the Update Box
-    let update_box = UpdateBox::new(&scans.update_box_scan.get_box().unwrap()).unwrap();
+    let update_box =
+        UpdateBox::new_with_config(&scans.update_box_scan.get_box().unwrap(), config).unwrap();
+
+    // Specify the tx fee
+    let transaction_fee = 2000000;
 
     // Get a box for the transaction fee
-    let ergs_box_for_fee = ErgsBox::new(&node.highest_value_unspent_box().unwrap()).unwrap();
+    let ergs_box_for_fee = get_ergs_box_for_fee(transaction_fee, node, config);
+
+    let unsigned_tx = match protocol.action_vote_for_update_multiple(
+        address_to_vote_for,
+        &user_ballot_boxes,
+        &update_box,
+        node.current_block_height().unwrap(),
+        transaction_fee,
+        &ergs_box_for_fee,
+        user_address,
+    ) {
+        Ok(unsigned_tx) => unsigned_tx,
+        Err(e) => {
+            println!("Could not vote for the update.\nError: {}", e);
+            std::process::exit(0);
+        }
+    };
+
+    let tx_id = node.sign_and_submit_transaction(&unsigned_tx).unwrap();
+
+    println!("Voted with {} Ballot boxes.", user_ballot_boxes.len());
+    println!("Vote For Update Tx Id: {}", tx_id);
+
+    tx_id
+}
+
+/// Build a vote for updating the protocol with every Ballot box held by
+/// the delegate. The fee is paid from the delegate's own boxes, so the
+/// unsigned transaction only needs to be signed by the delegate.
+fn build_delegated_vote(
+    args: &Args,
+    node: &NodeInterface,
+    network: Network,
+    config: &ProtocolConfig,
+) {
+    let delegate_address = args.flag_delegate.clone().unwrap_or_default();
+    // Acquiring Scans
+    let scans = get_protocol_scans(node);
+    // Acquire protocol
+    let protocol = StableCoinProtocol::new_with_config(config);
+
+    // Acquire the Ballot boxes held by the delegate
+    let delegate_ballot_boxes: Vec<BallotBox> = get_all_ballot_boxes(&scans, config)
+        .into_iter()
+        .filter(|bb| bb.holder_address() == delegate_address)
+        .collect();
+    if delegate_ballot_boxes.is_empty() {
+        println!("No valid Ballot box is held by `{}`.", delegate_address);
+        std::process::exit(0);
+    }
+
+    // Creating the Update Box
+    let update_box =
+        UpdateBox::new_with_config(&scans.update_box_scan.get_box().unwrap(), config).unwrap();
 
     // Specify the tx fee
     let transaction_fee = 2000000;
 
-    let unsigned_tx = protocol
-        .action_vote_for_update(
-            address_to_vote_for.clone(),
-            user_ballot_box,
-            update_box,
-            node.current_block_height().unwrap(),
-            transaction_fee,
-            ergs_box_for_fee,
-            user_address.clone(),
-        )
-        .unwrap();
+    // Get a box of the delegate for the transaction fee
+    let delegate_boxes = match fetch_boxes::fetch_ergs_boxes(&delegate_address, network) {
+        Ok(boxes) => boxes,
+        Err(e) => {
+            println!(
+                "Could not fetch the boxes held by `{}`.\nError: {}",
+                delegate_address, e
+            );
+            std::process::exit(0);
+        }
+    };
+    let ergs_box_for_fee = match delegate_boxes.into_iter().max_by_key(|b| b.nano_ergs()) {
+        Some(ergs_box) => ergs_box,
+        None => {
+            println!("No box held by `{}` can pay the fee.", delegate_address);
+            std::process::exit(0);
+        }
+    };
+
+    let unsigned_tx = match protocol.action_vote_for_update_multiple(
+        &args.arg_address,
+        &delegate_ballot_boxes,
+        &update_box,
+        node.current_block_height().unwrap(),
+        transaction_fee,
+        &ergs_box_for_fee,
+        &delegate_address,
+    ) {
+        Ok(unsigned_tx) => unsigned_tx,
+        Err(e) => {
+            println!("Could not build the delegated vote.\nError: {}", e);
+            std::process::exit(0);
+        }
+    };
+
+    let tx_json = serde_json::to_string_pretty(&unsigned_tx).unwrap();
+    match &args.flag_unsigned_tx {
+        Some(path) => match std::fs::write(path, tx_json) {
+            Ok(_) => println!("Unsigned Vote For Update Tx written to: {}", path),
+            Err(e) => println!("Could not write the unsigned transaction.\nError: {}", e),
+        },
+        None => println!("{}", tx_json),
+    }
+}
+
+/// Retract the vote held in the user's Ballot box
+fn retract_vote(
+    user_address: &P2PKAddressString,
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) -> String {
+    // Acquiring Scans
+    let scans = get_protocol_scans(node);
+    // Acquire protocol
+    let protocol = StableCoinProtocol::new_with_config(config);
+
+    let user_ballot_box = get_user_ballot_box(&scans, config);
+
+    // Specify the tx fee
+    let transaction_fee = 2000000;
+
+    // Get a box for the transaction fee
+    let ergs_box_for_fee = get_ergs_box_for_fee(transaction_fee, node, config);
+
+    let unsigned_tx = match protocol.action_retract_vote(
+        &user_ballot_box,
+        node.current_block_height().unwrap(),
+        transaction_fee,
+        &ergs_box_for_fee,
+        user_address,
+    ) {
+        Ok(unsigned_tx) => unsigned_tx,
+        Err(e) => {
+            println!("Could not retract the vote.\nError: {}", e);
+            std::process::exit(0);
+        }
+    };
 
     let tx_id = node.sign_and_submit_transaction(&unsigned_tx).unwrap();
 
-    println!("Vote For Update Tx Id: {}", tx_id);
+    println!("Retract Vote Tx Id: {}", tx_id);
+
+    tx_id
+}
+
+/// Re-cast the stale votes of the user's Ballot boxes against the current
+/// Update box. Only the first stale Ballot box is refreshed unless `all`.
+fn refresh_votes(
+    all: bool,
+    user_address: &P2PKAddressString,
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) -> String {
+    // Acquiring Scans
+    let scans = get_protocol_scans(node);
+    // Acquire protocol
+    let protocol = StableCoinProtocol::new_with_config(config);
+
+    // Creating the Update Box
+    let update_box =
+        UpdateBox::new_with_config(&scans.update_box_scan.get_box().unwrap(), config).unwrap();
+
+    let mut user_stale_ballots = stale_ballots(&get_user_ballot_boxes(&scans, config), &update_box);
+    if !all {
+        user_stale_ballots.truncate(1);
+    }
+
+    // Specify the tx fee
+    let transaction_fee = 2000000;
+
+    // Get a box for the transaction fee
+    let ergs_box_for_fee = get_ergs_box_for_fee(transaction_fee, node, config);
+
+    let unsigned_tx = match protocol.action_refresh_votes(
+        &user_stale_ballots,
+        &update_box,
+        node.current_block_height().unwrap(),
+        transaction_fee,
+        &ergs_box_for_fee,
+        user_address,
+    ) {
+        Ok(unsigned_tx) => unsigned_tx,
+        Err(e) => {
+            println!("Could not refresh the votes.\nError: {}", e);
+            std::process::exit(0);
+        }
+    };
+
+    let tx_id = node.sign_and_submit_transaction(&unsigned_tx).unwrap();
+
+    println!("Refreshed {} Ballot boxes.", user_stale_ballots.len());
+    println!("Refresh Votes Tx Id: {}", tx_id);
+
+    tx_id
+}
+
+/// Transfer ballot tokens from the user's Ballot box to a new holder
+fn transfer_ballot(
+    new_holder_address: &P2PKAddressString,
+    amount: u64,
+    user_address: &P2PKAddressString,
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) -> String {
+    // Acquiring Scans
+    let scans = get_protocol_scans(node);
+    // Acquire protocol
+    let protocol = StableCoinProtocol::new_with_config(config);
+
+    let user_ballot_box = get_user_ballot_box(&scans, config);
+
+    // Specify the tx fee
+    let transaction_fee = 2000000;
+
+    // Get a box for the transaction fee
+    let ergs_box_for_fee = get_ergs_box_for_fee(transaction_fee, node, config);
+
+    let unsigned_tx = match protocol.action_transfer_ballot(
+        &user_ballot_box,
+        amount,
+        new_holder_address,
+        node.current_block_height().unwrap(),
+        transaction_fee,
+        &ergs_box_for_fee,
+        user_address,
+    ) {
+        Ok(unsigned_tx) => unsigned_tx,
+        Err(e) => {
+            println!("Could not transfer the ballot tokens.\nError: {}", e);
+            std::process::exit(0);
+        }
+    };
+
+    let tx_id = node.sign_and_submit_transaction(&unsigned_tx).unwrap();
+
+    println!("Transfer Ballot Tx Id: {}", tx_id);
+
+    tx_id
+}
+
+/// Merge all of the user's Ballot boxes into a single Ballot box
+fn merge_ballots(
+    user_address: &P2PKAddressString,
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) -> String {
+    // Acquiring Scans
+    let scans = get_protocol_scans(node);
+    // Acquire protocol
+    let protocol = StableCoinProtocol::new_with_config(config);
+
+    let user_ballot_boxes = get_user_ballot_boxes(&scans, config);
+
+    // Specify the tx fee
+    let transaction_fee = 2000000;
+
+    // Get a box for the transaction fee
+    let ergs_box_for_fee = get_ergs_box_for_fee(transaction_fee, node, config);
+
+    let unsigned_tx = match protocol.action_merge_ballots(
+        &user_ballot_boxes,
+        node.current_block_height().unwrap(),
+        transaction_fee,
+        &ergs_box_for_fee,
+        user_address,
+    ) {
+        Ok(unsigned_tx) => unsigned_tx,
+        Err(e) => {
+            println!("Could not merge the ballot boxes.\nError: {}", e);
+            std::process::exit(0);
+        }
+    };
+
+    let tx_id = node.sign_and_submit_transaction(&unsigned_tx).unwrap();
+
+    println!("Merge Ballots Tx Id: {}", tx_id);
 
     tx_id
 }
 
 /// Mint ReserveCoins
-fn mint_reservecoins(amount: u64, user_address: P2PKAddressString, node: &NodeInterface) -> String {
+fn mint_reservecoins(
+    amount: u64,
+    user_address: P2PKAddressString,
+    strategy: SelectionStrategy,
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) -> String {
     println!("Minting ReserveCoins");
     // Acquiring Scans
-    let scans = get_protocol_scans(&node);
+    let scans = get_protocol_scans(node);
     // Acquire protocol
-    let protocol = StableCoinProtocol::new();
+    let protocol = StableCoinProtocol::new_with_config(config);
 
     // Create `ErgUsdOraclePoolBox`
     let oracle_box = ErgUsdOraclePoolBox::new(&scans.oracle_pool_scan.get_box().unwrap()).unwrap();
     // Create the Bank Box
-    let bank_box = BankBox::new(&scans.bank_scan.get_box().unwrap()).unwrap();
+    let bank_box = BankBox::new_with_config(&scans.bank_scan.get_box().unwrap(), config).unwrap();
 
     // Specify the tx fee
     let transaction_fee = 2000000;
     // Calculate how many nanoErgs required
     let nano_ergs_required =
-        bank_box.total_cost_to_mint_reservecoin(amount, &oracle_box, transaction_fee);
-    // Select boxes that cover the minimum required nanoErgs
-    let ergs_boxes = get_ergs_boxes_to_cover(nano_ergs_required, &node);
+        bank_box.total_cost_to_mint_reservecoin(amount, &oracle_box, transaction_fee, config);
+    println!("Ergs required: {}", nano_erg_to_erg(nano_ergs_required));
 
-    // Creating the unsigned tx
+    // Creating the unsigned tx, selecting the wallet boxes to spend
     let unsigned_tx = protocol
-        .action_mint_reservecoin(
+        .action_mint_reservecoin_from_wallet(
             amount,
             user_address,
             transaction_fee,
             node.current_block_height().unwrap(),
             &oracle_box,
             &bank_box,
-            &ergs_boxes,
+            &node.unspent_boxes().unwrap(),
+            strategy,
             "9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r".to_string(),
+            Some(nano_ergs_required),
+            None,
+            None,
         )
         .unwrap();
 
@@ -449,37 +1406,46 @@ fn mint_reservecoins(amount: u64, user_address: P2PKAddressString, node: &NodeIn
 }
 
 /// Mint StableCoins
-fn mint_stablecoins(amount: u64, user_address: P2PKAddressString, node: &NodeInterface) -> String {
+fn mint_stablecoins(
+    amount: u64,
+    user_address: P2PKAddressString,
+    strategy: SelectionStrategy,
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) -> String {
     println!("Minting StableCoins");
     // Acquiring Scans
-    let scans = get_protocol_scans(&node);
+    let scans = get_protocol_scans(node);
     // Acquire protocol
-    let protocol = StableCoinProtocol::new();
+    let protocol = StableCoinProtocol::new_with_config(config);
 
     // Create `ErgUsdOraclePoolBox`
     let oracle_box = ErgUsdOraclePoolBox::new(&scans.oracle_pool_scan.get_box().unwrap()).unwrap();
     // Create the Bank Box
-    let bank_box = BankBox::new(&scans.bank_scan.get_box().unwrap()).unwrap();
+    let bank_box = BankBox::new_with_config(&scans.bank_scan.get_box().unwrap(), config).unwrap();
 
     // Specify the tx fee
     let transaction_fee = 2000000;
     // Calculate how many nanoErgs required
     let nano_ergs_required =
-        bank_box.total_cost_to_mint_stablecoin(amount, &oracle_box, transaction_fee);
-    // Select boxes that cover the minimum required nanoErgs
-    let ergs_boxes = get_ergs_boxes_to_cover(nano_ergs_required, &node);
+        bank_box.total_cost_to_mint_stablecoin(amount, &oracle_box, transaction_fee, config);
+    println!("Ergs required: {}", nano_erg_to_erg(nano_ergs_required));
 
-    // Creating the unsigned tx
+    // Creating the unsigned tx, selecting the wallet boxes to spend
     let unsigned_tx = protocol
-        .action_mint_stablecoin(
+        .action_mint_stablecoin_from_wallet(
             amount,
             user_address,
             transaction_fee,
             node.current_block_height().unwrap(),
             &oracle_box,
             &bank_box,
-            &ergs_boxes,
+            &node.unspent_boxes().unwrap(),
+            strategy,
             "9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r".to_string(),
+            Some(nano_ergs_required),
+            None,
+            None,
         )
         .unwrap();
 
@@ -494,30 +1460,36 @@ fn mint_stablecoins(amount: u64, user_address: P2PKAddressString, node: &NodeInt
 fn redeem_reservecoins(
     amount: u64,
     user_address: P2PKAddressString,
+    strategy: SelectionStrategy,
     node: &NodeInterface,
+    config: &ProtocolConfig,
 ) -> String {
     println!("Redeeming ReserveCoins");
     // Acquiring Scans
-    let scans = get_protocol_scans(&node);
+    let scans = get_protocol_scans(node);
     // Acquire protocol
-    let protocol = StableCoinProtocol::new();
+    let protocol = StableCoinProtocol::new_with_config(config);
 
     // Create `ErgUsdOraclePoolBox`
     let oracle_box = ErgUsdOraclePoolBox::new(&scans.oracle_pool_scan.get_box().unwrap()).unwrap();
     // Create the Bank Box
-    let bank_box = BankBox::new(&scans.bank_scan.get_box().unwrap()).unwrap();
+    let bank_box = BankBox::new_with_config(&scans.bank_scan.get_box().unwrap(), config).unwrap();
 
     // Creating the unsigned tx
     let unsigned_tx = protocol
-        .action_redeem_reservecoin(
+        .action_redeem_reservecoin_from_wallet(
             amount,
             user_address,
             2000000,
             node.current_block_height().unwrap(),
             &oracle_box,
             &bank_box,
-            &get_reservecoin_boxes(node),
+            &node.unspent_boxes().unwrap(),
+            strategy,
             "9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r".to_string(),
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -532,30 +1504,36 @@ fn redeem_reservecoins(
 fn redeem_stablecoins(
     amount: u64,
     user_address: P2PKAddressString,
+    strategy: SelectionStrategy,
     node: &NodeInterface,
+    config: &ProtocolConfig,
 ) -> String {
     println!("Redeeming StableCoins");
     // Acquiring Scans
-    let scans = get_protocol_scans(&node);
+    let scans = get_protocol_scans(node);
     // Acquire protocol
-    let protocol = StableCoinProtocol::new();
+    let protocol = StableCoinProtocol::new_with_config(config);
 
     // Create `ErgUsdOraclePoolBox`
     let oracle_box = ErgUsdOraclePoolBox::new(&scans.oracle_pool_scan.get_box().unwrap()).unwrap();
     // Create the Bank Box
-    let bank_box = BankBox::new(&scans.bank_scan.get_box().unwrap()).unwrap();
+    let bank_box = BankBox::new_with_config(&scans.bank_scan.get_box().unwrap(), config).unwrap();
 
     // Creating the unsigned tx
     let unsigned_tx = protocol
-        .action_redeem_stablecoin(
+        .action_redeem_stablecoin_from_wallet(
             amount,
             user_address,
             2000000,
             node.current_block_height().unwrap(),
             &oracle_box,
             &bank_box,
-            &get_stablecoin_boxes(node),
+            &node.unspent_boxes().unwrap(),
+            strategy,
             "9iHyKxXs2ZNLMp9N9gbUT9V8gTbsV7HED1C1VhttMfBUMPDyF7r".to_string(),
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -566,6 +1544,35 @@ fn redeem_stablecoins(
     tx_id
 }
 
+/// Small error checking function for acquiring the `ProtocolConfig`.
+/// Starts from the preset of the network chosen via `--network` and
+/// overrides it with the file provided via `--config`. Networks without a
+/// preset start from the v0.4 template and require the file.
+fn get_protocol_config(args: &Args) -> (Network, ProtocolConfig) {
+    let network_name = args.flag_network.as_deref().unwrap_or("mainnet");
+    let network = match Network::from_name(network_name) {
+        Ok(network) => network,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(0);
+        }
+    };
+    let config = match (ProtocolConfig::preset(network), &args.flag_config) {
+        (Ok(preset), Some(path)) => preset.overlay_file(Path::new(path)),
+        (Ok(preset), None) => Ok(preset),
+        (Err(_), Some(path)) => ProtocolConfig::template().overlay_file(Path::new(path)),
+        (Err(e), None) => Err(e),
+    };
+    let config = match config.and_then(|c| c.validate().map(|_| c)) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("Could not load the protocol config.\nError: {}", e);
+            std::process::exit(0);
+        }
+    };
+    (network, config)
+}
+
 /// Small error checking function for acquiring data for a `NodeInterface` /
 /// from a local file.
 fn get_node_interface() -> NodeInterface {
@@ -590,7 +1597,7 @@ fn get_node_interface() -> NodeInterface {
 /// Small error checking function for acquiring `Scan`s
 /// by using the IDs from a local file.
 fn get_protocol_scans(node: &NodeInterface) -> ProtocolScans {
-    let res = Scan::read_local_scan_ids(&node);
+    let res = Scan::read_local_scan_ids(node);
     if let Err(e) = res {
         println!(
             "An error has occurred while attempting to retrieve the UTXO-set Scans: {:?}",
@@ -600,7 +1607,7 @@ fn get_protocol_scans(node: &NodeInterface) -> ProtocolScans {
     }
     let all_scans = res.unwrap();
 
-    return ProtocolScans {
+    ProtocolScans {
         bank_scan: all_scans[0].clone(),
         user_reservecoins_scan: all_scans[1].clone(),
         user_stablecoins_scan: all_scans[2].clone(),
@@ -608,46 +1615,94 @@ fn get_protocol_scans(node: &NodeInterface) -> ProtocolScans {
         user_update_ballot_scan: all_scans[4].clone(),
         all_update_ballots_scan: all_scans[5].clone(),
         update_box_scan: all_scans[6].clone(),
-    };
+    }
 }
 
 /// Acquire all of the boxes holding ReserveCoins
-pub fn get_reservecoin_boxes(node: &NodeInterface) -> Vec<ReserveCoinBox> {
+pub fn get_reservecoin_boxes(node: &NodeInterface, config: &ProtocolConfig) -> Vec<ReserveCoinBox> {
     // Acquiring Scans
-    let scans = get_protocol_scans(&node);
+    let scans = get_protocol_scans(node);
     // Acquire ReserveCoin boxes from user's wallet
     let boxes = scans.user_reservecoins_scan.get_boxes().unwrap();
-    let reservecoin_boxes = boxes
-        .into_iter()
-        .map(|b| ReserveCoinBox::new(&b).unwrap())
-        .collect();
 
-    reservecoin_boxes
+    boxes
+        .into_iter()
+        .filter_map(|b| ReserveCoinBox::new_with_config(&b, config).ok())
+        .collect()
 }
 
 /// Acquire all of the boxes holding StableCoins
-pub fn get_stablecoin_boxes(node: &NodeInterface) -> Vec<StableCoinBox> {
+pub fn get_stablecoin_boxes(node: &NodeInterface, config: &ProtocolConfig) -> Vec<StableCoinBox> {
     // Acquiring Scans
-    let scans = get_protocol_scans(&node);
+    let scans = get_protocol_scans(node);
     // Acquire ReserveCoin boxes from user's wallet
     let boxes = scans.user_stablecoins_scan.get_boxes().unwrap();
-    let stablecoin_boxes = boxes
-        .into_iter()
-        .map(|b| StableCoinBox::new(&b).unwrap())
-        .collect();
 
-    stablecoin_boxes
+    boxes
+        .into_iter()
+        .filter_map(|b| StableCoinBox::new_with_config(&b, config).ok())
+        .collect()
 }
 
-/// Wrapper function to acquire boxes to cover a given NanoErg amount
-/// already wrapped as `ErgsBox`
-pub fn get_ergs_boxes_to_cover(amount: NanoErg, node: &NodeInterface) -> Vec<ErgsBox> {
-    println!("Ergs required: {}", nano_erg_to_erg(amount));
+/// Acquire all of the valid `BallotBox`es
+fn get_all_ballot_boxes(scans: &ProtocolScans, config: &ProtocolConfig) -> Vec<BallotBox> {
+    let all_boxes = scans.all_update_ballots_scan.get_boxes().unwrap();
+    // Filter out all non-valid `BallotBox`es
+    all_boxes
+        .iter()
+        .filter_map(|b| BallotBox::new_with_config(b, config).ok())
+        .collect()
+}
 
-    let ergo_boxes = node.unspent_boxes_with_min_total(amount).unwrap();
-    // Convert selected `ErgoBox`es to `ErgsBox`es
-    ergo_boxes
-        .into_iter()
-        .map(|b| ErgsBox::new(&b).unwrap())
+/// Acquire all of the valid `BallotBox`es held by the user
+fn get_user_ballot_boxes(scans: &ProtocolScans, config: &ProtocolConfig) -> Vec<BallotBox> {
+    let user_boxes = scans.user_update_ballot_scan.get_boxes().unwrap();
+    // Filter out all non-valid `BallotBox`es
+    user_boxes
+        .iter()
+        .filter_map(|b| BallotBox::new_with_config(b, config).ok())
         .collect()
 }
+
+/// Acquire the first valid `BallotBox` held by the user, exiting if the
+/// user holds none
+fn get_user_ballot_box(scans: &ProtocolScans, config: &ProtocolConfig) -> BallotBox {
+    match get_user_ballot_boxes(scans, config).into_iter().next() {
+        Some(ballot_box) => ballot_box,
+        None => {
+            println!("No valid Ballot box was found in the wallet.");
+            std::process::exit(0);
+        }
+    }
+}
+
+/// Estimate the block time from the timestamps of the most recent block
+/// headers, falling back to the targeted block time if they are unavailable
+fn get_block_time_estimator(node: &NodeInterface) -> BlockTimeEstimator {
+    let headers = node.parse_response_to_json(node.send_get_req("/blocks/lastHeaders/100"));
+    let timestamps: Vec<u64> = match headers {
+        Ok(headers) => headers
+            .members()
+            .filter_map(|header| header["timestamp"].as_u64())
+            .collect(),
+        Err(_) => vec![],
+    };
+    BlockTimeEstimator::from_header_timestamps(&timestamps).unwrap_or_default()
+}
+
+/// Select the smallest box of the wallet which can pay the tx fee of an
+/// Action which also creates a change box, exiting if there is none
+fn get_ergs_box_for_fee(
+    transaction_fee: NanoErg,
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) -> ErgsBox {
+    let ergs_boxes = wallet_ergs_boxes(&node.unspent_boxes().unwrap());
+    match select_ergs_box_for_fee(&ergs_boxes, transaction_fee, config) {
+        Ok(ergs_box) => ergs_box,
+        Err(e) => {
+            println!("Could not find a box to pay the tx fee.\nError: {}", e);
+            std::process::exit(0);
+        }
+    }
+}
@@ -1,12 +1,12 @@
 use crate::Result;
+use ageusd_headless::parameters::ProtocolConfig;
 use ergo_node_interface::{NodeInterface, Scan};
-use ageusd_headless::parameters::*;
 
 /// Register the scan to find the `Bank` box via the provided `NodeInterface`
-pub fn register_bank_scan(node: &NodeInterface) -> Result<Scan> {
+pub fn register_bank_scan(node: &NodeInterface, config: &ProtocolConfig) -> Result<Scan> {
     let tracking_rule = object! {
             "predicate": "containsAsset",
-            "assetId": BANK_NFT_ID,
+            "assetId": config.bank_nft_id.clone(),
     };
 
     Ok(Scan::register(
@@ -18,16 +18,16 @@ pub fn register_bank_scan(node: &NodeInterface) -> Result<Scan> {
 
 /// Register to find the StableCoins that the user owns in the very first
 /// address of their Ergo node wallet via the provided `NodeInterface`
-pub fn register_stablecoin_scan(node: &NodeInterface) -> Result<Scan> {
+pub fn register_stablecoin_scan(node: &NodeInterface, config: &ProtocolConfig) -> Result<Scan> {
     let wallet_address = node.wallet_addresses()?[0].clone();
-    let user_address_bytes = Scan::serialize_p2pk_for_tracking(&node, &wallet_address)?;
+    let user_address_bytes = Scan::serialize_p2pk_for_tracking(node, &wallet_address)?;
 
     let tracking_rule = object! {
         "predicate": "and",
         "args": [
             {
             "predicate": "containsAsset",
-            "assetId": STABLECOIN_TOKEN_ID.clone(),
+            "assetId": config.stablecoin_token_id.clone(),
             },
             {
             "predicate": "equals",
@@ -45,16 +45,16 @@ pub fn register_stablecoin_scan(node: &NodeInterface) -> Result<Scan> {
 
 /// Register to find the ReserveCoins that the user owns in the very first
 /// address of their Ergo node wallet via the provided `NodeInterface`
-pub fn register_reservecoin_scan(node: &NodeInterface) -> Result<Scan> {
+pub fn register_reservecoin_scan(node: &NodeInterface, config: &ProtocolConfig) -> Result<Scan> {
     let wallet_address = node.wallet_addresses()?[0].clone();
-    let user_address_bytes = Scan::serialize_p2pk_for_tracking(&node, &wallet_address)?;
+    let user_address_bytes = Scan::serialize_p2pk_for_tracking(node, &wallet_address)?;
 
     let tracking_rule = object! {
         "predicate": "and",
         "args": [
             {
             "predicate": "containsAsset",
-            "assetId": RESERVECOIN_TOKEN_ID.clone(),
+            "assetId": config.reservecoin_token_id.clone(),
             },
             {
             "predicate": "equals",
@@ -71,10 +71,10 @@ pub fn register_reservecoin_scan(node: &NodeInterface) -> Result<Scan> {
 }
 
 /// Register to find the Oracle Pool Box
-pub fn register_oracle_pool_scan(node: &NodeInterface) -> Result<Scan> {
+pub fn register_oracle_pool_scan(node: &NodeInterface, config: &ProtocolConfig) -> Result<Scan> {
     let tracking_rule = object! {
             "predicate": "containsAsset",
-            "assetId": ORACLE_POOL_NFT_ID,
+            "assetId": config.oracle_pool_nft_id.clone(),
     };
 
     Ok(Scan::register(
@@ -84,22 +84,31 @@ pub fn register_oracle_pool_scan(node: &NodeInterface) -> Result<Scan> {
     )?)
 }
 
-/// Register to find the user's vote token. (Must be held by the first address
-/// in the Ergo Node wallet)
-pub fn register_user_ballot_token_scan(node: &NodeInterface) -> Result<Scan> {
-    let wallet_address = node.wallet_addresses()?[0].clone();
-    let user_address_bytes = Scan::serialize_p2pk_for_tracking(&node, &wallet_address)?;
+/// Register to find the user's vote tokens held by any of the addresses
+/// in the Ergo Node wallet
+pub fn register_user_ballot_token_scan(
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) -> Result<Scan> {
+    let mut address_predicates = json::JsonValue::new_array();
+    for wallet_address in node.wallet_addresses()? {
+        let user_address_bytes = Scan::serialize_p2pk_for_tracking(node, &wallet_address)?;
+        address_predicates.push(object! {
+            "predicate": "equals",
+            "value": user_address_bytes,
+        })?;
+    }
 
     let tracking_rule = object! {
         "predicate": "and",
         "args": [
             {
             "predicate": "containsAsset",
-            "assetId": UPDATE_BALLOT_TOKEN_ID.clone(),
+            "assetId": config.update_ballot_token_id.clone(),
             },
             {
-            "predicate": "equals",
-            "value": user_address_bytes.clone(),
+            "predicate": "or",
+            "args": address_predicates.clone(),
             }
         ]
     };
@@ -112,10 +121,13 @@ pub fn register_user_ballot_token_scan(node: &NodeInterface) -> Result<Scan> {
 }
 
 /// Register to find all boxes that hold ballot tokens
-pub fn register_all_ballot_tokens_scan(node: &NodeInterface) -> Result<Scan> {
+pub fn register_all_ballot_tokens_scan(
+    node: &NodeInterface,
+    config: &ProtocolConfig,
+) -> Result<Scan> {
     let tracking_rule = object! {
             "predicate": "containsAsset",
-            "assetId": UPDATE_BALLOT_TOKEN_ID,
+            "assetId": config.update_ballot_token_id.clone(),
     };
 
     Ok(Scan::register(
@@ -126,10 +138,10 @@ pub fn register_all_ballot_tokens_scan(node: &NodeInterface) -> Result<Scan> {
 }
 
 /// Register to find the Update box
-pub fn register_update_box_scan(node: &NodeInterface) -> Result<Scan> {
+pub fn register_update_box_scan(node: &NodeInterface, config: &ProtocolConfig) -> Result<Scan> {
     let tracking_rule = object! {
             "predicate": "containsAsset",
-            "assetId": UPDATE_NFT_ID,
+            "assetId": config.update_nft_id.clone(),
     };
 
     Ok(Scan::register(
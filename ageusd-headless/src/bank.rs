@@ -3,14 +3,16 @@
 // methods for reading current state of the protocol, as well as
 // methods for building an output box (creating `ErgoBoxCandidate`s for
 // Actions within the protocol).
-use crate::equations::reserve_ratio;
-use crate::error::ProtocolError;
-use crate::parameters::{
-    BANK_NFT_ID, COOLING_OFF_HEIGHT, FEE_PERCENT, IMPLEMENTOR_FEE_PERCENT, MAX_RESERVE_RATIO,
-    MIN_BOX_VALUE, MIN_RESERVE_RATIO, RESERVECOIN_DEFAULT_PRICE, RESERVECOIN_TOKEN_ID,
-    STABLECOIN_TOKEN_ID,
+use crate::equations::{
+    checked_add, checked_reserve_ratio, checked_reserve_ratio_bps, checked_sub,
+    contract_reserve_ratio, estimate_max_reservecoin_mint, estimate_max_reservecoin_redeem,
+    estimate_max_stablecoin_mint, first_satisfying, last_satisfying, long_mul, reserve_ratio,
+    reserve_ratio_bps, settle_maximum, to_long,
 };
-use ergo_headless_dapp_framework::encoding::{build_token, unwrap_long};
+use crate::error::Result;
+use crate::parameters::{ContractVersion, ProtocolConfig};
+use crate::registers::{decode_non_negative_long, verify_registers, RegisterSpec, RegisterType};
+use ergo_headless_dapp_framework::encoding::build_token;
 use ergo_headless_dapp_framework::{
     create_candidate, BoxSpec, ErgUsdOraclePoolBox, ExplorerFindable, HeadlessDappError, SpecBox,
     SpecifiedBox, TokenSpec, WASMBox, WrapBox, WrappedBox,
@@ -29,28 +31,60 @@ pub struct BankBox {
 }
 
 impl SpecifiedBox for BankBox {
-    /// A `BoxSpec` that checks that the box is a valid BankBox via
-    /// looking that it holds the correct StableCoin/ReserveCoin tokens
-    /// and the Bank NFT.
+    /// A `BoxSpec` that checks that the box is a valid BankBox of the
+    /// default (mainnet) deployment. See `BankBox::box_spec_for`.
+    ///
+    /// Beware: the derived `BankBox::new`, `BankBox::w_new` and
+    /// `process_explorer_response` use this spec, so they reject the boxes of
+    /// every other deployment (testnet, devnet or any custom `ProtocolConfig`).
+    /// Use `BankBox::new_with_config`, `BankBox::w_new_with_config` and
+    /// `BankBox::box_spec_for` instead.
     fn box_spec() -> BoxSpec {
-        let tok_1_spec = Some(TokenSpec::new(1..u64::MAX, STABLECOIN_TOKEN_ID));
-        let tok_2_spec = Some(TokenSpec::new(1..u64::MAX, RESERVECOIN_TOKEN_ID));
-        let tok_3_spec = Some(TokenSpec::new(1..2, BANK_NFT_ID));
-        let tok_specs = vec![tok_1_spec, tok_2_spec, tok_3_spec];
-        BoxSpec::new(None, None, vec![], tok_specs)
+        Self::box_spec_for(&ProtocolConfig::default())
     }
 }
 
 /// WASM-supported methods related to `BankStage`
 #[wasm_bindgen]
 impl BankBox {
+    /// Wrap an `ErgoBox` which is a valid BankBox of the deployment
+    /// described by `config`
+    #[wasm_bindgen]
+    pub fn w_new_with_config(
+        wrapped_box: WErgoBox,
+        config: &ProtocolConfig,
+    ) -> std::result::Result<BankBox, JsValue> {
+        let ergo_box: ErgoBox = wrapped_box.into();
+        BankBox::new_with_config(&ergo_box, config)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
     /// Acquire the current Reserve Ratio in the Bank box
     #[wasm_bindgen]
-    pub fn current_reserve_ratio(&self, oracle_box: &ErgUsdOraclePoolBox) -> u64 {
+    pub fn current_reserve_ratio(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> u64 {
         reserve_ratio(
-            self.base_reserves(),
+            self.base_reserves(config),
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// Acquire the current Reserve Ratio in the Bank box in basis points
+    /// (40090 == 400.9%)
+    #[wasm_bindgen]
+    pub fn current_reserve_ratio_bps(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        reserve_ratio_bps(
+            self.base_reserves(config),
             self.num_circulating_stablecoins(),
-            oracle_box.datapoint_in_cents(),
+            self.oracle_rate(oracle_box, config),
         )
     }
 
@@ -58,80 +92,137 @@ impl BankBox {
     /// of nanoErgs held inside, minus the minimum box value required for
     /// posting a box on-chain.
     #[wasm_bindgen]
-    pub fn base_reserves(&self) -> NanoErg {
-        if self.ergo_box.value.as_u64().clone() < MIN_BOX_VALUE {
+    pub fn base_reserves(&self, config: &ProtocolConfig) -> NanoErg {
+        if *self.ergo_box.value.as_u64() < config.min_box_value {
             return 0;
         }
-        // self.ergo_box.value.as_u64().clone() - MIN_BOX_VALUE
-        self.ergo_box.value.as_u64().clone()
+        // Contracts before v0.3 exclude the storage rent from the reserves
+        if config.contract_version.reserves_exclude_storage_rent() {
+            return *self.ergo_box.value.as_u64() - config.min_box_value;
+        }
+        *self.ergo_box.value.as_u64()
+    }
+
+    /// The nanoErg price of a single StableCoin according to the oracle
+    /// pool box, read as the `ContractVersion` of `config` does
+    #[wasm_bindgen]
+    pub fn oracle_rate(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> NanoErg {
+        config.contract_version.oracle_rate(oracle_box)
     }
 
     /// Outstanding liabilities in `NanoErg`s to cover the current minted
     /// StableCoins (StableCoins in circulation)
     #[wasm_bindgen]
-    pub fn liabilities(&self, oracle_box: &ErgUsdOraclePoolBox) -> NanoErg {
+    pub fn liabilities(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> NanoErg {
+        self.liabilities_at_rate(self.oracle_rate(oracle_box, config), config)
+    }
+
+    /// Outstanding liabilities in `NanoErg`s to cover the current minted
+    /// StableCoins if the oracle rate were `oracle_rate`
+    #[wasm_bindgen]
+    pub fn liabilities_at_rate(&self, oracle_rate: NanoErg, config: &ProtocolConfig) -> NanoErg {
         if self.num_circulating_stablecoins() == 0 {
-            return 0;
+            0
         } else {
             // The true liabilities for outstanding StableCoins
             let base_reserves_needed =
-                self.num_circulating_stablecoins() * oracle_box.datapoint_in_cents();
+                self.num_circulating_stablecoins() as u128 * oracle_rate as u128;
             // Returns the minimum between the reserves and the true liabilities
             // to cover the scenario where reserves are not sufficient.
-            return std::cmp::min(self.base_reserves(), base_reserves_needed);
+            std::cmp::min(self.base_reserves(config) as u128, base_reserves_needed) as u64
         }
     }
 
     /// The equity of the protocol. In other words what base reserves are left
     /// after having covered all liabilities.
     #[wasm_bindgen]
-    pub fn equity(&self, oracle_box: &ErgUsdOraclePoolBox) -> NanoErg {
-        if self.base_reserves() <= self.liabilities(oracle_box) {
+    pub fn equity(&self, oracle_box: &ErgUsdOraclePoolBox, config: &ProtocolConfig) -> NanoErg {
+        self.equity_at_rate(self.oracle_rate(oracle_box, config), config)
+    }
+
+    /// The equity of the protocol if the oracle rate were `oracle_rate`
+    #[wasm_bindgen]
+    pub fn equity_at_rate(&self, oracle_rate: NanoErg, config: &ProtocolConfig) -> NanoErg {
+        let liabilities = self.liabilities_at_rate(oracle_rate, config);
+        if self.base_reserves(config) <= liabilities {
             return 0;
         }
-        self.base_reserves() - self.liabilities(oracle_box)
+        self.base_reserves(config) - liabilities
     }
 
     /// The number of StableCoins currently minted. In other words the number
     /// currently in circulation. Held in R4 of Bank box.
     #[wasm_bindgen]
-    pub fn num_circulating_stablecoins(&self) -> u64 {
-        let registers = self.registers();
-        // Using unwrap because the `StageBox<Bank>` check should guarantee
-        // we have a valid box at the `Bank` stage.
-        unwrap_long(&registers[0]).unwrap() as u64
+    pub fn w_num_circulating_stablecoins(&self) -> std::result::Result<u64, JsValue> {
+        self.try_num_circulating_stablecoins()
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
     }
 
     /// The number of ReserveCoins currently minted. In other words the number
     /// currently in circulation. Held in R5 of Bank box.
     #[wasm_bindgen]
-    pub fn num_circulating_reservecoins(&self) -> u64 {
-        let registers = self.registers();
-        // Using unwrap because the `StageBox<Bank>` check should guarantee
-        // we have a valid box at the `Bank` stage.
-        unwrap_long(&registers[1]).unwrap() as u64
+    pub fn w_num_circulating_reservecoins(&self) -> std::result::Result<u64, JsValue> {
+        self.try_num_circulating_reservecoins()
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
     }
 
     /// Current StableCoin nominal price
     #[wasm_bindgen]
-    pub fn stablecoin_nominal_price(&self, oracle_box: &ErgUsdOraclePoolBox) -> u64 {
-        let oracle_rate = oracle_box.datapoint_in_cents();
+    pub fn stablecoin_nominal_price(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        self.stablecoin_nominal_price_at_rate(self.oracle_rate(oracle_box, config), config)
+    }
+
+    /// The StableCoin nominal price if the oracle rate were `oracle_rate`
+    #[wasm_bindgen]
+    pub fn stablecoin_nominal_price_at_rate(
+        &self,
+        oracle_rate: NanoErg,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        let liabilities = self.liabilities_at_rate(oracle_rate, config);
         if self.num_circulating_stablecoins() == 0
-            || oracle_rate < self.liabilities(oracle_box) / self.num_circulating_stablecoins()
+            || oracle_rate < liabilities / self.num_circulating_stablecoins()
         {
-            return oracle_rate;
+            oracle_rate
         } else {
-            return self.liabilities(oracle_box) / self.num_circulating_stablecoins();
+            liabilities / self.num_circulating_stablecoins()
         }
     }
 
     /// Current ReserveCoin nominal price
     #[wasm_bindgen]
-    pub fn reservecoin_nominal_price(&self, oracle_box: &ErgUsdOraclePoolBox) -> u64 {
-        if self.num_circulating_reservecoins() <= 1 || self.equity(oracle_box) == 0 {
-            return RESERVECOIN_DEFAULT_PRICE;
+    pub fn reservecoin_nominal_price(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        self.reservecoin_nominal_price_at_rate(self.oracle_rate(oracle_box, config), config)
+    }
+
+    /// The ReserveCoin nominal price if the oracle rate were `oracle_rate`
+    #[wasm_bindgen]
+    pub fn reservecoin_nominal_price_at_rate(
+        &self,
+        oracle_rate: NanoErg,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        let equity = self.equity_at_rate(oracle_rate, config);
+        if self.num_circulating_reservecoins() == 0 || equity == 0 {
+            return config.reservecoin_default_price;
         }
-        self.equity(oracle_box) / self.num_circulating_reservecoins()
+        equity / self.num_circulating_reservecoins()
     }
 
     /// the requested amount results in a new reserve ratio within the limits
@@ -140,9 +231,10 @@ impl BankBox {
         &self,
         oracle_box: &ErgUsdOraclePoolBox,
         amount: u64,
+        config: &ProtocolConfig,
     ) -> bool {
-        let new_reserve_ratio = self.mint_stablecoin_reserve_ratio(oracle_box, amount);
-        if new_reserve_ratio >= MIN_RESERVE_RATIO {
+        let new_reserve_ratio = self.mint_stablecoin_reserve_ratio(oracle_box, amount, config);
+        if new_reserve_ratio >= config.min_reserve_ratio {
             return true;
         }
         false
@@ -150,48 +242,53 @@ impl BankBox {
 
     /// Number of StableCoins possible to be minted based off of current Reserve Ratio
     #[wasm_bindgen]
-    pub fn num_able_to_mint_stablecoin(&self, oracle_box: &ErgUsdOraclePoolBox) -> u64 {
-        if !self.able_to_mint_stablecoin_amount(oracle_box, 1) {
+    pub fn num_able_to_mint_stablecoin(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        if !self.able_to_mint_stablecoin_amount(oracle_box, 1, config) {
             return 0;
         }
-
-        // Start at approximately the right amount
-        let mut low = self.equity(oracle_box) / oracle_box.datapoint_in_cents() / 4;
-        let mut high = u64::MAX - 1;
-
-        while low <= high {
-            let mid = ((high - low) / 2) + low;
-            let new_reserve_ratio = self.mint_stablecoin_reserve_ratio(oracle_box, mid);
-
-            if new_reserve_ratio == MIN_RESERVE_RATIO {
-                return mid;
-            }
-
-            if new_reserve_ratio < MIN_RESERVE_RATIO {
-                high = mid - 1;
-            }
-
-            if new_reserve_ratio > MIN_RESERVE_RATIO {
-                low = mid + 1;
-            }
-        }
-        return low;
+        let estimate = estimate_max_stablecoin_mint(
+            self.base_reserves(config),
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+            self.stablecoin_nominal_price(oracle_box, config),
+            config.fee_schedule().protocol_fee_percent,
+            config.min_reserve_ratio,
+        );
+        // Amounts which do not fit within a `Long` can never be minted
+        settle_maximum(estimate, i64::MAX as u64, |n| {
+            self.able_to_mint_stablecoin_amount(oracle_box, n, config)
+        })
     }
 
     /// Acquire the new reserve ratio after minting `num_to_mint` Stablecoins
+    /// Returns 0 if the calculation overflows.
     #[wasm_bindgen]
     pub fn mint_stablecoin_reserve_ratio(
         &self,
         oracle_box: &ErgUsdOraclePoolBox,
         num_to_mint: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let new_base_reserves =
-            self.base_reserves() + self.base_cost_to_mint_stablecoin(num_to_mint, oracle_box);
-        reserve_ratio(
-            new_base_reserves,
-            self.num_circulating_stablecoins() + num_to_mint,
-            oracle_box.datapoint_in_cents(),
-        )
+        self.checked_mint_stablecoin_reserve_ratio(oracle_box, num_to_mint, config)
+            .unwrap_or(0)
+    }
+
+    /// Acquire the new reserve ratio in basis points after minting
+    /// `num_to_mint` Stablecoins
+    /// Returns 0 if the calculation overflows.
+    #[wasm_bindgen]
+    pub fn mint_stablecoin_reserve_ratio_bps(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_mint: u64,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        self.checked_mint_stablecoin_reserve_ratio_bps(oracle_box, num_to_mint, config)
+            .unwrap_or(0)
     }
 
     /// The requested amount results in a new reserve ratio within the limits
@@ -201,70 +298,76 @@ impl BankBox {
         oracle_box: &ErgUsdOraclePoolBox,
         amount: u64,
         current_height: BlockHeight,
+        config: &ProtocolConfig,
     ) -> bool {
-        if current_height < COOLING_OFF_HEIGHT {
+        if config.in_cooling_off_period(current_height) {
             return true;
         }
 
-        let new_reserve_ratio = self.mint_reservecoin_reserve_ratio(oracle_box, amount);
-        if new_reserve_ratio <= MAX_RESERVE_RATIO {
+        let new_reserve_ratio = self.mint_reservecoin_reserve_ratio(oracle_box, amount, config);
+        if new_reserve_ratio <= config.max_reserve_ratio {
             return true;
         }
         false
     }
 
-    /// Number of ReserveCoins possible to be minted based off of current Reserve Ratio
+    /// Number of ReserveCoins possible to be minted based off of current Reserve Ratio.
+    /// Returns `u64::MAX` during the cooling off period, see
+    /// `reservecoin_mint_capacity` to tell the two apart.
     #[wasm_bindgen]
     pub fn num_able_to_mint_reservecoin(
         &self,
         oracle_box: &ErgUsdOraclePoolBox,
         current_height: BlockHeight,
+        config: &ProtocolConfig,
     ) -> u64 {
         // Check for cooling off period
-        if current_height < COOLING_OFF_HEIGHT {
+        if config.in_cooling_off_period(current_height) {
             return u64::MAX;
         }
         // Check if able to mint any at all
-        if !self.able_to_mint_reservecoin_amount(oracle_box, 1, current_height) {
+        if !self.able_to_mint_reservecoin_amount(oracle_box, 1, current_height, config) {
             return 0;
         }
-
-        let mut low = 0;
-        let mut high = u64::MAX - 1;
-
-        while low <= high {
-            let mid = ((high - low) / 2) + low;
-            let new_reserve_ratio = self.mint_reservecoin_reserve_ratio(oracle_box, mid);
-
-            if new_reserve_ratio == MAX_RESERVE_RATIO {
-                return mid;
-            }
-
-            if new_reserve_ratio > MAX_RESERVE_RATIO {
-                high = mid - 1;
-            }
-
-            if new_reserve_ratio < MAX_RESERVE_RATIO {
-                low = mid + 1;
-            }
-        }
-        return low;
+        let estimate = estimate_max_reservecoin_mint(
+            self.base_reserves(config),
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+            self.reservecoin_nominal_price(oracle_box, config),
+            config.fee_schedule().protocol_fee_percent,
+            config.max_reserve_ratio,
+        );
+        // Amounts which do not fit within a `Long` can never be minted
+        settle_maximum(estimate, i64::MAX as u64, |n| {
+            self.able_to_mint_reservecoin_amount(oracle_box, n, current_height, config)
+        })
     }
 
     /// Acquire the new reserve ratio after minting `num_to_mint` Reservecoins
+    /// Returns `u64::MAX` if the calculation overflows.
     #[wasm_bindgen]
     pub fn mint_reservecoin_reserve_ratio(
         &self,
         oracle_box: &ErgUsdOraclePoolBox,
         num_to_mint: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let new_base_reserves =
-            self.base_reserves() + self.base_cost_to_mint_reservecoin(num_to_mint, oracle_box);
-        reserve_ratio(
-            new_base_reserves,
-            self.num_circulating_stablecoins(),
-            oracle_box.datapoint_in_cents(),
-        )
+        self.checked_mint_reservecoin_reserve_ratio(oracle_box, num_to_mint, config)
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Acquire the new reserve ratio in basis points after minting
+    /// `num_to_mint` Reservecoins
+    /// Returns `u64::MAX` if the calculation overflows.
+    #[wasm_bindgen]
+    pub fn mint_reservecoin_reserve_ratio_bps(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_mint: u64,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        self.checked_mint_reservecoin_reserve_ratio_bps(oracle_box, num_to_mint, config)
+            .unwrap_or(u64::MAX)
     }
 
     /// The requested amount results in a new reserve ratio within the limits
@@ -273,277 +376,872 @@ impl BankBox {
         &self,
         oracle_box: &ErgUsdOraclePoolBox,
         amount: u64,
+        config: &ProtocolConfig,
     ) -> bool {
-        let new_reserve_ratio = self.redeem_reservecoin_reserve_ratio(oracle_box, amount);
-        if new_reserve_ratio >= MIN_RESERVE_RATIO {
+        let new_reserve_ratio = self.redeem_reservecoin_reserve_ratio(oracle_box, amount, config);
+        if new_reserve_ratio >= config.min_reserve_ratio {
             return true;
         }
         false
     }
 
     /// Number of ReserveCoins possible to be redeemed based off of current Reserve Ratio.
-    /// Checks if the provided `current_height` is before the COOLING_OFF_HEIGHT to verify
-    /// as well.
+    /// Never more than the number of circulating ReserveCoins.
     #[wasm_bindgen]
-    pub fn num_able_to_redeem_reservecoin(&self, oracle_box: &ErgUsdOraclePoolBox) -> u64 {
-        if self.redeem_reservecoin_reserve_ratio(oracle_box, 1) <= MIN_RESERVE_RATIO {
+    pub fn num_able_to_redeem_reservecoin(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        if !self.able_to_redeem_reservecoin_amount(oracle_box, 1, config) {
             return 0;
         }
-
-        let mut low = 0;
-        let mut high = u64::MAX - 1;
-
-        while low <= high {
-            let mid = ((high - low) / 2) + low;
-            let new_reserve_ratio = self.redeem_reservecoin_reserve_ratio(oracle_box, mid);
-
-            if new_reserve_ratio == MIN_RESERVE_RATIO {
-                return mid;
-            }
-
-            // Search values that are greater than val - to right of current mid_index
-            if new_reserve_ratio < MIN_RESERVE_RATIO {
-                high = mid - 1;
-            }
-
-            // Search values that are less than val - to the left of current mid_index
-            if new_reserve_ratio > MIN_RESERVE_RATIO {
-                low = mid + 1;
-            }
-        }
-        return low;
+        let estimate = estimate_max_reservecoin_redeem(
+            self.base_reserves(config),
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+            self.reservecoin_nominal_price(oracle_box, config),
+            config.fee_schedule().protocol_fee_percent,
+            config.min_reserve_ratio,
+        );
+        settle_maximum(estimate, self.num_circulating_reservecoins(), |n| {
+            self.able_to_redeem_reservecoin_amount(oracle_box, n, config)
+        })
     }
 
-    /// Acquire the new reserve ratio after minting `num_to_redeem` Reservecoins
+    /// Acquire the new reserve ratio after redeeming `num_to_redeem` Reservecoins
+    /// Returns 0 if the calculation overflows.
     #[wasm_bindgen]
     pub fn redeem_reservecoin_reserve_ratio(
         &self,
         oracle_box: &ErgUsdOraclePoolBox,
         num_to_redeem: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let redeem_amount = self.base_cost_to_mint_reservecoin(num_to_redeem, oracle_box);
-        let mut new_base_reserves = 0;
-        if redeem_amount < self.base_reserves() {
-            new_base_reserves = self.base_reserves() - redeem_amount;
-        }
+        self.checked_redeem_reservecoin_reserve_ratio(oracle_box, num_to_redeem, config)
+            .unwrap_or(0)
+    }
 
-        reserve_ratio(
-            new_base_reserves,
-            self.num_circulating_stablecoins(),
-            oracle_box.datapoint_in_cents(),
-        )
+    /// Acquire the new reserve ratio in basis points after redeeming
+    /// `num_to_redeem` Reservecoins
+    /// Returns 0 if the calculation overflows.
+    #[wasm_bindgen]
+    pub fn redeem_reservecoin_reserve_ratio_bps(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_redeem: u64,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        self.checked_redeem_reservecoin_reserve_ratio_bps(oracle_box, num_to_redeem, config)
+            .unwrap_or(0)
     }
 
     /// The total amount of nanoErgs which is needed to cover minting
     /// the provided number of ReserveCoins, cover tx fees, implementor
     /// fee, etc.
+    /// Returns `u64::MAX` if the calculation overflows.
     #[wasm_bindgen]
     pub fn total_cost_to_mint_stablecoin(
         &self,
         amount_to_mint: u64,
         oracle_box: &ErgUsdOraclePoolBox,
         transaction_fee: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let base_cost = self.base_cost_to_mint_stablecoin(amount_to_mint, &oracle_box);
-        base_cost
-            + transaction_fee
-            + (MIN_BOX_VALUE * 2)
-            + (base_cost as f64 * IMPLEMENTOR_FEE_PERCENT) as u64
+        self.checked_total_cost_to_mint_stablecoin(
+            amount_to_mint,
+            oracle_box,
+            transaction_fee,
+            config,
+        )
+        .unwrap_or(u64::MAX)
     }
 
     /// The amount of nanoErg fees for minting StableCoins.
     /// This includes protocol fees, tx fees, and implementor fees.
+    /// Returns `u64::MAX` if the calculation overflows.
     #[wasm_bindgen]
     pub fn fees_from_minting_stablecoin(
         &self,
         amount_to_mint: u64,
         oracle_box: &ErgUsdOraclePoolBox,
         transaction_fee: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let feeless_amount = self.stablecoin_nominal_price(oracle_box) * amount_to_mint;
-        let protocol_fee = feeless_amount * FEE_PERCENT / 100;
-        let implementor_fee = (self.base_cost_to_mint_stablecoin(amount_to_mint, oracle_box) as f64
-            * IMPLEMENTOR_FEE_PERCENT) as u64;
-        protocol_fee + transaction_fee + implementor_fee
+        self.checked_fees_from_minting_stablecoin(
+            amount_to_mint,
+            oracle_box,
+            transaction_fee,
+            config,
+        )
+        .unwrap_or(u64::MAX)
     }
 
     /// The amount of base currency (Ergs) which is needed to cover minting
     /// the provided number of StableCoins.
+    /// Returns `u64::MAX` if the calculation overflows.
     #[wasm_bindgen]
     pub fn base_cost_to_mint_stablecoin(
         &self,
         amount_to_mint: u64,
         oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
     ) -> u64 {
-        // Cost to mint without fees
-        let feeless_cost = self.stablecoin_nominal_price(oracle_box) * amount_to_mint;
-        // The StableCoin protocol fee charged
-        let protocol_fee = feeless_cost * FEE_PERCENT / 100;
-
-        feeless_cost + protocol_fee
+        self.checked_base_cost_to_mint_stablecoin(amount_to_mint, oracle_box, config)
+            .unwrap_or(u64::MAX)
     }
 
     /// The total amount of nanoErgs which is needed to cover minting
     /// the provided number of ReserveCoins, cover tx fees, implementor
     /// fee, etc.
+    /// Returns `u64::MAX` if the calculation overflows.
     #[wasm_bindgen]
     pub fn total_cost_to_mint_reservecoin(
         &self,
         amount_to_mint: u64,
         oracle_box: &ErgUsdOraclePoolBox,
         transaction_fee: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let base_cost = self.base_cost_to_mint_reservecoin(amount_to_mint, &oracle_box);
-        base_cost
-            + transaction_fee
-            + (MIN_BOX_VALUE * 2)
-            + (base_cost as f64 * IMPLEMENTOR_FEE_PERCENT) as u64
+        self.checked_total_cost_to_mint_reservecoin(
+            amount_to_mint,
+            oracle_box,
+            transaction_fee,
+            config,
+        )
+        .unwrap_or(u64::MAX)
     }
 
     /// The amount of nanoErg fees for minting ReserveCoins.
     /// This includes protocol fees, tx fees, and implementor fees.
+    /// Returns `u64::MAX` if the calculation overflows.
     #[wasm_bindgen]
     pub fn fees_from_minting_reservecoin(
         &self,
         amount_to_mint: u64,
         oracle_box: &ErgUsdOraclePoolBox,
         transaction_fee: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let feeless_amount = self.reservecoin_nominal_price(oracle_box) * amount_to_mint;
-        let protocol_fee = feeless_amount * FEE_PERCENT / 100;
-        let implementor_fee = (self.base_cost_to_mint_reservecoin(amount_to_mint, oracle_box)
-            as f64
-            * IMPLEMENTOR_FEE_PERCENT) as u64;
-        protocol_fee + transaction_fee + implementor_fee
+        self.checked_fees_from_minting_reservecoin(
+            amount_to_mint,
+            oracle_box,
+            transaction_fee,
+            config,
+        )
+        .unwrap_or(u64::MAX)
     }
 
     /// The amount of base currency (Ergs) which is needed to cover minting
     /// the provided number of ReserveCoins.
+    /// Returns `u64::MAX` if the calculation overflows.
     #[wasm_bindgen]
     pub fn base_cost_to_mint_reservecoin(
         &self,
         amount_to_mint: u64,
         oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
     ) -> u64 {
-        // Cost to mint without fees
-        let feeless_cost = self.reservecoin_nominal_price(oracle_box) * amount_to_mint;
-        // The StableCoin protocol fee charged
-        let protocol_fee = feeless_cost * FEE_PERCENT / 100;
-
-        feeless_cost + protocol_fee
+        self.checked_base_cost_to_mint_reservecoin(amount_to_mint, oracle_box, config)
+            .unwrap_or(u64::MAX)
     }
 
     /// The amount of nanoErgs which will be redeemed
     /// from the protocol based on current reserves + the number of
     /// ReserveCoins being redeemed by the user after paying for
     // tx fees, implementor fees, etc.
+    /// Returns 0 if the calculation overflows.
     #[wasm_bindgen]
     pub fn total_amount_from_redeeming_reservecoin(
         &self,
         amount_to_redeem: u64,
         oracle_box: &ErgUsdOraclePoolBox,
         transaction_fee: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let base_amount = self.base_amount_from_redeeming_reservecoin(amount_to_redeem, oracle_box);
-        let fees = transaction_fee + (base_amount as f64 * IMPLEMENTOR_FEE_PERCENT) as u64;
-
-        if base_amount > fees {
-            return base_amount - fees;
-        } else {
-            return 0;
-        }
+        self.checked_total_amount_from_redeeming_reservecoin(
+            amount_to_redeem,
+            oracle_box,
+            transaction_fee,
+            config,
+        )
+        .unwrap_or(0)
     }
 
     /// The amount of nanoErg fees for redeeming ReserveCoins.
     /// This includes protocol fees, tx fees, and implementor fees.
+    /// Returns `u64::MAX` if the calculation overflows.
     #[wasm_bindgen]
     pub fn fees_from_redeeming_reservecoin(
         &self,
         amount_to_redeem: u64,
         oracle_box: &ErgUsdOraclePoolBox,
         transaction_fee: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let feeless_amount = self.reservecoin_nominal_price(oracle_box) * amount_to_redeem;
-        let protocol_fee = feeless_amount * FEE_PERCENT / 100;
-        let implementor_fee =
-            (self.base_amount_from_redeeming_reservecoin(amount_to_redeem, oracle_box) as f64
-                * IMPLEMENTOR_FEE_PERCENT) as u64;
-        protocol_fee + transaction_fee + implementor_fee
+        self.checked_fees_from_redeeming_reservecoin(
+            amount_to_redeem,
+            oracle_box,
+            transaction_fee,
+            config,
+        )
+        .unwrap_or(u64::MAX)
     }
 
     /// The amount of base currency (Ergs) which will be redeemed
     /// from the protocol based on current reserves + the number of
     /// ReserveCoins being redeemed by the user. Includes protocol fee.
+    /// Returns 0 if the calculation overflows.
     #[wasm_bindgen]
     pub fn base_amount_from_redeeming_reservecoin(
         &self,
         amount_to_redeem: u64,
         oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
     ) -> u64 {
-        // Cost to mint without fees
-        let feeless_amount = self.reservecoin_nominal_price(oracle_box) * amount_to_redeem;
-        // The StableCoin protocol fee charged
-        let protocol_fee = feeless_amount * FEE_PERCENT / 100;
-
-        feeless_amount - protocol_fee
+        self.checked_base_amount_from_redeeming_reservecoin(amount_to_redeem, oracle_box, config)
+            .unwrap_or(0)
     }
 
     /// The amount of nanoErgs which will be redeemed
     /// from the protocol based on current reserves + the number of
     /// StableCoins being redeemed by the user after paying for
     // tx fees, implementor fees, etc.
+    /// Returns 0 if the calculation overflows.
     #[wasm_bindgen]
     pub fn total_amount_from_redeeming_stablecoin(
         &self,
         amount_to_redeem: u64,
         oracle_box: &ErgUsdOraclePoolBox,
         transaction_fee: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let base_amount = self.base_amount_from_redeeming_stablecoin(amount_to_redeem, oracle_box);
-        let fees = transaction_fee + (base_amount as f64 * IMPLEMENTOR_FEE_PERCENT) as u64;
-
-        if base_amount > fees {
-            return base_amount - fees;
-        } else {
-            return 0;
-        }
+        self.checked_total_amount_from_redeeming_stablecoin(
+            amount_to_redeem,
+            oracle_box,
+            transaction_fee,
+            config,
+        )
+        .unwrap_or(0)
     }
 
     /// The amount of nanoErg fees for redeeming StableCoins.
     /// This includes protocol fees, tx fees, and implementor fees.
+    /// Returns `u64::MAX` if the calculation overflows.
     #[wasm_bindgen]
     pub fn fees_from_redeeming_stablecoin(
         &self,
         amount_to_redeem: u64,
         oracle_box: &ErgUsdOraclePoolBox,
         transaction_fee: u64,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let feeless_amount = self.stablecoin_nominal_price(oracle_box) * amount_to_redeem;
-        let protocol_fee = feeless_amount * FEE_PERCENT / 100;
-        let implementor_fee =
-            (self.base_amount_from_redeeming_stablecoin(amount_to_redeem, oracle_box) as f64
-                * IMPLEMENTOR_FEE_PERCENT) as u64;
-        protocol_fee + transaction_fee + implementor_fee
+        self.checked_fees_from_redeeming_stablecoin(
+            amount_to_redeem,
+            oracle_box,
+            transaction_fee,
+            config,
+        )
+        .unwrap_or(u64::MAX)
     }
 
     /// The amount of base currency (Ergs) which will be redeemed
     /// from the protocol based on current reserves + the number of
     /// StableCoins being redeemed by the user.
+    /// Returns 0 if the calculation overflows.
     #[wasm_bindgen]
     pub fn base_amount_from_redeeming_stablecoin(
         &self,
         amount_to_redeem: u64,
         oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        self.checked_base_amount_from_redeeming_stablecoin(amount_to_redeem, oracle_box, config)
+            .unwrap_or(0)
+    }
+
+    /// The maximum number of StableCoins which can be minted with a budget of
+    /// `budget` nanoErgs. The budget must cover the protocol fee, implementor
+    /// fee, tx fee and the min box values of the outputs. Respects the
+    /// minimum reserve ratio. Returns 0 if not even 1 can be minted.
+    #[wasm_bindgen]
+    pub fn num_able_to_mint_stablecoin_with_budget(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        budget: NanoErg,
+        transaction_fee: NanoErg,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        let price = std::cmp::max(self.stablecoin_nominal_price(oracle_box, config), 1);
+        last_satisfying(0, budget / price, |n| {
+            let cost =
+                self.checked_total_cost_to_mint_stablecoin(n, oracle_box, transaction_fee, config);
+            matches!(cost, Ok(cost) if cost <= budget)
+                && self.able_to_mint_stablecoin_amount(oracle_box, n, config)
+        })
+        .unwrap_or(0)
+    }
+
+    /// The maximum number of ReserveCoins which can be minted with a budget of
+    /// `budget` nanoErgs. The budget must cover the protocol fee, implementor
+    /// fee, tx fee and the min box values of the outputs. Respects the
+    /// maximum reserve ratio. Returns 0 if not even 1 can be minted.
+    #[wasm_bindgen]
+    pub fn num_able_to_mint_reservecoin_with_budget(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        budget: NanoErg,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        config: &ProtocolConfig,
     ) -> u64 {
-        let feeless_amount = self.stablecoin_nominal_price(oracle_box) * amount_to_redeem;
-        let protocol_fee = feeless_amount * FEE_PERCENT / 100;
+        let price = std::cmp::max(self.reservecoin_nominal_price(oracle_box, config), 1);
+        last_satisfying(0, budget / price, |n| {
+            let cost =
+                self.checked_total_cost_to_mint_reservecoin(n, oracle_box, transaction_fee, config);
+            matches!(cost, Ok(cost) if cost <= budget)
+                && self.able_to_mint_reservecoin_amount(oracle_box, n, current_height, config)
+        })
+        .unwrap_or(0)
+    }
 
-        feeless_amount - protocol_fee
+    /// The minimum number of StableCoins which need to be redeemed in order
+    /// to receive at least `target_payout` nanoErgs after paying the
+    /// implementor fee and tx fee. Returns 0 if the payout cannot be reached.
+    #[wasm_bindgen]
+    pub fn num_to_redeem_stablecoin_for_payout(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        target_payout: NanoErg,
+        transaction_fee: NanoErg,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        // The most which can be redeemed given the reserves of the Bank
+        let max_redeemable = last_satisfying(0, self.num_circulating_stablecoins(), |n| {
+            let amount = self.checked_base_amount_from_redeeming_stablecoin(n, oracle_box, config);
+            matches!(amount, Ok(amount) if amount <= self.base_reserves(config))
+        })
+        .unwrap_or(0);
+        first_satisfying(1, max_redeemable, |n| {
+            let payout = self.checked_total_amount_from_redeeming_stablecoin(
+                n,
+                oracle_box,
+                transaction_fee,
+                config,
+            );
+            matches!(payout, Ok(payout) if payout >= target_payout)
+        })
+        .unwrap_or(0)
+    }
+
+    /// The minimum number of ReserveCoins which need to be redeemed in order
+    /// to receive at least `target_payout` nanoErgs after paying the
+    /// implementor fee and tx fee. Respects the minimum reserve ratio.
+    /// Returns 0 if the payout cannot be reached.
+    #[wasm_bindgen]
+    pub fn num_to_redeem_reservecoin_for_payout(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        target_payout: NanoErg,
+        transaction_fee: NanoErg,
+        config: &ProtocolConfig,
+    ) -> u64 {
+        // The most which can be redeemed given the reserve ratio
+        let max_redeemable = last_satisfying(0, self.num_circulating_reservecoins(), |n| {
+            n == 0 || self.able_to_redeem_reservecoin_amount(oracle_box, n, config)
+        })
+        .unwrap_or(0);
+        first_satisfying(1, max_redeemable, |n| {
+            let payout = self.checked_total_amount_from_redeeming_reservecoin(
+                n,
+                oracle_box,
+                transaction_fee,
+                config,
+            );
+            matches!(payout, Ok(payout) if payout >= target_payout)
+        })
+        .unwrap_or(0)
+    }
+}
+
+/// Checked pricing methods related to `BankStage` which return an
+/// `ArithmeticOverflow` error rather than overflowing
+impl BankBox {
+    /// Acquire the current Reserve Ratio in the Bank box
+    pub fn checked_current_reserve_ratio(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        checked_reserve_ratio(
+            self.base_reserves(config),
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// Acquire the current Reserve Ratio in the Bank box in basis points
+    pub fn checked_current_reserve_ratio_bps(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        checked_reserve_ratio_bps(
+            self.base_reserves(config),
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// Acquire the current Reserve Ratio in the Bank box exactly as the Bank
+    /// contract computes it. `None` if no StableCoins are circulating, in
+    /// which case the contract uses the max reserve ratio instead.
+    pub fn contract_reserve_ratio(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<Option<u64>> {
+        contract_reserve_ratio(
+            self.base_reserves(config),
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// Acquire the new reserve ratio after minting `num_to_mint` Stablecoins
+    pub fn checked_mint_stablecoin_reserve_ratio(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_mint: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let (base_reserves, circulating_stablecoins) =
+            self.reserves_after_minting_stablecoin(oracle_box, num_to_mint, config)?;
+        checked_reserve_ratio(
+            base_reserves,
+            circulating_stablecoins,
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// Acquire the new reserve ratio in basis points after minting
+    /// `num_to_mint` Stablecoins
+    pub fn checked_mint_stablecoin_reserve_ratio_bps(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_mint: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let (base_reserves, circulating_stablecoins) =
+            self.reserves_after_minting_stablecoin(oracle_box, num_to_mint, config)?;
+        checked_reserve_ratio_bps(
+            base_reserves,
+            circulating_stablecoins,
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// Acquire the new reserve ratio after minting `num_to_mint` Reservecoins
+    pub fn checked_mint_reservecoin_reserve_ratio(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_mint: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        checked_reserve_ratio(
+            self.reserves_after_minting_reservecoin(oracle_box, num_to_mint, config)?,
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// Acquire the new reserve ratio in basis points after minting
+    /// `num_to_mint` Reservecoins
+    pub fn checked_mint_reservecoin_reserve_ratio_bps(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_mint: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        checked_reserve_ratio_bps(
+            self.reserves_after_minting_reservecoin(oracle_box, num_to_mint, config)?,
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// Acquire the new reserve ratio after redeeming `num_to_redeem` Reservecoins
+    pub fn checked_redeem_reservecoin_reserve_ratio(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_redeem: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        checked_reserve_ratio(
+            self.reserves_after_redeeming_reservecoin(oracle_box, num_to_redeem, config)?,
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// Acquire the new reserve ratio in basis points after redeeming
+    /// `num_to_redeem` Reservecoins
+    pub fn checked_redeem_reservecoin_reserve_ratio_bps(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_redeem: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        checked_reserve_ratio_bps(
+            self.reserves_after_redeeming_reservecoin(oracle_box, num_to_redeem, config)?,
+            self.num_circulating_stablecoins(),
+            self.oracle_rate(oracle_box, config),
+        )
+    }
+
+    /// The base reserves and circulating StableCoins after minting
+    /// `num_to_mint` StableCoins
+    fn reserves_after_minting_stablecoin(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_mint: u64,
+        config: &ProtocolConfig,
+    ) -> Result<(NanoErg, u64)> {
+        let new_base_reserves = checked_add(
+            self.base_reserves(config),
+            self.checked_base_cost_to_mint_stablecoin(num_to_mint, oracle_box, config)?,
+            "new base reserves",
+        )?;
+        let new_circulating_stablecoins = checked_add(
+            self.num_circulating_stablecoins(),
+            num_to_mint,
+            "new circulating StableCoins",
+        )?;
+        Ok((new_base_reserves, new_circulating_stablecoins))
+    }
+
+    /// The base reserves after minting `num_to_mint` ReserveCoins
+    fn reserves_after_minting_reservecoin(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_mint: u64,
+        config: &ProtocolConfig,
+    ) -> Result<NanoErg> {
+        checked_add(
+            self.base_reserves(config),
+            self.checked_base_cost_to_mint_reservecoin(num_to_mint, oracle_box, config)?,
+            "new base reserves",
+        )
+    }
+
+    /// The base reserves after redeeming `num_to_redeem` ReserveCoins
+    fn reserves_after_redeeming_reservecoin(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        num_to_redeem: u64,
+        config: &ProtocolConfig,
+    ) -> Result<NanoErg> {
+        // The protocol fee stays within the reserves, as the contract expects
+        let redeem_amount =
+            self.checked_base_amount_from_redeeming_reservecoin(num_to_redeem, oracle_box, config)?;
+        Ok(self.base_reserves(config).saturating_sub(redeem_amount))
+    }
+
+    /// The total amount of nanoErgs which is needed to cover minting
+    /// the provided number of StableCoins, cover tx fees, implementor
+    /// fee, etc.
+    pub fn checked_total_cost_to_mint_stablecoin(
+        &self,
+        amount_to_mint: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        transaction_fee: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let base_cost =
+            self.checked_base_cost_to_mint_stablecoin(amount_to_mint, oracle_box, config)?;
+        config
+            .fee_schedule()
+            .total_mint_cost(base_cost, transaction_fee, config.min_box_value)
+    }
+
+    /// The amount of nanoErg fees for minting StableCoins.
+    /// This includes protocol fees, tx fees, and implementor fees.
+    pub fn checked_fees_from_minting_stablecoin(
+        &self,
+        amount_to_mint: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        transaction_fee: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let feeless_amount = long_mul(
+            self.stablecoin_nominal_price(oracle_box, config),
+            amount_to_mint,
+            "StableCoin feeless amount",
+        )?;
+        let base_cost =
+            self.checked_base_cost_to_mint_stablecoin(amount_to_mint, oracle_box, config)?;
+        config
+            .fee_schedule()
+            .total_fees(feeless_amount, base_cost, transaction_fee)
+    }
+
+    /// The amount of base currency (Ergs) which is needed to cover minting
+    /// the provided number of StableCoins.
+    pub fn checked_base_cost_to_mint_stablecoin(
+        &self,
+        amount_to_mint: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        config.fee_schedule().base_mint_cost(
+            self.stablecoin_nominal_price(oracle_box, config),
+            amount_to_mint,
+        )
+    }
+
+    /// The total amount of nanoErgs which is needed to cover minting
+    /// the provided number of ReserveCoins, cover tx fees, implementor
+    /// fee, etc.
+    pub fn checked_total_cost_to_mint_reservecoin(
+        &self,
+        amount_to_mint: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        transaction_fee: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let base_cost =
+            self.checked_base_cost_to_mint_reservecoin(amount_to_mint, oracle_box, config)?;
+        config
+            .fee_schedule()
+            .total_mint_cost(base_cost, transaction_fee, config.min_box_value)
+    }
+
+    /// The amount of nanoErg fees for minting ReserveCoins.
+    /// This includes protocol fees, tx fees, and implementor fees.
+    pub fn checked_fees_from_minting_reservecoin(
+        &self,
+        amount_to_mint: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        transaction_fee: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let feeless_amount = long_mul(
+            self.reservecoin_nominal_price(oracle_box, config),
+            amount_to_mint,
+            "ReserveCoin feeless amount",
+        )?;
+        let base_cost =
+            self.checked_base_cost_to_mint_reservecoin(amount_to_mint, oracle_box, config)?;
+        config
+            .fee_schedule()
+            .total_fees(feeless_amount, base_cost, transaction_fee)
+    }
+
+    /// The amount of base currency (Ergs) which is needed to cover minting
+    /// the provided number of ReserveCoins.
+    pub fn checked_base_cost_to_mint_reservecoin(
+        &self,
+        amount_to_mint: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        config.fee_schedule().base_mint_cost(
+            self.reservecoin_nominal_price(oracle_box, config),
+            amount_to_mint,
+        )
+    }
+
+    /// The amount of nanoErgs which will be redeemed
+    /// from the protocol based on current reserves + the number of
+    /// ReserveCoins being redeemed by the user after paying for
+    /// tx fees, implementor fees, etc.
+    pub fn checked_total_amount_from_redeeming_reservecoin(
+        &self,
+        amount_to_redeem: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        transaction_fee: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let base_amount = self.checked_base_amount_from_redeeming_reservecoin(
+            amount_to_redeem,
+            oracle_box,
+            config,
+        )?;
+        config
+            .fee_schedule()
+            .total_redeem_amount(base_amount, transaction_fee)
+    }
+
+    /// The amount of nanoErg fees for redeeming ReserveCoins.
+    /// This includes protocol fees, tx fees, and implementor fees.
+    pub fn checked_fees_from_redeeming_reservecoin(
+        &self,
+        amount_to_redeem: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        transaction_fee: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let feeless_amount = long_mul(
+            self.reservecoin_nominal_price(oracle_box, config),
+            amount_to_redeem,
+            "ReserveCoin feeless amount",
+        )?;
+        let base_amount = self.checked_base_amount_from_redeeming_reservecoin(
+            amount_to_redeem,
+            oracle_box,
+            config,
+        )?;
+        config
+            .fee_schedule()
+            .total_fees(feeless_amount, base_amount, transaction_fee)
+    }
+
+    /// The amount of base currency (Ergs) which will be redeemed
+    /// from the protocol based on current reserves + the number of
+    /// ReserveCoins being redeemed by the user. Includes protocol fee.
+    pub fn checked_base_amount_from_redeeming_reservecoin(
+        &self,
+        amount_to_redeem: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        config.fee_schedule().base_redeem_amount(
+            self.reservecoin_nominal_price(oracle_box, config),
+            amount_to_redeem,
+        )
+    }
+
+    /// The amount of nanoErgs which will be redeemed
+    /// from the protocol based on current reserves + the number of
+    /// StableCoins being redeemed by the user after paying for
+    /// tx fees, implementor fees, etc.
+    pub fn checked_total_amount_from_redeeming_stablecoin(
+        &self,
+        amount_to_redeem: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        transaction_fee: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let base_amount = self.checked_base_amount_from_redeeming_stablecoin(
+            amount_to_redeem,
+            oracle_box,
+            config,
+        )?;
+        config
+            .fee_schedule()
+            .total_redeem_amount(base_amount, transaction_fee)
+    }
+
+    /// The amount of nanoErg fees for redeeming StableCoins.
+    /// This includes protocol fees, tx fees, and implementor fees.
+    pub fn checked_fees_from_redeeming_stablecoin(
+        &self,
+        amount_to_redeem: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        transaction_fee: u64,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        let feeless_amount = long_mul(
+            self.stablecoin_nominal_price(oracle_box, config),
+            amount_to_redeem,
+            "StableCoin feeless amount",
+        )?;
+        let base_amount = self.checked_base_amount_from_redeeming_stablecoin(
+            amount_to_redeem,
+            oracle_box,
+            config,
+        )?;
+        config
+            .fee_schedule()
+            .total_fees(feeless_amount, base_amount, transaction_fee)
+    }
+
+    /// The amount of base currency (Ergs) which will be redeemed
+    /// from the protocol based on current reserves + the number of
+    /// StableCoins being redeemed by the user.
+    pub fn checked_base_amount_from_redeeming_stablecoin(
+        &self,
+        amount_to_redeem: u64,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<u64> {
+        config.fee_schedule().base_redeem_amount(
+            self.stablecoin_nominal_price(oracle_box, config),
+            amount_to_redeem,
+        )
     }
 }
 
 /// Rust methods related to `BankStage`
 impl BankBox {
+    /// A `BoxSpec` that checks that the box is a valid BankBox via
+    /// looking that it holds the correct StableCoin/ReserveCoin tokens
+    /// and the Bank NFT of the deployment described by `config`.
+    pub fn box_spec_for(config: &ProtocolConfig) -> BoxSpec {
+        let tok_1_spec = Some(TokenSpec::new(1..u64::MAX, &config.stablecoin_token_id));
+        let tok_2_spec = Some(TokenSpec::new(1..u64::MAX, &config.reservecoin_token_id));
+        let tok_3_spec = Some(TokenSpec::new(1..2, &config.bank_nft_id));
+        let tok_specs = vec![tok_1_spec, tok_2_spec, tok_3_spec];
+        BoxSpec::new_predicated(None, None, vec![], tok_specs, Some(Self::predicate))
+    }
+
+    /// Wrap an `ErgoBox` which is a valid BankBox of the deployment
+    /// described by `config`
+    pub fn new_with_config(b: &ErgoBox, config: &ProtocolConfig) -> Result<BankBox> {
+        BankBox::box_spec_for(config).verify_box(b)?;
+        Ok(BankBox {
+            ergo_box: b.clone(),
+        })
+    }
+
+    /// The specs of R4/R5 of the Bank box
+    pub fn register_specs() -> Vec<RegisterSpec> {
+        vec![
+            RegisterSpec::new("circulating StableCoins", RegisterType::NonNegativeLong),
+            RegisterSpec::new("circulating ReserveCoins", RegisterType::NonNegativeLong),
+        ]
+    }
+
+    /// A predicate to check the types of the registers
+    pub fn predicate(b: &ErgoBox) -> bool {
+        verify_registers(b, &Self::register_specs()).is_ok()
+    }
+
+    /// The number of StableCoins currently in circulation, held in R4
+    pub fn try_num_circulating_stablecoins(&self) -> Result<u64> {
+        decode_non_negative_long(&self.ergo_box, 0, "circulating StableCoins")
+    }
+
+    /// The number of ReserveCoins currently in circulation, held in R5
+    pub fn try_num_circulating_reservecoins(&self) -> Result<u64> {
+        decode_non_negative_long(&self.ergo_box, 1, "circulating ReserveCoins")
+    }
+
+    /// The number of StableCoins currently in circulation, held in R4
+    pub fn num_circulating_stablecoins(&self) -> u64 {
+        // Using unwrap because the `BoxSpec` predicate guarantees
+        // we have a valid box at the `Bank` stage.
+        self.try_num_circulating_stablecoins().unwrap()
+    }
+
+    /// The number of ReserveCoins currently in circulation, held in R5
+    pub fn num_circulating_reservecoins(&self) -> u64 {
+        // Using unwrap because the `BoxSpec` predicate guarantees
+        // we have a valid box at the `Bank` stage.
+        self.try_num_circulating_reservecoins().unwrap()
+    }
+
+    /// Detect the `ContractVersion` the Bank box was compiled from via its
+    /// `ErgoTree`. If several versions are possible the one of `config` is
+    /// preferred, otherwise the latest. `None` if the version is unknown.
+    pub fn detect_contract_version(&self, config: &ProtocolConfig) -> Option<ContractVersion> {
+        let possible_versions = ContractVersion::possible_versions(&self.ergo_box.ergo_tree);
+        if possible_versions.contains(&config.contract_version) {
+            return Some(config.contract_version);
+        }
+        possible_versions.last().copied()
+    }
+
     /// Create an `ErgoBoxCandidate` for the output Bank box for the
     /// `Mint ReserveCoin` action
     pub fn create_mint_reservecoin_candidate(
@@ -553,25 +1251,38 @@ impl BankBox {
         circulating_reservecoins_out: u64,
         reservecoin_value_in_base: NanoErg,
         input_bank_box: &BankBox,
-    ) -> Result<ErgoBoxCandidate, ProtocolError> {
+        config: &ProtocolConfig,
+    ) -> Result<ErgoBoxCandidate> {
         // Specify the tokens in the Bank Box
         let stablecoin_tokens = self.tokens()[0].clone();
         // Specify ReserveCoins
         let bank_reservecoin_token_in = self.tokens()[1].clone();
         let reservecoin_tokens = build_token(
-            RESERVECOIN_TOKEN_ID,
-            u64::from(bank_reservecoin_token_in.amount) - amount_to_mint,
+            &config.reservecoin_token_id,
+            checked_sub(
+                u64::from(bank_reservecoin_token_in.amount),
+                amount_to_mint,
+                "Bank ReserveCoin tokens",
+            )?,
         )?;
         let nft_token = self.tokens()[2].clone();
         let obb_tokens = vec![stablecoin_tokens, reservecoin_tokens, nft_token];
         // Specify the registers in the Bank Box
         let registers_vec = vec![
-            (self.num_circulating_stablecoins() as i64).into(),
-            (circulating_reservecoins_out as i64).into(),
+            to_long(
+                self.num_circulating_stablecoins(),
+                "circulating StableCoins",
+            )?
+            .into(),
+            to_long(circulating_reservecoins_out, "circulating ReserveCoins")?.into(),
         ];
         // Creating the output Bank box candidate
         let output_bank_candidate = create_candidate(
-            self.nano_ergs() + reservecoin_value_in_base,
+            checked_add(
+                self.nano_ergs(),
+                reservecoin_value_in_base,
+                "Bank box value",
+            )?,
             &input_bank_box.p2s_address(),
             &obb_tokens,
             &registers_vec,
@@ -580,7 +1291,7 @@ impl BankBox {
 
         // Verify the output Bank box is a valid Bank box
         let processed_box = &ErgoBox::from_box_candidate(&output_bank_candidate, TxId::zero(), 0);
-        BankBox::box_spec().verify_box(processed_box)?;
+        BankBox::box_spec_for(config).verify_box(processed_box)?;
 
         Ok(output_bank_candidate)
     }
@@ -594,23 +1305,32 @@ impl BankBox {
         circulating_stablecoins_out: u64,
         stablecoin_value_in_base: u64,
         input_bank_box: &BankBox,
-    ) -> Result<ErgoBoxCandidate, ProtocolError> {
+        config: &ProtocolConfig,
+    ) -> Result<ErgoBoxCandidate> {
         let bank_stablecoin_token_in = self.tokens()[0].clone();
         let stablecoin_tokens = build_token(
-            STABLECOIN_TOKEN_ID,
-            u64::from(bank_stablecoin_token_in.amount) - amount_to_mint,
+            &config.stablecoin_token_id,
+            checked_sub(
+                u64::from(bank_stablecoin_token_in.amount),
+                amount_to_mint,
+                "Bank StableCoin tokens",
+            )?,
         )?;
         let reservecoin_tokens = self.tokens()[1].clone();
         let nft_token = self.tokens()[2].clone();
         let obb_tokens = vec![stablecoin_tokens, reservecoin_tokens, nft_token];
         // Specify the registers in the Bank Box
         let registers_vec = vec![
-            (circulating_stablecoins_out as i64).into(),
-            (self.num_circulating_reservecoins() as i64).into(),
+            to_long(circulating_stablecoins_out, "circulating StableCoins")?.into(),
+            to_long(
+                self.num_circulating_reservecoins(),
+                "circulating ReserveCoins",
+            )?
+            .into(),
         ];
         // Creating the output Bank box candidate
         let output_bank_candidate = create_candidate(
-            self.nano_ergs() + stablecoin_value_in_base,
+            checked_add(self.nano_ergs(), stablecoin_value_in_base, "Bank box value")?,
             &input_bank_box.p2s_address(),
             &obb_tokens,
             &registers_vec,
@@ -619,7 +1339,7 @@ impl BankBox {
 
         // Verify the output Bank box is a valid Bank box
         let processed_box = &ErgoBox::from_box_candidate(&output_bank_candidate, TxId::zero(), 0);
-        BankBox::box_spec().verify_box(processed_box)?;
+        BankBox::box_spec_for(config).verify_box(processed_box)?;
 
         Ok(output_bank_candidate)
     }
@@ -633,25 +1353,38 @@ impl BankBox {
         circulating_reservecoins_out: u64,
         reservecoin_value_in_base: u64,
         input_bank_box: &BankBox,
-    ) -> Result<ErgoBoxCandidate, ProtocolError> {
+        config: &ProtocolConfig,
+    ) -> Result<ErgoBoxCandidate> {
         // Specify the tokens in the Bank Box
         let stablecoin_tokens = self.tokens()[0].clone();
         // Specifying ReserveCoins
         let bank_reservecoin_token_in = self.tokens()[1].clone();
         let reservecoin_tokens = build_token(
-            RESERVECOIN_TOKEN_ID,
-            u64::from(bank_reservecoin_token_in.amount) + amount_to_redeem,
+            &config.reservecoin_token_id,
+            checked_add(
+                u64::from(bank_reservecoin_token_in.amount),
+                amount_to_redeem,
+                "Bank ReserveCoin tokens",
+            )?,
         )?;
         let nft_token = self.tokens()[2].clone();
         let obb_tokens = vec![stablecoin_tokens, reservecoin_tokens, nft_token];
         // Specify the registers in the Bank Box
         let registers_vec = vec![
-            (self.num_circulating_stablecoins() as i64).into(),
-            (circulating_reservecoins_out as i64).into(),
+            to_long(
+                self.num_circulating_stablecoins(),
+                "circulating StableCoins",
+            )?
+            .into(),
+            to_long(circulating_reservecoins_out, "circulating ReserveCoins")?.into(),
         ];
         // Creating the output Bank box candidate
         let output_bank_candidate = create_candidate(
-            self.nano_ergs() - reservecoin_value_in_base,
+            checked_sub(
+                self.nano_ergs(),
+                reservecoin_value_in_base,
+                "Bank box value",
+            )?,
             &input_bank_box.p2s_address(),
             &obb_tokens,
             &registers_vec,
@@ -660,7 +1393,7 @@ impl BankBox {
 
         // Verify the output Bank box is a valid Bank box
         let processed_box = &ErgoBox::from_box_candidate(&output_bank_candidate, TxId::zero(), 0);
-        BankBox::box_spec().verify_box(processed_box)?;
+        BankBox::box_spec_for(config).verify_box(processed_box)?;
 
         Ok(output_bank_candidate)
     }
@@ -674,23 +1407,32 @@ impl BankBox {
         circulating_stablecoins_out: u64,
         stablecoin_value_in_base: u64,
         input_bank_box: &BankBox,
-    ) -> Result<ErgoBoxCandidate, ProtocolError> {
+        config: &ProtocolConfig,
+    ) -> Result<ErgoBoxCandidate> {
         let bank_stablecoin_token_in = self.tokens()[0].clone();
         let stablecoin_tokens = build_token(
-            STABLECOIN_TOKEN_ID,
-            u64::from(bank_stablecoin_token_in.amount) + amount_to_redeem,
+            &config.stablecoin_token_id,
+            checked_add(
+                u64::from(bank_stablecoin_token_in.amount),
+                amount_to_redeem,
+                "Bank StableCoin tokens",
+            )?,
         )?;
         let reservecoin_tokens = self.tokens()[1].clone();
         let nft_token = self.tokens()[2].clone();
         let obb_tokens = vec![stablecoin_tokens, reservecoin_tokens, nft_token];
         // Specify the registers in the Bank Box
         let registers_vec = vec![
-            (circulating_stablecoins_out as i64).into(),
-            (self.num_circulating_reservecoins() as i64).into(),
+            to_long(circulating_stablecoins_out, "circulating StableCoins")?.into(),
+            to_long(
+                self.num_circulating_reservecoins(),
+                "circulating ReserveCoins",
+            )?
+            .into(),
         ];
         // Creating the output Bank box candidate
         let output_bank_candidate = create_candidate(
-            self.nano_ergs() - stablecoin_value_in_base,
+            checked_sub(self.nano_ergs(), stablecoin_value_in_base, "Bank box value")?,
             &input_bank_box.p2s_address(),
             &obb_tokens,
             &registers_vec,
@@ -699,7 +1441,7 @@ impl BankBox {
 
         // Verify the output Bank box is a valid Bank box
         let processed_box = &ErgoBox::from_box_candidate(&output_bank_candidate, TxId::zero(), 0);
-        BankBox::box_spec().verify_box(processed_box)?;
+        BankBox::box_spec_for(config).verify_box(processed_box)?;
 
         Ok(output_bank_candidate)
     }
@@ -711,7 +1453,7 @@ impl BankBox {
         update_address: &P2SAddressString,
         input_bank_box: &BankBox,
         current_height: BlockHeight,
-    ) -> Result<ErgoBoxCandidate, ProtocolError> {
+    ) -> Result<ErgoBoxCandidate> {
         let output_bank_candidate = create_candidate(
             input_bank_box.nano_ergs(),
             update_address,
@@ -0,0 +1,204 @@
+// This file holds the `BlockTimeEstimator`, which projects how long it will
+// take for the chain to reach a given block height. Protocol parameters such
+// as the cooling off height are defined in blocks, so this is used to present
+// them to users in terms of time.
+use crate::error::{ProtocolError, Result};
+use ergo_headless_dapp_framework::BlockHeight;
+use wasm_bindgen::prelude::*;
+
+/// The targeted time between Ergo blocks in milliseconds (2 minutes)
+pub const TARGET_BLOCK_TIME_MS: u64 = 120_000;
+
+const MS_PER_MINUTE: u64 = 60_000;
+const MS_PER_HOUR: u64 = 60 * MS_PER_MINUTE;
+const MS_PER_DAY: u64 = 24 * MS_PER_HOUR;
+
+/// Estimates the time at which future block heights will be reached based
+/// off of an average time between blocks
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTimeEstimator {
+    /// The average time between blocks in milliseconds
+    pub average_block_time_ms: u64,
+}
+
+/// WASM-supported methods related to `BlockTimeEstimator`
+#[wasm_bindgen]
+impl BlockTimeEstimator {
+    /// Create a new `BlockTimeEstimator` using the provided average time
+    /// between blocks in milliseconds
+    #[wasm_bindgen(constructor)]
+    pub fn new(average_block_time_ms: u64) -> BlockTimeEstimator {
+        BlockTimeEstimator {
+            average_block_time_ms,
+        }
+    }
+
+    /// A `BlockTimeEstimator` which assumes blocks are produced at the
+    /// targeted block time of Ergo
+    #[wasm_bindgen]
+    pub fn target() -> BlockTimeEstimator {
+        BlockTimeEstimator::new(TARGET_BLOCK_TIME_MS)
+    }
+
+    /// Create a `BlockTimeEstimator` from the timestamps (in milliseconds)
+    /// of a number of consecutive recent block headers
+    #[wasm_bindgen]
+    pub fn w_from_header_timestamps(
+        timestamps: Vec<u64>,
+    ) -> std::result::Result<BlockTimeEstimator, JsValue> {
+        BlockTimeEstimator::from_header_timestamps(&timestamps)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// The number of milliseconds until `target_height` is reached.
+    /// Returns 0 if `target_height` has already been reached.
+    #[wasm_bindgen]
+    pub fn ms_until_height(&self, current_height: BlockHeight, target_height: BlockHeight) -> u64 {
+        target_height
+            .saturating_sub(current_height)
+            .saturating_mul(self.average_block_time_ms)
+    }
+
+    /// The estimated timestamp (in milliseconds) at which `target_height`
+    /// will be reached, given the timestamp of the block at `current_height`
+    #[wasm_bindgen]
+    pub fn timestamp_at_height(
+        &self,
+        current_height: BlockHeight,
+        current_timestamp: u64,
+        target_height: BlockHeight,
+    ) -> u64 {
+        current_timestamp.saturating_add(self.ms_until_height(current_height, target_height))
+    }
+
+    /// A human readable description of the time until `target_height`,
+    /// such as "~12 days"
+    #[wasm_bindgen]
+    pub fn describe_time_until_height(
+        &self,
+        current_height: BlockHeight,
+        target_height: BlockHeight,
+    ) -> String {
+        describe_duration(self.ms_until_height(current_height, target_height))
+    }
+}
+
+/// Rust methods related to `BlockTimeEstimator`
+impl BlockTimeEstimator {
+    /// Create a `BlockTimeEstimator` from the timestamps (in milliseconds)
+    /// of a number of consecutive recent block headers. The timestamps may
+    /// be provided in any order.
+    pub fn from_header_timestamps(timestamps: &[u64]) -> Result<BlockTimeEstimator> {
+        let (first, last) = match (timestamps.iter().min(), timestamps.iter().max()) {
+            (Some(first), Some(last)) if timestamps.len() >= 2 => (*first, *last),
+            _ => {
+                return Err(ProtocolError::InvalidInputValue(
+                    "At least 2 header timestamps are required to estimate the block time."
+                        .to_string(),
+                ))
+            }
+        };
+        let average_block_time_ms = (last - first) / (timestamps.len() as u64 - 1);
+        if average_block_time_ms == 0 {
+            return Err(ProtocolError::InvalidInputValue(
+                "The header timestamps do not span any time.".to_string(),
+            ));
+        }
+        Ok(BlockTimeEstimator::new(average_block_time_ms))
+    }
+}
+
+impl Default for BlockTimeEstimator {
+    fn default() -> BlockTimeEstimator {
+        BlockTimeEstimator::target()
+    }
+}
+
+/// A human readable, rounded description of a duration in milliseconds,
+/// such as "~12 days", "~5 hours" or "~8 minutes"
+pub fn describe_duration(ms: u64) -> String {
+    let (amount, unit) = if ms >= 2 * MS_PER_DAY {
+        (rounded_div(ms, MS_PER_DAY), "day")
+    } else if ms >= 2 * MS_PER_HOUR {
+        (rounded_div(ms, MS_PER_HOUR), "hour")
+    } else {
+        (rounded_div(ms, MS_PER_MINUTE), "minute")
+    };
+    if amount == 1 {
+        format!("~{} {}", amount, unit)
+    } else {
+        format!("~{} {}s", amount, unit)
+    }
+}
+
+fn rounded_div(a: u64, b: u64) -> u64 {
+    a / b + if a % b >= b / 2 { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_input(result: Result<BlockTimeEstimator>) {
+        match result {
+            Err(ProtocolError::InvalidInputValue(_)) => (),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn from_header_timestamps() {
+        let ordered = [1_000, 121_000, 239_000, 361_000];
+        let estimator = BlockTimeEstimator::from_header_timestamps(&ordered).unwrap();
+        assert_eq!(estimator.average_block_time_ms, 120_000);
+
+        let unordered = [239_000, 1_000, 361_000, 121_000];
+        assert_eq!(
+            BlockTimeEstimator::from_header_timestamps(&unordered).unwrap(),
+            estimator
+        );
+
+        // Fewer than 2 timestamps span no blocks
+        invalid_input(BlockTimeEstimator::from_header_timestamps(&[]));
+        invalid_input(BlockTimeEstimator::from_header_timestamps(&[1_000]));
+        // Timestamps which do not span at least 1 ms per block
+        invalid_input(BlockTimeEstimator::from_header_timestamps(&[5, 5, 5]));
+        invalid_input(BlockTimeEstimator::from_header_timestamps(&[0, 1, 1]));
+    }
+
+    #[test]
+    fn time_until_height() {
+        let estimator = BlockTimeEstimator::target();
+        assert_eq!(
+            estimator.ms_until_height(100, 110),
+            10 * TARGET_BLOCK_TIME_MS
+        );
+        assert_eq!(estimator.ms_until_height(110, 100), 0);
+        assert_eq!(
+            estimator.timestamp_at_height(100, 1_000, 101),
+            1_000 + TARGET_BLOCK_TIME_MS
+        );
+        assert_eq!(
+            estimator.describe_time_until_height(0, 720 * 12),
+            "~12 days"
+        );
+    }
+
+    #[test]
+    fn describe_duration_rounds_to_the_nearest_unit() {
+        assert_eq!(describe_duration(0), "~0 minutes");
+        assert_eq!(describe_duration(MS_PER_MINUTE / 2 - 1), "~0 minutes");
+        assert_eq!(describe_duration(MS_PER_MINUTE / 2), "~1 minute");
+        assert_eq!(describe_duration(90 * MS_PER_MINUTE), "~90 minutes");
+        assert_eq!(describe_duration(2 * MS_PER_HOUR - 1), "~120 minutes");
+        assert_eq!(describe_duration(2 * MS_PER_HOUR), "~2 hours");
+        assert_eq!(describe_duration(2 * MS_PER_DAY - 1), "~48 hours");
+        assert_eq!(describe_duration(2 * MS_PER_DAY), "~2 days");
+        assert_eq!(
+            describe_duration(12 * MS_PER_DAY + 12 * MS_PER_HOUR),
+            "~13 days"
+        );
+        assert_eq!(describe_duration(u64::MAX), "~213503982335 days");
+    }
+}
@@ -0,0 +1,507 @@
+// This file holds the `GenesisBuilder`, which builds the sequence of
+// transactions that bootstraps a new deployment of the protocol. Each token
+// (Bank NFT, StableCoin, ReserveCoin, Update NFT & ballot tokens) is minted
+// in its own transaction, as Ergo allows a single new token per transaction
+// whose ID is the box id of the first input. As such every token ID is
+// known as soon as its minting transaction is built. Once every token is
+// minted the initial Bank & Update boxes are created, after which the
+// resulting `ProtocolConfig` is the deployment parameters file.
+use crate::contract_diff::{constant_segment, TreeConstant};
+use crate::equations::{checked_add, checked_sub, to_long};
+use crate::error::{ProtocolError, Result};
+use crate::parameters::ProtocolConfig;
+use ergo_headless_dapp_framework::encoding::{
+    build_token, deserialize_p2s_to_ergo_tree, hash_and_serialize_p2s, serialize_hex_encoded_string,
+};
+use ergo_headless_dapp_framework::{
+    create_candidate, ErgsBox, TokensChangeBox, TxFeeBox, WrappedBox,
+};
+use ergo_headless_dapp_framework::{BlockHeight, NanoErg, P2PKAddressString, P2SAddressString};
+use ergo_lib::chain::ergo_box::ErgoBox;
+use ergo_lib::chain::transaction::unsigned::UnsignedTransaction;
+use ergo_lib::chain::transaction::UnsignedInput;
+use ergo_lib::serialization::SigmaSerializable;
+use std::path::Path;
+
+/// The number of StableCoins/ReserveCoins minted at genesis, as in
+/// `ageusd-smart-contracts/v0.4/AgeUSD.scala`
+pub const COIN_TOKEN_SUPPLY: u64 = 10000000000000;
+
+/// The tokens minted during the genesis of a deployment, in the order in
+/// which they are minted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenesisToken {
+    BankNft,
+    StableCoin,
+    ReserveCoin,
+    UpdateNft,
+    BallotToken,
+}
+
+impl GenesisToken {
+    /// All of the genesis tokens, in the order in which they are minted
+    pub fn all() -> Vec<GenesisToken> {
+        vec![
+            GenesisToken::BankNft,
+            GenesisToken::StableCoin,
+            GenesisToken::ReserveCoin,
+            GenesisToken::UpdateNft,
+            GenesisToken::BallotToken,
+        ]
+    }
+
+    /// The name of the token as set in its metadata
+    pub fn name(&self) -> &'static str {
+        match self {
+            GenesisToken::BankNft => "AgeUSD Bank NFT",
+            GenesisToken::StableCoin => "AgeUSD",
+            GenesisToken::ReserveCoin => "AgeRSV",
+            GenesisToken::UpdateNft => "AgeUSD Update NFT",
+            GenesisToken::BallotToken => "AgeUSD Ballot Token",
+        }
+    }
+
+    /// The description of the token as set in its metadata
+    pub fn description(&self) -> &'static str {
+        match self {
+            GenesisToken::BankNft => "Identifies the Bank box of the AgeUSD protocol.",
+            GenesisToken::StableCoin => "The StableCoin of the AgeUSD protocol.",
+            GenesisToken::ReserveCoin => "The ReserveCoin of the AgeUSD protocol.",
+            GenesisToken::UpdateNft => "Identifies the Update box of the AgeUSD protocol.",
+            GenesisToken::BallotToken => "Voting power for updating the AgeUSD protocol.",
+        }
+    }
+
+    /// The number of decimals of the token. StableCoins are minted in cents.
+    pub fn decimals(&self) -> u64 {
+        match self {
+            GenesisToken::StableCoin => 2,
+            _ => 0,
+        }
+    }
+
+    /// The name of the `ProtocolConfig` field which holds the token ID
+    pub fn config_field(&self) -> &'static str {
+        match self {
+            GenesisToken::BankNft => "bank_nft_id",
+            GenesisToken::StableCoin => "stablecoin_token_id",
+            GenesisToken::ReserveCoin => "reservecoin_token_id",
+            GenesisToken::UpdateNft => "update_nft_id",
+            GenesisToken::BallotToken => "update_ballot_token_id",
+        }
+    }
+}
+
+/// An address which receives ballot tokens at genesis
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BallotHolder {
+    /// The address the Ballot box is guarded by
+    pub address: P2PKAddressString,
+    /// The number of ballot tokens the holder receives
+    pub num_ballot_tokens: u64,
+}
+
+/// Builds the genesis sequence of a new deployment of the protocol
+#[derive(Debug, Clone)]
+pub struct GenesisBuilder {
+    /// The parameters of the deployment, which are filled in with the
+    /// token IDs as they are minted
+    config: ProtocolConfig,
+    /// The holders of the ballot tokens
+    ballot_holders: Vec<BallotHolder>,
+}
+
+impl GenesisBuilder {
+    /// Create a new `GenesisBuilder`. The token IDs held in `base_config`
+    /// are replaced as each token is minted, while every other parameter is
+    /// kept as is. The ballot holders must together hold at least
+    /// `min_votes` ballot tokens, or no update could ever be collected.
+    pub fn new(
+        base_config: &ProtocolConfig,
+        ballot_holders: Vec<BallotHolder>,
+    ) -> Result<GenesisBuilder> {
+        let mut total_ballot_tokens: u64 = 0;
+        for holder in &ballot_holders {
+            if holder.num_ballot_tokens == 0 {
+                return Err(ProtocolError::InvalidInputValue(format!(
+                    "The ballot holder {} receives no ballot tokens.",
+                    holder.address
+                )));
+            }
+            total_ballot_tokens = checked_add(
+                total_ballot_tokens,
+                holder.num_ballot_tokens,
+                "total ballot tokens",
+            )?;
+        }
+        if total_ballot_tokens < base_config.min_votes {
+            return Err(ProtocolError::InvalidInputValue(format!(
+                "The ballot holders hold {} ballot tokens, but {} are required to collect votes.",
+                total_ballot_tokens, base_config.min_votes
+            )));
+        }
+
+        let mut config = base_config.clone();
+        for token in GenesisToken::all() {
+            set_token_id(&mut config, token, String::new());
+        }
+        Ok(GenesisBuilder {
+            config,
+            ballot_holders,
+        })
+    }
+
+    /// The parameters of the deployment built thus far
+    pub fn config(&self) -> &ProtocolConfig {
+        &self.config
+    }
+
+    /// The ID of the token, if its minting transaction has been built
+    pub fn token_id(&self, token: GenesisToken) -> Option<String> {
+        let token_id = match token {
+            GenesisToken::BankNft => &self.config.bank_nft_id,
+            GenesisToken::StableCoin => &self.config.stablecoin_token_id,
+            GenesisToken::ReserveCoin => &self.config.reservecoin_token_id,
+            GenesisToken::UpdateNft => &self.config.update_nft_id,
+            GenesisToken::BallotToken => &self.config.update_ballot_token_id,
+        };
+        if token_id.is_empty() {
+            return None;
+        }
+        Some(token_id.clone())
+    }
+
+    /// The next token to be minted, `None` once every token is minted
+    pub fn next_token(&self) -> Option<GenesisToken> {
+        GenesisToken::all()
+            .into_iter()
+            .find(|t| self.token_id(*t).is_none())
+    }
+
+    /// The number of ballot tokens minted across all of the holders
+    pub fn total_ballot_tokens(&self) -> u64 {
+        self.ballot_holders
+            .iter()
+            .fold(0, |acc, h| acc + h.num_ballot_tokens)
+    }
+
+    /// Action: Mint Genesis Token
+    /// Mints the token, recording its ID (the box id of the first input box).
+    /// The StableCoin & ReserveCoin supplies and the NFTs are sent to the
+    /// `user_address`, while the ballot tokens are sent to a Ballot box
+    /// (with no vote cast) for each of the ballot holders.
+    pub fn action_mint_token(
+        &mut self,
+        token: GenesisToken,
+        input_boxes: &[ErgsBox],
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        user_address: &P2PKAddressString,
+    ) -> Result<UnsignedTransaction> {
+        let first_input_box = input_boxes
+            .first()
+            .ok_or(ProtocolError::InsufficientNumberOfBoxes())?;
+        let token_id = first_input_box.box_id();
+        let min_box_value = self.config.min_box_value;
+
+        // Create the output box candidates holding the minted tokens
+        let mut output_candidates = vec![];
+        if token == GenesisToken::BallotToken {
+            // R4/R5 are set as empty byte collections so the boxes are
+            // `BallotBox`es which have not cast a vote yet
+            let empty_constant = serialize_hex_encoded_string(&String::new())
+                .map_err(|_| ProtocolError::InvalidRegisterValues())?;
+            for holder in &self.ballot_holders {
+                output_candidates.push(create_candidate(
+                    min_box_value,
+                    &holder.address,
+                    &vec![build_token(&token_id, holder.num_ballot_tokens)?],
+                    &vec![empty_constant.clone(), empty_constant.clone()],
+                    current_height,
+                )?);
+            }
+        } else {
+            let amount = match token {
+                GenesisToken::StableCoin | GenesisToken::ReserveCoin => COIN_TOKEN_SUPPLY,
+                _ => 1,
+            };
+            // Set R4-R6 to the token's name, description & decimals
+            let registers_vec = [
+                token.name().to_string(),
+                token.description().to_string(),
+                token.decimals().to_string(),
+            ]
+            .iter()
+            .map(|value| serialize_hex_encoded_string(&utf8_hex(value)))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| ProtocolError::InvalidRegisterValues())?;
+            output_candidates.push(create_candidate(
+                min_box_value,
+                user_address,
+                &vec![build_token(&token_id, amount)?],
+                &registers_vec,
+                current_height,
+            )?);
+        }
+
+        // Number of nanoErgs left over from the inputs after paying for the
+        // outputs & the fee
+        let mut required_nano_ergs = transaction_fee;
+        for candidate in &output_candidates {
+            required_nano_ergs = checked_add(
+                required_nano_ergs,
+                *candidate.value.as_u64(),
+                "required nanoErgs",
+            )?;
+        }
+        let input_nano_ergs = sum_nano_ergs(input_boxes)?;
+        if input_nano_ergs < required_nano_ergs {
+            return Err(ProtocolError::InsufficientNanoErgs(required_nano_ergs));
+        }
+        let left_over_nano_ergs =
+            checked_sub(input_nano_ergs, required_nano_ergs, "left over nanoErgs")?;
+
+        let change_box_candidate = TokensChangeBox::output_candidate(
+            &unwrap_boxes(input_boxes),
+            left_over_nano_ergs,
+            user_address,
+            current_height,
+        )?;
+        output_candidates.push(change_box_candidate);
+
+        // Create the Transaction Fee box candidate
+        let transaction_fee_box_candidate =
+            TxFeeBox::output_candidate(transaction_fee, current_height)?;
+        output_candidates.push(transaction_fee_box_candidate);
+
+        // Defining inputs
+        let tx_inputs: Vec<UnsignedInput> =
+            input_boxes.iter().map(|b| b.get_box().into()).collect();
+
+        let unsigned_tx = UnsignedTransaction::new(tx_inputs, vec![], output_candidates);
+
+        set_token_id(&mut self.config, token, token_id);
+        Ok(unsigned_tx)
+    }
+
+    /// Action: Create Bank & Update Boxes
+    /// Creates the initial Bank box, holding the full StableCoin/ReserveCoin
+    /// supplies, the Bank NFT, `bank_nano_ergs` and R4/R5 set to 0, along
+    /// with the initial Update box, holding the Update NFT and the hash of
+    /// the Bank contract in R4. The `input_boxes` must hold these tokens.
+    /// As the contracts are compiled with the token IDs embedded, every
+    /// token must be minted first, and the `bank_address` must embed the
+    /// Update NFT while the `update_address` must embed the Bank NFT &
+    /// the ballot token.
+    pub fn action_create_bank_and_update_boxes(
+        &self,
+        bank_address: &P2SAddressString,
+        update_address: &P2SAddressString,
+        input_boxes: &[ErgsBox],
+        bank_nano_ergs: NanoErg,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        user_address: &P2PKAddressString,
+    ) -> Result<UnsignedTransaction> {
+        if let Some(token) = self.next_token() {
+            return Err(ProtocolError::InvalidTokens(format!(
+                "The {} has not been minted yet.",
+                token.name()
+            )));
+        }
+        self.check_embedded_tokens(bank_address, &[GenesisToken::UpdateNft])?;
+        self.check_embedded_tokens(
+            update_address,
+            &[GenesisToken::BankNft, GenesisToken::BallotToken],
+        )?;
+        let min_box_value = self.config.min_box_value;
+        if bank_nano_ergs < min_box_value {
+            return Err(ProtocolError::InvalidBoxValue(bank_nano_ergs));
+        }
+
+        // Specify the tokens of the Bank & Update boxes
+        let bank_tokens = vec![
+            build_token(&self.config.stablecoin_token_id, COIN_TOKEN_SUPPLY)?,
+            build_token(&self.config.reservecoin_token_id, COIN_TOKEN_SUPPLY)?,
+            build_token(&self.config.bank_nft_id, 1)?,
+        ];
+        let update_tokens = vec![build_token(&self.config.update_nft_id, 1)?];
+        let input_ergo_boxes = unwrap_boxes(input_boxes);
+        for token in bank_tokens.iter().chain(update_tokens.iter()) {
+            let token_id: String = token.token_id.0.clone().into();
+            let required = u64::from(token.amount);
+            if sum_token_amount(&input_ergo_boxes, &token_id)? < required {
+                return Err(ProtocolError::InvalidTokens(format!(
+                    "The input boxes hold less than {} of the token {}.",
+                    required, token_id
+                )));
+            }
+        }
+
+        // Creating the initial Bank box candidate with no coins in circulation
+        let bank_registers = vec![
+            to_long(0, "circulating StableCoins")?.into(),
+            to_long(0, "circulating ReserveCoins")?.into(),
+        ];
+        let bank_box_candidate = create_candidate(
+            bank_nano_ergs,
+            bank_address,
+            &bank_tokens,
+            &bank_registers,
+            current_height,
+        )?;
+
+        // Creating the initial Update box candidate, holding the hash of the
+        // current Bank contract as its control value
+        let update_registers = vec![hash_and_serialize_p2s(bank_address)?];
+        let update_box_candidate = create_candidate(
+            min_box_value,
+            update_address,
+            &update_tokens,
+            &update_registers,
+            current_height,
+        )?;
+
+        // Number of nanoErgs left over from the inputs after paying for the
+        // Bank/Update boxes & the fee
+        let required_nano_ergs = checked_add(
+            checked_add(bank_nano_ergs, min_box_value, "required nanoErgs")?,
+            transaction_fee,
+            "required nanoErgs",
+        )?;
+        let input_nano_ergs = sum_nano_ergs(input_boxes)?;
+        if input_nano_ergs < required_nano_ergs {
+            return Err(ProtocolError::InsufficientNanoErgs(required_nano_ergs));
+        }
+        let left_over_nano_ergs =
+            checked_sub(input_nano_ergs, required_nano_ergs, "left over nanoErgs")?;
+
+        let mut spent_tokens = bank_tokens.clone();
+        spent_tokens.extend(update_tokens.clone());
+        let change_box_candidate = TokensChangeBox::output_candidate_filtered(
+            &spent_tokens,
+            &input_ergo_boxes,
+            left_over_nano_ergs,
+            user_address,
+            current_height,
+        )?;
+
+        // Create the Transaction Fee box candidate
+        let transaction_fee_box_candidate =
+            TxFeeBox::output_candidate(transaction_fee, current_height)?;
+
+        // Defining inputs
+        let tx_inputs: Vec<UnsignedInput> =
+            input_boxes.iter().map(|b| b.get_box().into()).collect();
+
+        let unsigned_tx = UnsignedTransaction::new(
+            tx_inputs,
+            vec![],
+            vec![
+                bank_box_candidate,
+                update_box_candidate,
+                change_box_candidate,
+                transaction_fee_box_candidate,
+            ],
+        );
+
+        Ok(unsigned_tx)
+    }
+
+    /// Check that the contract at the P2S address was compiled with the
+    /// IDs of the minted tokens held in its constant segment
+    fn check_embedded_tokens(
+        &self,
+        address: &P2SAddressString,
+        tokens: &[GenesisToken],
+    ) -> Result<()> {
+        let tree = deserialize_p2s_to_ergo_tree(address.clone())
+            .map_err(|_| ProtocolError::InvalidP2SAddress(address.clone()))?;
+        let constants = constant_segment(&tree.sigma_serialize_bytes()).ok_or_else(|| {
+            ProtocolError::InvalidConfig(format!(
+                "The constants of the contract at {} could not be read.",
+                address
+            ))
+        })?;
+        for token in tokens {
+            let token_id = self.token_id(*token).unwrap_or_default();
+            if !constants.contains(&TreeConstant::Bytes(token_id.clone())) {
+                return Err(ProtocolError::InvalidConfig(format!(
+                    "The contract at {} does not embed the {} {}.",
+                    address,
+                    token.name(),
+                    token_id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The `ProtocolConfig` of the deployment. Errors if any of the
+    /// genesis tokens has not been minted yet.
+    pub fn deployment_config(&self) -> Result<ProtocolConfig> {
+        if let Some(token) = self.next_token() {
+            return Err(ProtocolError::InvalidConfig(format!(
+                "`{}` is not set as the {} has not been minted yet.",
+                token.config_field(),
+                token.name()
+            )));
+        }
+        self.config.validate()?;
+        Ok(self.config.clone())
+    }
+
+    /// Write the deployment parameters file. Files ending in `.json` are
+    /// written as JSON, everything else is written as TOML.
+    pub fn save_deployment_config(&self, path: &Path) -> Result<()> {
+        let config = self.deployment_config()?;
+        let contents = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => config.to_json()?,
+            _ => config.to_toml()?,
+        };
+        std::fs::write(path, contents)
+            .map_err(|e| ProtocolError::InvalidConfig(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Set the field of the `ProtocolConfig` which holds the token's ID
+fn set_token_id(config: &mut ProtocolConfig, token: GenesisToken, token_id: String) {
+    match token {
+        GenesisToken::BankNft => config.bank_nft_id = token_id,
+        GenesisToken::StableCoin => config.stablecoin_token_id = token_id,
+        GenesisToken::ReserveCoin => config.reservecoin_token_id = token_id,
+        GenesisToken::UpdateNft => config.update_nft_id = token_id,
+        GenesisToken::BallotToken => config.update_ballot_token_id = token_id,
+    }
+}
+
+/// Encode a string as the hex of its UTF-8 bytes
+fn utf8_hex(value: &str) -> String {
+    value.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The `ErgoBox`es wrapped by the `ErgsBox`es
+fn unwrap_boxes(boxes: &[ErgsBox]) -> Vec<ErgoBox> {
+    boxes.iter().map(|b| b.get_box()).collect()
+}
+
+/// Sums the nanoErg value of a list of `ErgsBox`es
+fn sum_nano_ergs(boxes: &[ErgsBox]) -> Result<NanoErg> {
+    boxes.iter().try_fold(0, |acc, b| {
+        checked_add(acc, b.nano_ergs(), "total input nanoErgs")
+    })
+}
+
+/// Sums the amount of the token held across a list of `ErgoBox`es
+fn sum_token_amount(boxes: &Vec<ErgoBox>, token_id: &str) -> Result<u64> {
+    let mut total = 0;
+    for b in boxes {
+        for token in &b.tokens {
+            let id: String = token.token_id.0.clone().into();
+            if id == token_id {
+                total = checked_add(total, u64::from(token.amount), "input token amount")?;
+            }
+        }
+    }
+    Ok(total)
+}
@@ -0,0 +1,255 @@
+// This file holds the `MintCapacity`, the number of coins which can currently
+// be minted from the Bank box. While the protocol is in the cooling off period
+// the max reserve ratio is not enforced, so ReserveCoin minting is unlimited
+// up until the cooling off height rather than bounded by a number of coins.
+use crate::bank::BankBox;
+use crate::parameters::ProtocolConfig;
+use ergo_headless_dapp_framework::{BlockHeight, ErgUsdOraclePoolBox};
+use wasm_bindgen::prelude::*;
+
+/// The number of coins which can currently be minted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MintCapacity {
+    /// Any amount can be minted until `until_height`, from which the
+    /// reserve ratio limit is enforced
+    Unlimited { until_height: BlockHeight },
+    /// At most this many coins can be minted
+    Limited(u64),
+}
+
+/// Rust methods related to `MintCapacity`
+impl MintCapacity {
+    /// Whether any amount can currently be minted
+    pub fn is_unlimited(&self) -> bool {
+        match self {
+            MintCapacity::Unlimited { .. } => true,
+            MintCapacity::Limited(_) => false,
+        }
+    }
+
+    /// The maximum number of coins which can be minted, or `None` if unlimited
+    pub fn amount(&self) -> Option<u64> {
+        match self {
+            MintCapacity::Unlimited { .. } => None,
+            MintCapacity::Limited(amount) => Some(*amount),
+        }
+    }
+
+    /// Whether `amount` coins can currently be minted
+    pub fn allows(&self, amount: u64) -> bool {
+        match self {
+            MintCapacity::Unlimited { .. } => true,
+            MintCapacity::Limited(limit) => amount <= *limit,
+        }
+    }
+}
+
+/// The kind of a `MintCapacity`, used as the tag of `WMintCapacity`
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MintCapacityKind {
+    Unlimited,
+    Limited,
+}
+
+/// The WASM representation of a `MintCapacity`, tagged by `kind`. Only the
+/// field belonging to the `kind` is meaningful, the other is set to 0.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WMintCapacity {
+    /// Whether minting is unlimited or limited
+    pub kind: MintCapacityKind,
+    /// The height from which minting is limited, if `Unlimited`
+    pub until_height: BlockHeight,
+    /// The maximum number of coins which can be minted, if `Limited`
+    pub amount: u64,
+}
+
+impl From<MintCapacity> for WMintCapacity {
+    fn from(capacity: MintCapacity) -> WMintCapacity {
+        match capacity {
+            MintCapacity::Unlimited { until_height } => WMintCapacity {
+                kind: MintCapacityKind::Unlimited,
+                until_height,
+                amount: 0,
+            },
+            MintCapacity::Limited(amount) => WMintCapacity {
+                kind: MintCapacityKind::Limited,
+                until_height: 0,
+                amount,
+            },
+        }
+    }
+}
+
+impl From<WMintCapacity> for MintCapacity {
+    fn from(capacity: WMintCapacity) -> MintCapacity {
+        match capacity.kind {
+            MintCapacityKind::Unlimited => MintCapacity::Unlimited {
+                until_height: capacity.until_height,
+            },
+            MintCapacityKind::Limited => MintCapacity::Limited(capacity.amount),
+        }
+    }
+}
+
+/// WASM-supported mint capacity methods related to `BankStage`
+#[wasm_bindgen]
+impl BankBox {
+    /// The number of ReserveCoins which can currently be minted, which is
+    /// unlimited during the cooling off period
+    #[wasm_bindgen]
+    pub fn w_reservecoin_mint_capacity(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        current_height: BlockHeight,
+        config: &ProtocolConfig,
+    ) -> WMintCapacity {
+        self.reservecoin_mint_capacity(oracle_box, current_height, config)
+            .into()
+    }
+
+    /// The number of StableCoins which can currently be minted
+    #[wasm_bindgen]
+    pub fn w_stablecoin_mint_capacity(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> WMintCapacity {
+        self.stablecoin_mint_capacity(oracle_box, config).into()
+    }
+}
+
+/// Mint capacity methods related to `BankStage`
+impl BankBox {
+    /// The number of ReserveCoins which can currently be minted, which is
+    /// unlimited during the cooling off period
+    pub fn reservecoin_mint_capacity(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        current_height: BlockHeight,
+        config: &ProtocolConfig,
+    ) -> MintCapacity {
+        if config.in_cooling_off_period(current_height) {
+            return MintCapacity::Unlimited {
+                until_height: config.cooling_off_height,
+            };
+        }
+        MintCapacity::Limited(self.num_able_to_mint_reservecoin(oracle_box, current_height, config))
+    }
+
+    /// The number of StableCoins which can currently be minted
+    pub fn stablecoin_mint_capacity(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> MintCapacity {
+        MintCapacity::Limited(self.num_able_to_mint_stablecoin(oracle_box, config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ergo_headless_dapp_framework::create_candidate;
+    use ergo_headless_dapp_framework::encoding::build_token;
+    use ergo_lib::chain::ergo_box::ErgoBox;
+    use ergo_lib::chain::transaction::TxId;
+
+    /// A P2S address to hold the Bank & oracle pool boxes
+    const ADDRESS: &str = "2iHkR7CWvD1R4j1yZg5bkeDRQavjAaVPeTDFGGLZduHyfWMuYpmhHocX8GJoaieTx78FntzJbCBVL6rf96ocJoZdmWBL2fci7NqWgAirppPQmZ7fN9V6z13Ay6brPriBKYqLp1bT2Fk4FkFLCfdPpe";
+
+    fn config() -> ProtocolConfig {
+        ProtocolConfig {
+            stablecoin_token_id: "01".repeat(32),
+            reservecoin_token_id: "02".repeat(32),
+            bank_nft_id: "03".repeat(32),
+            ..ProtocolConfig::default()
+        }
+    }
+
+    fn ergo_box(value: u64, tokens: Vec<(String, u64)>, registers: Vec<i64>) -> ErgoBox {
+        let tokens = tokens
+            .iter()
+            .map(|(id, amount)| build_token(id, *amount).unwrap())
+            .collect();
+        let registers = registers.into_iter().map(|r| r.into()).collect();
+        let candidate =
+            create_candidate(value, &ADDRESS.to_string(), &tokens, &registers, 0).unwrap();
+        ErgoBox::from_box_candidate(&candidate, TxId::zero(), 0)
+    }
+
+    /// A Bank box with a reserve ratio of 500% and an oracle pool box
+    /// pricing 1 cent at 2000000 nanoErgs
+    fn boxes() -> (BankBox, ErgUsdOraclePoolBox) {
+        let config = config();
+        let bank_box = ergo_box(
+            10_000_000_000_000,
+            vec![
+                (config.stablecoin_token_id.clone(), 1_000_000_000_000),
+                (config.reservecoin_token_id.clone(), 1_000_000_000_000),
+                (config.bank_nft_id.clone(), 1),
+            ],
+            vec![1_000_000, 1_000_000_000],
+        );
+        let oracle_box = ergo_box(
+            config.min_box_value,
+            vec![(config.oracle_pool_nft_id.clone(), 1)],
+            vec![200_000_000],
+        );
+        (
+            BankBox::new_with_config(&bank_box, &config).unwrap(),
+            ErgUsdOraclePoolBox::new(&oracle_box).unwrap(),
+        )
+    }
+
+    #[test]
+    fn reservecoin_minting_is_limited_from_the_cooling_off_height() {
+        let config = config();
+        let (bank_box, oracle_box) = boxes();
+        let cooling_off_height = config.cooling_off_height;
+
+        for height in &[0, cooling_off_height - 1] {
+            assert_eq!(
+                bank_box.reservecoin_mint_capacity(&oracle_box, *height, &config),
+                MintCapacity::Unlimited {
+                    until_height: cooling_off_height
+                }
+            );
+        }
+        for height in &[cooling_off_height, cooling_off_height + 1] {
+            let capacity = bank_box.reservecoin_mint_capacity(&oracle_box, *height, &config);
+            let limit = bank_box.num_able_to_mint_reservecoin(&oracle_box, *height, &config);
+            assert_eq!(capacity, MintCapacity::Limited(limit));
+            assert!(capacity.allows(limit));
+            assert!(!capacity.allows(limit + 1));
+        }
+    }
+
+    #[test]
+    fn stablecoin_minting_is_always_limited() {
+        let config = config();
+        let (bank_box, oracle_box) = boxes();
+        assert_eq!(
+            bank_box.stablecoin_mint_capacity(&oracle_box, &config),
+            MintCapacity::Limited(bank_box.num_able_to_mint_stablecoin(&oracle_box, &config))
+        );
+    }
+
+    #[test]
+    fn wasm_representation_round_trips() {
+        let unlimited = MintCapacity::Unlimited { until_height: 10 };
+        assert!(unlimited.is_unlimited());
+        assert_eq!(unlimited.amount(), None);
+        assert!(unlimited.allows(u64::MAX));
+
+        let limited = MintCapacity::Limited(5);
+        assert!(!limited.is_unlimited());
+        assert_eq!(limited.amount(), Some(5));
+
+        for capacity in &[unlimited, limited] {
+            let w_capacity: WMintCapacity = (*capacity).into();
+            assert_eq!(MintCapacity::from(w_capacity), *capacity);
+        }
+    }
+}
@@ -0,0 +1,750 @@
+// This file holds the coin selection used to pick the input boxes of an
+// Action from an unsorted list of the user's boxes (ie. their wallet UTXOs).
+// A `SelectionTarget` describes the nanoErgs and/or tokens the inputs must
+// hold, and is derived from a `Quote` so that it covers everything the
+// Action spends, including the `MIN_BOX_VALUE` of each output it creates.
+use crate::bank::BankBox;
+use crate::equations::checked_add;
+use crate::error::{ProtocolError, Result};
+use crate::input_boxes::{ReserveCoinBox, StableCoinBox};
+use crate::parameters::ProtocolConfig;
+use crate::protocol::StableCoinProtocol;
+use crate::quote::{Quote, QuoteAction};
+use ergo_headless_dapp_framework::{BlockHeight, ErgUsdOraclePoolBox, ErgsBox, NanoErg};
+use ergo_headless_dapp_framework::{ErgoAddressString, P2PKAddressString, WrappedBox};
+use ergo_lib::chain::ergo_box::ErgoBox;
+use ergo_lib::chain::transaction::unsigned::UnsignedTransaction;
+
+/// The maximum number of combinations `MinimizeChange` tries before settling
+/// for the best combination found so far
+const MAX_SELECTION_TRIES: usize = 100000;
+
+/// The strategy used to pick input boxes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionStrategy {
+    /// Use the boxes holding the most first, minimizing the number of inputs
+    LargestFirst,
+    /// Use the smallest single box which covers the target. Falls back to
+    /// `LargestFirst` if no single box does.
+    SmallestSufficient,
+    /// Use the combination of boxes which leaves the least change
+    MinimizeChange,
+    /// Use boxes which hold no tokens other than the targeted one, only
+    /// using the others (fewest tokens first) if those do not suffice
+    AvoidForeignTokens,
+}
+
+impl SelectionStrategy {
+    /// All of the selection strategies
+    pub fn all() -> Vec<SelectionStrategy> {
+        vec![
+            SelectionStrategy::LargestFirst,
+            SelectionStrategy::SmallestSufficient,
+            SelectionStrategy::MinimizeChange,
+            SelectionStrategy::AvoidForeignTokens,
+        ]
+    }
+
+    /// The name of the strategy as used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            SelectionStrategy::LargestFirst => "largest-first",
+            SelectionStrategy::SmallestSufficient => "smallest-sufficient",
+            SelectionStrategy::MinimizeChange => "minimize-change",
+            SelectionStrategy::AvoidForeignTokens => "avoid-foreign-tokens",
+        }
+    }
+
+    /// Parse a strategy from its name
+    pub fn from_name(name: &str) -> Result<SelectionStrategy> {
+        SelectionStrategy::all()
+            .into_iter()
+            .find(|s| s.name() == name.to_lowercase())
+            .ok_or_else(|| {
+                ProtocolError::InvalidInputValue(format!(
+                    "Unknown coin selection strategy `{}`. Expected one of: largest-first, smallest-sufficient, minimize-change, avoid-foreign-tokens.",
+                    name
+                ))
+            })
+    }
+}
+
+/// The amounts which the selected boxes must hold in total
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectionTarget {
+    /// The nanoErgs which must be held
+    pub nano_ergs: NanoErg,
+    /// The id and amount of the token which must be held, if any
+    pub token: Option<(String, u64)>,
+}
+
+impl SelectionTarget {
+    /// A target of only nanoErgs
+    pub fn nano_ergs(nano_ergs: NanoErg) -> SelectionTarget {
+        SelectionTarget {
+            nano_ergs,
+            token: None,
+        }
+    }
+
+    /// A target of only an amount of a token
+    pub fn token(token_id: &str, amount: u64) -> SelectionTarget {
+        SelectionTarget {
+            nano_ergs: 0,
+            token: Some((token_id.to_string(), amount)),
+        }
+    }
+
+    /// The target of the inputs of the Action described by the `Quote`.
+    /// Minting requires the quoted total, which includes the min box value
+    /// of the Receipt and change boxes. Redeeming requires the coins, as the
+    /// fees are paid out of the redeemed nanoErgs.
+    pub fn for_quote(quote: &Quote, config: &ProtocolConfig) -> SelectionTarget {
+        match quote.action {
+            QuoteAction::MintStableCoin | QuoteAction::MintReserveCoin => {
+                SelectionTarget::nano_ergs(quote.total)
+            }
+            QuoteAction::RedeemStableCoin => {
+                SelectionTarget::token(&config.stablecoin_token_id, quote.amount)
+            }
+            QuoteAction::RedeemReserveCoin => {
+                SelectionTarget::token(&config.reservecoin_token_id, quote.amount)
+            }
+        }
+    }
+
+    /// The target of the box paying the tx fee of an Action which also
+    /// creates a change box (ie. the voting/ballot Actions)
+    pub fn for_fee_box(
+        transaction_fee: NanoErg,
+        config: &ProtocolConfig,
+    ) -> Result<SelectionTarget> {
+        Ok(SelectionTarget::nano_ergs(checked_add(
+            transaction_fee,
+            config.min_box_value,
+            "required nanoErgs",
+        )?))
+    }
+
+    /// The amount of the targeted token, 0 if there is none
+    fn token_amount(&self) -> u64 {
+        self.token.as_ref().map(|(_, amount)| *amount).unwrap_or(0)
+    }
+
+    /// The error for when the boxes do not hold enough to meet the target
+    fn insufficient(&self, token_total: u64, config: &ProtocolConfig) -> ProtocolError {
+        match &self.token {
+            Some((token_id, amount)) if token_total < *amount => {
+                if token_id == &config.stablecoin_token_id {
+                    ProtocolError::InsufficientStableCoins(*amount)
+                } else if token_id == &config.reservecoin_token_id {
+                    ProtocolError::InsufficientReserveCoins(*amount)
+                } else {
+                    ProtocolError::InvalidTokens(format!(
+                        "The boxes hold {} of the {} tokens `{}` required.",
+                        token_total, amount, token_id
+                    ))
+                }
+            }
+            _ => ProtocolError::InsufficientNanoErgs(self.nano_ergs),
+        }
+    }
+}
+
+/// What a single box can contribute towards a `SelectionTarget`
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    nano_ergs: u64,
+    token_amount: u64,
+    num_foreign_tokens: usize,
+}
+
+/// The running totals of the selected boxes
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    nano_ergs: u64,
+    token_amount: u64,
+}
+
+impl Totals {
+    fn add(&self, candidate: &Candidate) -> Totals {
+        Totals {
+            nano_ergs: self.nano_ergs.saturating_add(candidate.nano_ergs),
+            token_amount: self.token_amount.saturating_add(candidate.token_amount),
+        }
+    }
+
+    fn meets(&self, target: &SelectionTarget) -> bool {
+        self.nano_ergs >= target.nano_ergs && self.token_amount >= target.token_amount()
+    }
+
+    /// The change left over, in order of importance
+    fn change(&self, target: &SelectionTarget) -> (u64, u64) {
+        (
+            self.token_amount - target.token_amount(),
+            self.nano_ergs - target.nano_ergs,
+        )
+    }
+}
+
+/// Select boxes from `boxes` (in any order) which together meet the
+/// `target`, using the given `strategy`. The selected boxes are returned
+/// in the order they were provided in.
+pub fn select_boxes<T: WrappedBox + Clone>(
+    boxes: &[T],
+    target: &SelectionTarget,
+    strategy: SelectionStrategy,
+    config: &ProtocolConfig,
+) -> Result<Vec<T>> {
+    let candidates: Vec<Candidate> = boxes
+        .iter()
+        .map(|b| candidate(&b.get_box(), target))
+        .collect();
+    let selected = select_candidates(&candidates, target, strategy);
+    match selected {
+        Some(mut indices) => {
+            indices.sort();
+            Ok(indices.into_iter().map(|i| boxes[i].clone()).collect())
+        }
+        None => {
+            let token_total = candidates
+                .iter()
+                .fold(0u64, |acc, c| acc.saturating_add(c.token_amount));
+            Err(target.insufficient(token_total, config))
+        }
+    }
+}
+
+/// Select the `ErgsBox`es which hold at least `nano_ergs`
+pub fn select_ergs_boxes(
+    boxes: &[ErgsBox],
+    nano_ergs: NanoErg,
+    strategy: SelectionStrategy,
+    config: &ProtocolConfig,
+) -> Result<Vec<ErgsBox>> {
+    select_boxes(
+        boxes,
+        &SelectionTarget::nano_ergs(nano_ergs),
+        strategy,
+        config,
+    )
+}
+
+/// Select the `StableCoinBox`es which hold at least `amount` StableCoins
+pub fn select_stablecoin_boxes(
+    boxes: &[StableCoinBox],
+    amount: u64,
+    strategy: SelectionStrategy,
+    config: &ProtocolConfig,
+) -> Result<Vec<StableCoinBox>> {
+    let target = SelectionTarget::token(&config.stablecoin_token_id, amount);
+    select_boxes(boxes, &target, strategy, config)
+}
+
+/// Select the `ReserveCoinBox`es which hold at least `amount` ReserveCoins
+pub fn select_reservecoin_boxes(
+    boxes: &[ReserveCoinBox],
+    amount: u64,
+    strategy: SelectionStrategy,
+    config: &ProtocolConfig,
+) -> Result<Vec<ReserveCoinBox>> {
+    let target = SelectionTarget::token(&config.reservecoin_token_id, amount);
+    select_boxes(boxes, &target, strategy, config)
+}
+
+/// Select a single `ErgsBox` to pay the tx fee of an Action which also
+/// creates a change box. The smallest box which covers it is used,
+/// preferring boxes which hold no tokens.
+pub fn select_ergs_box_for_fee(
+    boxes: &[ErgsBox],
+    transaction_fee: NanoErg,
+    config: &ProtocolConfig,
+) -> Result<ErgsBox> {
+    let target = SelectionTarget::for_fee_box(transaction_fee, config)?;
+    boxes
+        .iter()
+        .filter(|b| b.nano_ergs() >= target.nano_ergs)
+        .min_by_key(|b| (b.get_box().tokens.len(), b.nano_ergs()))
+        .cloned()
+        .ok_or_else(|| target.insufficient(0, config))
+}
+
+/// Wrap every box of the wallet as an `ErgsBox`
+pub fn wallet_ergs_boxes(wallet_boxes: &[ErgoBox]) -> Vec<ErgsBox> {
+    wallet_boxes
+        .iter()
+        .filter_map(|b| ErgsBox::new(b).ok())
+        .collect()
+}
+
+/// The boxes of the wallet which hold StableCoins
+pub fn wallet_stablecoin_boxes(
+    wallet_boxes: &[ErgoBox],
+    config: &ProtocolConfig,
+) -> Vec<StableCoinBox> {
+    wallet_boxes
+        .iter()
+        .filter_map(|b| StableCoinBox::new_with_config(b, config).ok())
+        .collect()
+}
+
+/// The boxes of the wallet which hold ReserveCoins
+pub fn wallet_reservecoin_boxes(
+    wallet_boxes: &[ErgoBox],
+    config: &ProtocolConfig,
+) -> Vec<ReserveCoinBox> {
+    wallet_boxes
+        .iter()
+        .filter_map(|b| ReserveCoinBox::new_with_config(b, config).ok())
+        .collect()
+}
+
+/// Select the indices of the candidates which together meet the `target`
+/// using the given `strategy`
+fn select_candidates(
+    candidates: &[Candidate],
+    target: &SelectionTarget,
+    strategy: SelectionStrategy,
+) -> Option<Vec<usize>> {
+    let all: Vec<usize> = (0..candidates.len()).collect();
+    match strategy {
+        SelectionStrategy::LargestFirst => largest_first(candidates, &all, target),
+        SelectionStrategy::SmallestSufficient => smallest_sufficient(candidates, target)
+            .or_else(|| largest_first(candidates, &all, target)),
+        SelectionStrategy::MinimizeChange => {
+            minimize_change(candidates, target).or_else(|| largest_first(candidates, &all, target))
+        }
+        SelectionStrategy::AvoidForeignTokens => fewest_foreign_tokens_first(candidates, target),
+    }
+}
+
+fn candidate(b: &ErgoBox, target: &SelectionTarget) -> Candidate {
+    let target_token_id = target.token.as_ref().map(|(id, _)| id.as_str());
+    let mut token_amount = 0u64;
+    let mut num_foreign_tokens = 0;
+    for t in &b.tokens {
+        let token_id: String = t.token_id.0.clone().into();
+        if Some(token_id.as_str()) == target_token_id {
+            token_amount = token_amount.saturating_add(u64::from(t.amount));
+        } else {
+            num_foreign_tokens += 1;
+        }
+    }
+    Candidate {
+        nano_ergs: *b.value.as_u64(),
+        token_amount,
+        num_foreign_tokens,
+    }
+}
+
+/// Select from `pool` (whose order is kept between equal boxes) by first
+/// taking the boxes holding the most of the token, and then the boxes
+/// holding the most nanoErgs until both parts of the target are met
+fn largest_first(
+    candidates: &[Candidate],
+    pool: &[usize],
+    target: &SelectionTarget,
+) -> Option<Vec<usize>> {
+    let mut selected = vec![];
+    let mut totals = Totals::default();
+
+    let mut by_token = pool.to_vec();
+    by_token.sort_by_key(|i| std::cmp::Reverse(candidates[*i].token_amount));
+    for i in by_token {
+        if totals.token_amount >= target.token_amount() || candidates[i].token_amount == 0 {
+            break;
+        }
+        totals = totals.add(&candidates[i]);
+        selected.push(i);
+    }
+
+    let mut by_nano_ergs = pool.to_vec();
+    by_nano_ergs.sort_by_key(|i| std::cmp::Reverse(candidates[*i].nano_ergs));
+    for i in by_nano_ergs {
+        if totals.nano_ergs >= target.nano_ergs {
+            break;
+        }
+        if !selected.contains(&i) {
+            totals = totals.add(&candidates[i]);
+            selected.push(i);
+        }
+    }
+
+    match totals.meets(target) {
+        true => Some(selected),
+        false => None,
+    }
+}
+
+/// Select boxes in order of how many foreign tokens they hold, so that the
+/// boxes holding none are exhausted before any others are used. Within boxes
+/// holding the same number of foreign tokens, the largest are taken first.
+fn fewest_foreign_tokens_first(
+    candidates: &[Candidate],
+    target: &SelectionTarget,
+) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by_key(|i| {
+        let c = &candidates[*i];
+        (
+            c.num_foreign_tokens,
+            std::cmp::Reverse(c.token_amount),
+            std::cmp::Reverse(c.nano_ergs),
+        )
+    });
+
+    let mut selected = vec![];
+    let mut totals = Totals::default();
+    for i in order {
+        if totals.meets(target) {
+            break;
+        }
+        // Skip boxes which contribute nothing to the unmet parts of the target
+        let needs_token = totals.token_amount < target.token_amount();
+        let needs_nano_ergs = totals.nano_ergs < target.nano_ergs;
+        let c = &candidates[i];
+        if (needs_token && c.token_amount > 0) || (needs_nano_ergs && c.nano_ergs > 0) {
+            totals = totals.add(c);
+            selected.push(i);
+        }
+    }
+
+    match totals.meets(target) {
+        true => Some(selected),
+        false => None,
+    }
+}
+
+/// The smallest single box which meets the target on its own
+fn smallest_sufficient(candidates: &[Candidate], target: &SelectionTarget) -> Option<Vec<usize>> {
+    (0..candidates.len())
+        .filter(|i| Totals::default().add(&candidates[*i]).meets(target))
+        .min_by_key(|i| (candidates[*i].token_amount, candidates[*i].nano_ergs))
+        .map(|i| vec![i])
+}
+
+/// The combination of boxes which meets the target with the least change,
+/// found via a depth-first search over the boxes ordered largest first
+fn minimize_change(candidates: &[Candidate], target: &SelectionTarget) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by_key(|i| {
+        std::cmp::Reverse((candidates[*i].token_amount, candidates[*i].nano_ergs))
+    });
+    // The totals of every box from each position onwards, used for pruning
+    let mut remaining = vec![Totals::default(); order.len() + 1];
+    for pos in (0..order.len()).rev() {
+        remaining[pos] = remaining[pos + 1].add(&candidates[order[pos]]);
+    }
+
+    let mut search = ChangeSearch {
+        candidates,
+        target,
+        order: &order,
+        remaining: &remaining,
+        best: None,
+        tries: 0,
+    };
+    search.search(0, Totals::default(), &mut vec![]);
+    search.best.map(|(_, selected)| selected)
+}
+
+/// The state of the depth-first search of `minimize_change`
+struct ChangeSearch<'a> {
+    candidates: &'a [Candidate],
+    target: &'a SelectionTarget,
+    order: &'a [usize],
+    remaining: &'a [Totals],
+    best: Option<((u64, u64), Vec<usize>)>,
+    tries: usize,
+}
+
+impl<'a> ChangeSearch<'a> {
+    fn search(&mut self, pos: usize, totals: Totals, selected: &mut Vec<usize>) {
+        self.tries += 1;
+        if self.tries > MAX_SELECTION_TRIES {
+            return;
+        }
+        if totals.meets(self.target) {
+            // Adding more boxes can only increase the change
+            let change = totals.change(self.target);
+            let improves = match &self.best {
+                Some((best, _)) => change < *best,
+                None => true,
+            };
+            if improves {
+                self.best = Some((change, selected.clone()));
+            }
+            return;
+        }
+        // Prune if even every remaining box cannot meet the target
+        let reachable = Totals {
+            nano_ergs: totals
+                .nano_ergs
+                .saturating_add(self.remaining[pos].nano_ergs),
+            token_amount: totals
+                .token_amount
+                .saturating_add(self.remaining[pos].token_amount),
+        };
+        if pos >= self.order.len() || !reachable.meets(self.target) {
+            return;
+        }
+        // Either include or skip the box at `pos`
+        let i = self.order[pos];
+        selected.push(i);
+        self.search(pos + 1, totals.add(&self.candidates[i]), selected);
+        selected.pop();
+        self.search(pos + 1, totals, selected);
+    }
+}
+
+/// Actions which select their own inputs from the user's wallet UTXOs
+impl StableCoinProtocol {
+    /// Action: Mint ReserveCoin by providing Ergs.
+    /// The input boxes are selected from `wallet_boxes` via `strategy`.
+    pub fn action_mint_reservecoin_from_wallet(
+        &self,
+        amount_to_mint: u64,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        wallet_boxes: &[ErgoBox],
+        strategy: SelectionStrategy,
+        implementor_address: ErgoAddressString,
+        max_total_cost: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
+    ) -> Result<UnsignedTransaction> {
+        let quote = bank_box.quote_mint_reservecoin(
+            amount_to_mint,
+            transaction_fee,
+            current_height,
+            oracle_box,
+            &self.config,
+        )?;
+        quote.verify_max_total_cost(max_total_cost)?;
+        quote.verify_reserve_ratio_drift(expected_reserve_ratio, max_reserve_ratio_drift)?;
+        let ergs_boxes = select_boxes(
+            &wallet_ergs_boxes(wallet_boxes),
+            &SelectionTarget::for_quote(&quote, &self.config),
+            strategy,
+            &self.config,
+        )?;
+        self.action_mint_reservecoin_with_quote(
+            &quote,
+            user_address,
+            current_height,
+            oracle_box,
+            bank_box,
+            &ergs_boxes,
+            implementor_address,
+        )
+    }
+
+    /// Action: Mint StableCoin by providing Ergs.
+    /// The input boxes are selected from `wallet_boxes` via `strategy`.
+    pub fn action_mint_stablecoin_from_wallet(
+        &self,
+        amount_to_mint: u64,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        wallet_boxes: &[ErgoBox],
+        strategy: SelectionStrategy,
+        implementor_address: ErgoAddressString,
+        max_total_cost: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
+    ) -> Result<UnsignedTransaction> {
+        let quote = bank_box.quote_mint_stablecoin(
+            amount_to_mint,
+            transaction_fee,
+            oracle_box,
+            &self.config,
+        )?;
+        quote.verify_max_total_cost(max_total_cost)?;
+        quote.verify_reserve_ratio_drift(expected_reserve_ratio, max_reserve_ratio_drift)?;
+        let ergs_boxes = select_boxes(
+            &wallet_ergs_boxes(wallet_boxes),
+            &SelectionTarget::for_quote(&quote, &self.config),
+            strategy,
+            &self.config,
+        )?;
+        self.action_mint_stablecoin_with_quote(
+            &quote,
+            user_address,
+            current_height,
+            oracle_box,
+            bank_box,
+            &ergs_boxes,
+            implementor_address,
+        )
+    }
+
+    /// Action: Redeem ReserveCoin for Ergs.
+    /// The ReserveCoin boxes are selected from `wallet_boxes` via `strategy`.
+    pub fn action_redeem_reservecoin_from_wallet(
+        &self,
+        amount_to_redeem: u64,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        wallet_boxes: &[ErgoBox],
+        strategy: SelectionStrategy,
+        implementor_address: ErgoAddressString,
+        min_total_received: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
+    ) -> Result<UnsignedTransaction> {
+        let rc_boxes = select_reservecoin_boxes(
+            &wallet_reservecoin_boxes(wallet_boxes, &self.config),
+            amount_to_redeem,
+            strategy,
+            &self.config,
+        )?;
+        self.action_redeem_reservecoin(
+            amount_to_redeem,
+            user_address,
+            transaction_fee,
+            current_height,
+            oracle_box,
+            bank_box,
+            &rc_boxes,
+            implementor_address,
+            min_total_received,
+            expected_reserve_ratio,
+            max_reserve_ratio_drift,
+        )
+    }
+
+    /// Action: Redeem StableCoin for Ergs.
+    /// The StableCoin boxes are selected from `wallet_boxes` via `strategy`.
+    pub fn action_redeem_stablecoin_from_wallet(
+        &self,
+        amount_to_redeem: u64,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        wallet_boxes: &[ErgoBox],
+        strategy: SelectionStrategy,
+        implementor_address: ErgoAddressString,
+        min_total_received: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
+    ) -> Result<UnsignedTransaction> {
+        let sc_boxes = select_stablecoin_boxes(
+            &wallet_stablecoin_boxes(wallet_boxes, &self.config),
+            amount_to_redeem,
+            strategy,
+            &self.config,
+        )?;
+        self.action_redeem_stablecoin(
+            amount_to_redeem,
+            user_address,
+            transaction_fee,
+            current_height,
+            oracle_box,
+            bank_box,
+            &sc_boxes,
+            implementor_address,
+            min_total_received,
+            expected_reserve_ratio,
+            max_reserve_ratio_drift,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ergs(nano_ergs: u64, num_foreign_tokens: usize) -> Candidate {
+        Candidate {
+            nano_ergs,
+            token_amount: 0,
+            num_foreign_tokens,
+        }
+    }
+
+    fn tokens(token_amount: u64, nano_ergs: u64, num_foreign_tokens: usize) -> Candidate {
+        Candidate {
+            nano_ergs,
+            token_amount,
+            num_foreign_tokens,
+        }
+    }
+
+    /// A wallet on which every strategy picks different inputs
+    fn wallet() -> Vec<Candidate> {
+        vec![ergs(12, 1), ergs(6, 0), ergs(5, 0), ergs(11, 2), ergs(4, 0)]
+    }
+
+    fn select(
+        candidates: &[Candidate],
+        target: &SelectionTarget,
+        strategy: SelectionStrategy,
+    ) -> Option<Vec<usize>> {
+        select_candidates(candidates, target, strategy).map(|mut indices| {
+            indices.sort();
+            indices
+        })
+    }
+
+    #[test]
+    fn strategies_pick_different_inputs() {
+        let target = SelectionTarget::nano_ergs(10);
+        let wallet = wallet();
+        let picks: Vec<Vec<usize>> = SelectionStrategy::all()
+            .into_iter()
+            .map(|s| select(&wallet, &target, s).unwrap())
+            .collect();
+        assert_eq!(picks[0], vec![0]);
+        assert_eq!(picks[1], vec![3]);
+        assert_eq!(picks[2], vec![1, 4]);
+        assert_eq!(picks[3], vec![1, 2]);
+        for (i, a) in picks.iter().enumerate() {
+            for b in &picks[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn avoid_foreign_tokens_exhausts_token_free_boxes_first() {
+        let wallet = wallet();
+        let strategy = SelectionStrategy::AvoidForeignTokens;
+        // The token-free boxes hold 15, so the box with the fewest foreign
+        // tokens is added rather than the largest
+        let target = SelectionTarget::nano_ergs(20);
+        assert_eq!(select(&wallet, &target, strategy), Some(vec![0, 1, 2, 4]));
+        let target = SelectionTarget::nano_ergs(38);
+        assert_eq!(
+            select(&wallet, &target, strategy),
+            Some(vec![0, 1, 2, 3, 4])
+        );
+        let target = SelectionTarget::nano_ergs(39);
+        assert_eq!(select(&wallet, &target, strategy), None);
+    }
+
+    #[test]
+    fn avoid_foreign_tokens_skips_boxes_without_the_token() {
+        let wallet = vec![
+            ergs(100, 0),
+            tokens(5, 1, 1),
+            tokens(3, 1, 0),
+            tokens(4, 1, 0),
+        ];
+        let target = SelectionTarget::token("token", 6);
+        let strategy = SelectionStrategy::AvoidForeignTokens;
+        assert_eq!(select(&wallet, &target, strategy), Some(vec![2, 3]));
+        let target = SelectionTarget::token("token", 8);
+        assert_eq!(select(&wallet, &target, strategy), Some(vec![1, 2, 3]));
+    }
+}
@@ -0,0 +1,847 @@
+// This file holds the `BankContractDiff`, a report on what changes when the
+// Bank box is moved to a proposed new contract via the `Update Protocol`
+// Action. It compares the constant segments & sizes of the two `ErgoTree`s
+// and checks that the output Bank box built by the `Update Protocol` Action
+// keeps everything the `updatePath` of the Update contract requires to stay
+// the same.
+use crate::bank::BankBox;
+use crate::error::{ProtocolError, Result};
+use crate::parameters::{ContractVersion, ProtocolConfig};
+use crate::update::UpdateBox;
+use crate::voting::address_hash;
+use ergo_headless_dapp_framework::encoding::deserialize_p2s_to_ergo_tree;
+use ergo_headless_dapp_framework::{BlockHeight, P2SAddressString, WrappedBox};
+use ergo_lib::ergo_tree::ErgoTree;
+use ergo_lib::serialization::SigmaSerializable;
+
+/// The `ErgoTree` header flag marking that constants are segregated
+const CONSTANT_SEGREGATION_FLAG: u8 = 0x10;
+/// The `ErgoTree` header flag marking that the tree size follows the header
+const SIZE_FLAG: u8 = 0x08;
+
+/// A constant held in the constant segment of an `ErgoTree`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeConstant {
+    Boolean(bool),
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    /// A `Coll[Byte]` as a hex string (such as a token ID)
+    Bytes(String),
+    /// A constant of any other type, such as a `Coll[Int]` or a
+    /// `SigmaProp`, held as its type & its serialized value as hex
+    Other {
+        tpe: String,
+        value: String,
+    },
+}
+
+impl std::fmt::Display for TreeConstant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeConstant::Boolean(b) => write!(f, "{}", b),
+            TreeConstant::Byte(n) => write!(f, "{}", n),
+            TreeConstant::Short(n) => write!(f, "{}", n),
+            TreeConstant::Int(n) => write!(f, "{}", n),
+            TreeConstant::Long(n) => write!(f, "{}", n),
+            TreeConstant::Bytes(hex) => write!(f, "{}", hex),
+            TreeConstant::Other { tpe, value } => write!(f, "{}({})", tpe, value),
+        }
+    }
+}
+
+/// A constant which differs between the current and proposed `ErgoTree`s
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantChange {
+    /// The index of the constant within the constant segment
+    pub index: usize,
+    /// The constant in the current tree, `None` if the segment is shorter
+    pub current: Option<TreeConstant>,
+    /// The constant in the proposed tree, `None` if the segment is shorter
+    pub proposed: Option<TreeConstant>,
+    /// The `ProtocolConfig` field which the current (or else proposed)
+    /// value uniquely matches, such as `fee_percent` or `bank_nft_id`
+    pub parameter: Option<String>,
+}
+
+/// Whether the output Bank box of the `Update Protocol` Action keeps what
+/// the `updatePath` of the Update contract requires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdatePathCheck {
+    /// The input Bank box holds the Bank NFT as its third token
+    pub preserves_bank_nft: bool,
+    /// The output Bank box holds the same tokens as the input
+    pub preserves_tokens: bool,
+    /// R4/R5 (circulating StableCoins/ReserveCoins) are unchanged
+    pub preserves_registers: bool,
+    /// The nanoErg value of the Bank box is unchanged
+    pub preserves_value: bool,
+    /// The proposed contract differs from the current one
+    pub changes_proposition: bool,
+    /// The proposed address hash matches R4 of the Update box. `None` if no
+    /// Update box was provided.
+    pub matches_update_box: Option<bool>,
+}
+
+impl UpdatePathCheck {
+    /// Whether the Update contract would accept the `Update Protocol` Action
+    pub fn is_valid(&self) -> bool {
+        self.preserves_bank_nft
+            && self.preserves_tokens
+            && self.preserves_registers
+            && self.preserves_value
+            && self.changes_proposition
+            && self.matches_update_box.unwrap_or(true)
+    }
+}
+
+/// The differences between the deployed Bank contract and a proposed one
+#[derive(Debug, Clone, PartialEq)]
+pub struct BankContractDiff {
+    /// The proposed P2S address of the Bank contract
+    pub proposed_address: P2SAddressString,
+    /// The blake2b256 hash of the proposed address' ErgoTree
+    pub proposed_address_hash: String,
+    /// The size in bytes of the current serialized `ErgoTree`
+    pub current_script_size: usize,
+    /// The size in bytes of the proposed serialized `ErgoTree`
+    pub proposed_script_size: usize,
+    /// The contract versions the current tree may have been compiled from
+    pub current_versions: Vec<ContractVersion>,
+    /// The contract versions the proposed tree may have been compiled from
+    pub proposed_versions: Vec<ContractVersion>,
+    /// The constants which differ, by index in the constant segment.
+    /// `None` if either constant segment could not be read.
+    pub constant_changes: Option<Vec<ConstantChange>>,
+    /// The checks of the `updatePath` of the Update contract
+    pub update_path: UpdatePathCheck,
+}
+
+impl BankContractDiff {
+    /// Diff the contract of the Bank box against the proposed P2S address,
+    /// naming the constants which match a parameter of `config`.
+    /// If the `UpdateBox` is provided the proposed address is also checked
+    /// against the address hash collected in its R4.
+    pub fn new(
+        bank_box: &BankBox,
+        proposed_address: &P2SAddressString,
+        update_box: Option<&UpdateBox>,
+        current_height: BlockHeight,
+        config: &ProtocolConfig,
+    ) -> Result<BankContractDiff> {
+        let current_tree = bank_box.get_box().ergo_tree;
+        let proposed_tree = deserialize_p2s_to_ergo_tree(proposed_address.clone())
+            .map_err(|_| ProtocolError::InvalidP2SAddress(proposed_address.clone()))?;
+        let current_bytes = current_tree.sigma_serialize_bytes();
+        let proposed_bytes = proposed_tree.sigma_serialize_bytes();
+        let proposed_address_hash = address_hash(proposed_address)?;
+
+        let update_path = update_path_check(
+            bank_box,
+            proposed_address,
+            &proposed_address_hash,
+            current_bytes != proposed_bytes,
+            update_box,
+            current_height,
+            config,
+        )?;
+
+        Ok(BankContractDiff {
+            proposed_address: proposed_address.clone(),
+            proposed_address_hash,
+            current_script_size: current_bytes.len(),
+            proposed_script_size: proposed_bytes.len(),
+            current_versions: ContractVersion::possible_versions(&current_tree),
+            proposed_versions: ContractVersion::possible_versions(&proposed_tree),
+            constant_changes: diff_constants(&current_tree, &proposed_tree, config),
+            update_path,
+        })
+    }
+
+    /// The change in script size in bytes
+    pub fn script_size_change(&self) -> i64 {
+        self.proposed_script_size as i64 - self.current_script_size as i64
+    }
+}
+
+/// Compare the constant segments of two `ErgoTree`s index by index,
+/// naming the constants which match a parameter of `config`.
+/// `None` if either constant segment could not be read.
+pub fn diff_constants(
+    current: &ErgoTree,
+    proposed: &ErgoTree,
+    config: &ProtocolConfig,
+) -> Option<Vec<ConstantChange>> {
+    let current_constants = constant_segment(&current.sigma_serialize_bytes())?;
+    let proposed_constants = constant_segment(&proposed.sigma_serialize_bytes())?;
+
+    let num_constants = std::cmp::max(current_constants.len(), proposed_constants.len());
+    let changes = (0..num_constants)
+        .filter_map(|index| {
+            let current = current_constants.get(index).cloned();
+            let proposed = proposed_constants.get(index).cloned();
+            if current == proposed {
+                return None;
+            }
+            let parameter = current
+                .as_ref()
+                .and_then(|c| matching_parameter(c, config))
+                .or_else(|| {
+                    proposed
+                        .as_ref()
+                        .and_then(|c| matching_parameter(c, config))
+                });
+            Some(ConstantChange {
+                index,
+                current,
+                proposed,
+                parameter: parameter.map(|p| p.to_string()),
+            })
+        })
+        .collect();
+    Some(changes)
+}
+
+/// Read the constant segment of a serialized `ErgoTree`. Constants of types
+/// without a `TreeConstant` variant are read as `TreeConstant::Other`.
+/// `None` if the tree does not segregate its constants, or holds a constant
+/// whose size can not be known without a full sigma parser (such as a `Box`).
+pub fn constant_segment(tree_bytes: &[u8]) -> Option<Vec<TreeConstant>> {
+    let mut reader = ByteReader::new(tree_bytes);
+    let header = reader.byte()?;
+    if header & CONSTANT_SEGREGATION_FLAG == 0 {
+        return None;
+    }
+    if header & SIZE_FLAG != 0 {
+        reader.vlq()?;
+    }
+    let num_constants = reader.vlq()?;
+    let mut constants = vec![];
+    for _ in 0..num_constants {
+        constants.push(reader.constant()?);
+    }
+    Some(constants)
+}
+
+/// Decode a single constant serialized as hex, such as a register value
+/// held in the JSON of a box. `None` if the hex is invalid or the constant
+/// can not be read.
+pub fn decode_constant_hex(hex: &str) -> Option<TreeConstant> {
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    ByteReader::new(&bytes).constant()
+}
+
+/// The `ProtocolConfig` field which the constant uniquely matches
+fn matching_parameter(constant: &TreeConstant, config: &ProtocolConfig) -> Option<&'static str> {
+    let matches: Vec<&'static str> = match constant {
+        TreeConstant::Bytes(hex) => vec![
+            ("stablecoin_token_id", &config.stablecoin_token_id),
+            ("reservecoin_token_id", &config.reservecoin_token_id),
+            ("bank_nft_id", &config.bank_nft_id),
+            ("oracle_pool_nft_id", &config.oracle_pool_nft_id),
+            ("update_nft_id", &config.update_nft_id),
+            ("update_ballot_token_id", &config.update_ballot_token_id),
+        ]
+        .into_iter()
+        .filter(|(_, id)| !id.is_empty() && *id == hex)
+        .map(|(name, _)| name)
+        .collect(),
+        TreeConstant::Boolean(_) | TreeConstant::Other { .. } => vec![],
+        _ => {
+            let value = match constant {
+                TreeConstant::Byte(n) => *n as i64,
+                TreeConstant::Short(n) => *n as i64,
+                TreeConstant::Int(n) => *n as i64,
+                TreeConstant::Long(n) => *n,
+                _ => return None,
+            };
+            vec![
+                ("fee_percent", config.fee_percent),
+                ("min_reserve_ratio", config.min_reserve_ratio),
+                ("max_reserve_ratio", config.max_reserve_ratio),
+                ("cooling_off_height", config.cooling_off_height),
+                ("min_box_value", config.min_box_value),
+                (
+                    "reservecoin_default_price",
+                    config.reservecoin_default_price,
+                ),
+            ]
+            .into_iter()
+            .filter(|(_, v)| value >= 0 && *v == value as u64)
+            .map(|(name, _)| name)
+            .collect()
+        }
+    };
+    if matches.len() == 1 {
+        return Some(matches[0]);
+    }
+    None
+}
+
+/// Check the output Bank box of the `Update Protocol` Action against the
+/// `updatePath` of the Update contract. The output is built exactly as
+/// `StableCoinProtocol::action_update_protocol` builds it.
+fn update_path_check(
+    bank_box: &BankBox,
+    proposed_address: &P2SAddressString,
+    proposed_address_hash: &str,
+    changes_proposition: bool,
+    update_box: Option<&UpdateBox>,
+    current_height: BlockHeight,
+    config: &ProtocolConfig,
+) -> Result<UpdatePathCheck> {
+    let bank_in = bank_box.get_box();
+    let bank_out =
+        bank_box.create_update_protocol_candidate(proposed_address, bank_box, current_height)?;
+
+    let preserves_bank_nft = bank_in
+        .tokens
+        .get(2)
+        .map(|t| {
+            let token_id: String = t.token_id.0.clone().into();
+            token_id == config.bank_nft_id
+        })
+        .unwrap_or(false);
+    let registers_in = bank_in.additional_registers.get_ordered_values();
+    let registers_out = bank_out.additional_registers.get_ordered_values();
+
+    Ok(UpdatePathCheck {
+        preserves_bank_nft,
+        preserves_tokens: bank_in.tokens == bank_out.tokens,
+        preserves_registers: registers_in.len() >= 2
+            && registers_out.len() >= 2
+            && registers_in[0..2] == registers_out[0..2],
+        preserves_value: bank_in.value == bank_out.value,
+        changes_proposition,
+        matches_update_box: update_box.map(|ub| ub.address_hash() == proposed_address_hash),
+    })
+}
+
+/// The number of type codes reserved for each type constructor by the sigma
+/// type serializer, with the primitive types taking codes 1 to 11
+const PRIM_RANGE: u8 = 12;
+/// The type code of a tuple with its number of elements following
+const TUPLE_TYPE_CODE: u8 = 96;
+/// The type code of `Unit`
+const UNIT_TYPE_CODE: u8 = 98;
+/// The type code of `AvlTree`
+const AVL_TREE_TYPE_CODE: u8 = 100;
+
+/// The type of a constant as serialized by the sigma type serializer.
+/// Only the types whose values can be sized without a full sigma parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ConstantType {
+    /// A primitive type by its type code, from `Boolean` (1) to `SigmaProp` (8)
+    Prim(u8),
+    Coll(Box<ConstantType>),
+    Tuple(Vec<ConstantType>),
+    Unit,
+    AvlTree,
+}
+
+impl ConstantType {
+    fn prim(code: u8) -> Option<ConstantType> {
+        match code {
+            1..=8 => Some(ConstantType::Prim(code)),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ConstantType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstantType::Prim(code) => {
+                let names = [
+                    "Boolean",
+                    "Byte",
+                    "Short",
+                    "Int",
+                    "Long",
+                    "BigInt",
+                    "GroupElement",
+                    "SigmaProp",
+                ];
+                write!(f, "{}", names[*code as usize - 1])
+            }
+            ConstantType::Coll(elem) => write!(f, "Coll[{}]", elem),
+            ConstantType::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|t| t.to_string()).collect();
+                write!(f, "({})", items.join(", "))
+            }
+            ConstantType::Unit => write!(f, "Unit"),
+            ConstantType::AvlTree => write!(f, "AvlTree"),
+        }
+    }
+}
+
+/// Encode bytes as a hex string
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A reader over a serialized `ErgoTree` which decodes the VLQ/ZigZag
+/// encodings used by the sigma serializer
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> ByteReader<'a> {
+        ByteReader { bytes, position: 0 }
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        let byte = *self.bytes.get(self.position)?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.position.checked_add(len)?;
+        let bytes = self.bytes.get(self.position..end)?;
+        self.position = end;
+        Some(bytes)
+    }
+
+    /// An unsigned VLQ encoded integer
+    fn vlq(&mut self) -> Option<u64> {
+        let mut value: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift >= 64 {
+                return None;
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+            shift += 7;
+        }
+    }
+
+    /// A ZigZag + VLQ encoded signed integer
+    fn zigzag(&mut self) -> Option<i64> {
+        let n = self.vlq()?;
+        Some((n >> 1) as i64 ^ -((n & 1) as i64))
+    }
+
+    /// A constant serialized as its type followed by its value. Types
+    /// without a `TreeConstant` variant are read as `TreeConstant::Other`.
+    fn constant(&mut self) -> Option<TreeConstant> {
+        let tpe = self.constant_type()?;
+        let start = self.position;
+        let constant = match &tpe {
+            ConstantType::Prim(1) => TreeConstant::Boolean(self.byte()? != 0),
+            ConstantType::Prim(2) => TreeConstant::Byte(self.byte()? as i8),
+            ConstantType::Prim(3) => TreeConstant::Short(self.zigzag()? as i16),
+            ConstantType::Prim(4) => TreeConstant::Int(self.zigzag()? as i32),
+            ConstantType::Prim(5) => TreeConstant::Long(self.zigzag()?),
+            ConstantType::Coll(elem) if **elem == ConstantType::Prim(2) => {
+                let len = self.vlq()? as usize;
+                TreeConstant::Bytes(to_hex(self.bytes(len)?))
+            }
+            _ => {
+                self.skip_value(&tpe)?;
+                TreeConstant::Other {
+                    tpe: tpe.to_string(),
+                    value: to_hex(&self.bytes[start..self.position]),
+                }
+            }
+        };
+        Some(constant)
+    }
+
+    /// A type serialized by the sigma type serializer. `None` for the types
+    /// whose values can not be sized, such as `Option` or `Box`.
+    fn constant_type(&mut self) -> Option<ConstantType> {
+        let code = self.byte()?;
+        let prim_id = code % PRIM_RANGE;
+        // The element type is embedded in the code, or else follows it
+        let element = |reader: &mut ByteReader| match prim_id {
+            0 => reader.constant_type(),
+            _ => ConstantType::prim(prim_id),
+        };
+        let coll = |t: ConstantType| ConstantType::Coll(Box::new(t));
+        let tpe = match code / PRIM_RANGE {
+            0 => ConstantType::prim(code)?,
+            // Coll[T]
+            1 => coll(element(self)?),
+            // Coll[Coll[T]]
+            2 => coll(coll(element(self)?)),
+            // (T1, T2) with T1 embedded, or else both following
+            5 => {
+                let first = element(self)?;
+                ConstantType::Tuple(vec![first, self.constant_type()?])
+            }
+            // (T1, T2) with T2 embedded, or else a triple following
+            6 => match prim_id {
+                0 => ConstantType::Tuple(vec![
+                    self.constant_type()?,
+                    self.constant_type()?,
+                    self.constant_type()?,
+                ]),
+                _ => {
+                    let first = self.constant_type()?;
+                    ConstantType::Tuple(vec![first, ConstantType::prim(prim_id)?])
+                }
+            },
+            // (T, T) with T embedded, or else a quadruple following
+            7 => match prim_id {
+                0 => ConstantType::Tuple(vec![
+                    self.constant_type()?,
+                    self.constant_type()?,
+                    self.constant_type()?,
+                    self.constant_type()?,
+                ]),
+                _ => {
+                    let t = ConstantType::prim(prim_id)?;
+                    ConstantType::Tuple(vec![t.clone(), t])
+                }
+            },
+            _ => match code {
+                TUPLE_TYPE_CODE => {
+                    let len = self.byte()?;
+                    let items = (0..len)
+                        .map(|_| self.constant_type())
+                        .collect::<Option<Vec<ConstantType>>>()?;
+                    ConstantType::Tuple(items)
+                }
+                UNIT_TYPE_CODE => ConstantType::Unit,
+                AVL_TREE_TYPE_CODE => ConstantType::AvlTree,
+                _ => return None,
+            },
+        };
+        Some(tpe)
+    }
+
+    /// Step over a value of the given type
+    fn skip_value(&mut self, tpe: &ConstantType) -> Option<()> {
+        match tpe {
+            ConstantType::Prim(1) | ConstantType::Prim(2) => {
+                self.byte()?;
+            }
+            ConstantType::Prim(3) | ConstantType::Prim(4) | ConstantType::Prim(5) => {
+                self.vlq()?;
+            }
+            // BigInt
+            ConstantType::Prim(6) => {
+                let len = self.vlq()? as usize;
+                self.bytes(len)?;
+            }
+            // GroupElement
+            ConstantType::Prim(7) => {
+                self.bytes(33)?;
+            }
+            ConstantType::Prim(_) => self.sigma_boolean()?,
+            // Coll[Boolean] is packed as bits
+            ConstantType::Coll(elem) if **elem == ConstantType::Prim(1) => {
+                let len = self.vlq()? as usize;
+                self.bytes((len + 7) >> 3)?;
+            }
+            ConstantType::Coll(elem) if **elem == ConstantType::Prim(2) => {
+                let len = self.vlq()? as usize;
+                self.bytes(len)?;
+            }
+            ConstantType::Coll(elem) => {
+                for _ in 0..self.vlq()? {
+                    self.skip_value(elem)?;
+                }
+            }
+            ConstantType::Tuple(items) => {
+                for item in items {
+                    self.skip_value(item)?;
+                }
+            }
+            ConstantType::Unit => {}
+            // The digest, the flags, the key length & an optional value length
+            ConstantType::AvlTree => {
+                self.bytes(33)?;
+                self.byte()?;
+                self.vlq()?;
+                if self.byte()? != 0 {
+                    self.vlq()?;
+                }
+            }
+        }
+        Some(())
+    }
+
+    /// Step over a `SigmaBoolean`, the value of a `SigmaProp`
+    fn sigma_boolean(&mut self) -> Option<()> {
+        match self.byte()? {
+            // ProveDlog
+            0xcd => {
+                self.bytes(33)?;
+            }
+            // ProveDHTuple
+            0xce => {
+                self.bytes(4 * 33)?;
+            }
+            // CAND & COR
+            0x96 | 0x97 => {
+                for _ in 0..self.vlq()? {
+                    self.sigma_boolean()?;
+                }
+            }
+            // CTHRESHOLD
+            0x98 => {
+                self.vlq()?;
+                for _ in 0..self.vlq()? {
+                    self.sigma_boolean()?;
+                }
+            }
+            // TrivialProp false & true
+            0xd2 | 0xd3 => {}
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ergo_headless_dapp_framework::create_candidate;
+    use ergo_headless_dapp_framework::encoding::{build_token, serialize_hex_encoded_string};
+    use ergo_lib::chain::ergo_box::ErgoBox;
+    use ergo_lib::chain::transaction::TxId;
+
+    /// A P2S address whose constant segment holds a `Coll[Int]`
+    const PLACEHOLDER_ADDRESS: &str = "2iHkR7CWvD1R4j1yZg5bkeDRQavjAaVPeTDFGGLZduHyfWMuYpmhHocX8GJoaieTx78FntzJbCBVL6rf96ocJoZdmWBL2fci7NqWgAirppPQmZ7fN9V6z13Ay6brPriBKYqLp1bT2Fk4FkFLCfdPpe";
+
+    /// Constants serialized by ergo-lib (as the registers R4-R9 of a box
+    /// candidate) along with how they are expected to be read
+    fn serialized_constants() -> Vec<(Vec<u8>, TreeConstant)> {
+        let token_id = "0fb1eca4646950743bc5a8c341c16871a0ad9b4077e3b276bf93855d51a042d1";
+        let expected = vec![
+            TreeConstant::Boolean(true),
+            TreeConstant::Byte(-5),
+            TreeConstant::Short(-300),
+            TreeConstant::Int(i32::MIN),
+            TreeConstant::Long(i64::MAX),
+            TreeConstant::Bytes(token_id.to_string()),
+        ];
+        let candidate = create_candidate(
+            1000000,
+            &PLACEHOLDER_ADDRESS.to_string(),
+            &vec![],
+            &vec![
+                true.into(),
+                (-5i8).into(),
+                (-300i16).into(),
+                i32::MIN.into(),
+                i64::MAX.into(),
+                serialize_hex_encoded_string(&token_id.to_string()).unwrap(),
+            ],
+            0,
+        )
+        .unwrap();
+        candidate
+            .additional_registers
+            .get_ordered_values()
+            .iter()
+            .map(|c| c.sigma_serialize_bytes())
+            .zip(expected)
+            .collect()
+    }
+
+    #[test]
+    fn constants_serialized_by_ergo_lib_are_decoded() {
+        let constants = serialized_constants();
+        assert_eq!(constants.len(), 6);
+        for (bytes, expected) in constants {
+            assert_eq!(decode_constant_hex(&to_hex(&bytes)), Some(expected));
+        }
+        for n in &[0i64, 1, -1, 63, -64, 64, i64::MIN] {
+            let candidate = create_candidate(
+                1000000,
+                &PLACEHOLDER_ADDRESS.to_string(),
+                &vec![],
+                &vec![(*n).into()],
+                0,
+            )
+            .unwrap();
+            let bytes =
+                candidate.additional_registers.get_ordered_values()[0].sigma_serialize_bytes();
+            assert_eq!(
+                decode_constant_hex(&to_hex(&bytes)),
+                Some(TreeConstant::Long(*n))
+            );
+        }
+    }
+
+    #[test]
+    fn constant_segments_are_read() {
+        let (constants, expected): (Vec<Vec<u8>>, Vec<TreeConstant>) =
+            serialized_constants().into_iter().unzip();
+        let segment: Vec<u8> = constants.concat();
+        let body = vec![0xd1, 0x7f];
+
+        // Constants segregated, without the size of the tree
+        let mut tree = vec![CONSTANT_SEGREGATION_FLAG, constants.len() as u8];
+        tree.extend(&segment);
+        tree.extend(&body);
+        assert_eq!(constant_segment(&tree), Some(expected.clone()));
+
+        // Constants segregated, with the size of the tree after the header
+        let size = 1 + segment.len() + body.len();
+        let mut sized_tree = vec![
+            CONSTANT_SEGREGATION_FLAG | SIZE_FLAG,
+            (size as u8 & 0x7f) | 0x80,
+            (size >> 7) as u8,
+            constants.len() as u8,
+        ];
+        sized_tree.extend(&segment);
+        sized_tree.extend(&body);
+        assert_eq!(constant_segment(&sized_tree), Some(expected));
+
+        // Constants inlined in the body
+        assert_eq!(constant_segment(&[0x00, 0xd1, 0x7f]), None);
+        // A truncated segment
+        assert_eq!(
+            constant_segment(&tree[0..tree.len() - body.len() - 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn constants_of_other_types_are_read_by_type() {
+        // The placeholder tree holds a `Coll[Int]` constant
+        let tree = deserialize_p2s_to_ergo_tree(PLACEHOLDER_ADDRESS.to_string()).unwrap();
+        let bytes = tree.sigma_serialize_bytes();
+        assert_eq!(bytes[0], CONSTANT_SEGREGATION_FLAG);
+        let constants = constant_segment(&bytes).unwrap();
+        assert_eq!(constants.len(), 5);
+        assert_eq!(
+            constants[3],
+            TreeConstant::Other {
+                tpe: "Coll[Int]".to_string(),
+                value: "0102".to_string(),
+            }
+        );
+        assert_eq!(constants[4], TreeConstant::Int(1));
+
+        let group_element = format!("02{}", "ab".repeat(32));
+        let other = |tpe: &str, value: &str| {
+            Some(TreeConstant::Other {
+                tpe: tpe.to_string(),
+                value: value.to_string(),
+            })
+        };
+        // Coll[Int] of 1 and -1
+        assert_eq!(
+            decode_constant_hex("10020201"),
+            other("Coll[Int]", "020201")
+        );
+        // Coll[Boolean] of 10 bits
+        assert_eq!(
+            decode_constant_hex("0d0aff03"),
+            other("Coll[Boolean]", "0aff03")
+        );
+        // Coll[Coll[Byte]] of [0x01] and []
+        assert_eq!(
+            decode_constant_hex("1a02010100"),
+            other("Coll[Coll[Byte]]", "02010100")
+        );
+        // SigmaProp of a ProveDlog
+        assert_eq!(
+            decode_constant_hex(&format!("08cd{}", group_element)),
+            other("SigmaProp", &format!("cd{}", group_element))
+        );
+        // SigmaProp of a 1-of-2 CTHRESHOLD of a ProveDlog & a TrivialProp
+        assert_eq!(
+            decode_constant_hex(&format!("08980102cd{}d3", group_element)),
+            other("SigmaProp", &format!("980102cd{}d3", group_element))
+        );
+        // (Int, Coll[Byte]) of 1 and [0xff]
+        assert_eq!(
+            decode_constant_hex("400e0201ff"),
+            other("(Int, Coll[Byte])", "0201ff")
+        );
+        // (Long, Boolean, Unit) of -1 and true
+        assert_eq!(
+            decode_constant_hex("60030501620101"),
+            other("(Long, Boolean, Unit)", "0101")
+        );
+
+        // A tree with a `GroupElement` between two known constants
+        let mut tree = vec![CONSTANT_SEGREGATION_FLAG, 3, 0x04, 0x02, 0x07];
+        tree.extend((0..33).map(|_| 0x02));
+        tree.extend(&[0x01, 0x01, 0xd1, 0x7f]);
+        assert_eq!(
+            constant_segment(&tree),
+            Some(vec![
+                TreeConstant::Int(1),
+                TreeConstant::Other {
+                    tpe: "GroupElement".to_string(),
+                    value: "02".repeat(33),
+                },
+                TreeConstant::Boolean(true),
+            ])
+        );
+    }
+
+    #[test]
+    fn unsized_constants_are_not_read() {
+        // Option[Int] & Box
+        assert_eq!(decode_constant_hex("280102"), None);
+        assert_eq!(decode_constant_hex("63"), None);
+        // A truncated Coll[Int]
+        assert_eq!(decode_constant_hex("100202"), None);
+        assert_eq!(decode_constant_hex("0e0"), None);
+        assert_eq!(decode_constant_hex("zz"), None);
+    }
+
+    #[test]
+    fn update_path_checks_the_built_bank_box() {
+        let config = ProtocolConfig {
+            stablecoin_token_id: "01".repeat(32),
+            reservecoin_token_id: "02".repeat(32),
+            bank_nft_id: "03".repeat(32),
+            ..ProtocolConfig::template()
+        };
+        let tokens = vec![
+            build_token(&config.stablecoin_token_id, 1000).unwrap(),
+            build_token(&config.reservecoin_token_id, 1000).unwrap(),
+            build_token(&config.bank_nft_id, 1).unwrap(),
+        ];
+        let candidate = create_candidate(
+            5000000,
+            &PLACEHOLDER_ADDRESS.to_string(),
+            &tokens,
+            &vec![10i64.into(), 20i64.into()],
+            0,
+        )
+        .unwrap();
+        let bank_box = BankBox::new_with_config(
+            &ErgoBox::from_box_candidate(&candidate, TxId::zero(), 0),
+            &config,
+        )
+        .unwrap();
+
+        // Moving the Bank box to the contract it is already guarded by
+        let diff = BankContractDiff::new(
+            &bank_box,
+            &PLACEHOLDER_ADDRESS.to_string(),
+            None,
+            100,
+            &config,
+        )
+        .unwrap();
+        let check = diff.update_path;
+        assert!(check.preserves_bank_nft);
+        assert!(check.preserves_tokens);
+        assert!(check.preserves_registers);
+        assert!(check.preserves_value);
+        assert!(!check.changes_proposition);
+        assert!(!check.is_valid());
+        assert_eq!(diff.constant_changes, Some(vec![]));
+    }
+}
@@ -0,0 +1,859 @@
+// This file holds a local model of the Bank contract
+// (`ageusd-smart-contracts/v0.*/AgeUSD.scala`). It evaluates the same
+// predicates as the on-chain `bankScript` of the configured
+// `ContractVersion` against an input Bank box and the
+// output Bank/Receipt box candidates of a minting/redeeming Action, so that
+// an invalid transaction is caught before it is submitted to a node.
+// All arithmetic follows `Long` semantics, an overflow failing the
+// condition which was being evaluated just as it would fail the script.
+use crate::bank::BankBox;
+use crate::error::{ProtocolError, Result};
+use crate::parameters::{ContractVersion, ProtocolConfig};
+use ergo_headless_dapp_framework::encoding::unwrap_long;
+use ergo_headless_dapp_framework::{BlockHeight, ErgUsdOraclePoolBox, WrappedBox};
+use ergo_lib::chain::ergo_box::{ErgoBoxCandidate, NonMandatoryRegisterId, NonMandatoryRegisters};
+use ergo_lib::chain::token::Token;
+use std::convert::TryFrom;
+
+/// The max reserve ratio used by the Bank contract during the cooling off period
+const INF_RESERVE_RATIO: i64 = 1000000000;
+
+/// The validity conditions checked by the Bank contract when exchanging.
+/// Conditions which were introduced in later versions are only checked
+/// for those versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BankCondition {
+    /// `validDataInput`/`mandatoryRateConditions`: The data-input is the oracle pool box
+    ValidDataInput,
+    /// The output Bank box holds at least the min storage rent
+    MinStorageRent,
+    /// The output Bank box is guarded by the same contract
+    PropositionConserved,
+    /// `rcExchangeXorScExchange`: Exactly one of StableCoins/ReserveCoins is exchanged
+    RcExchangeXorScExchange,
+    /// `coinsConserved`: Tokens + circulating coins are unchanged
+    CoinsConserved,
+    /// `validDeltas`: The circulating coins & reserves move by the Receipt deltas
+    ValidDeltas,
+    /// `tokenIdsConserved`: The Bank box tokens are unchanged
+    TokenIdsConserved,
+    /// `validReserveRatio`: The output reserve ratio is within bounds
+    ValidReserveRatio,
+    /// `bcReserveDelta == brDeltaExpectedWithFee`: The reserves change by the
+    /// nominal price of the exchanged coins plus the protocol fee
+    BrDeltaExpectedWithFee,
+}
+
+/// Evaluate every condition of the Bank contract for the provided
+/// input Bank box, oracle box and output Bank/Receipt box candidates,
+/// as deployed with the parameters of `config`.
+/// Errors with `BankContractViolation` naming the first condition which fails,
+/// or with `InvalidConfig` if the input Bank box was evidently compiled from
+/// a different `ContractVersion` than the configured one.
+pub fn validate_bank_transition(
+    bank_box_in: &BankBox,
+    oracle_box: &ErgUsdOraclePoolBox,
+    bank_box_out: &ErgoBoxCandidate,
+    receipt_box: &ErgoBoxCandidate,
+    current_height: BlockHeight,
+    config: &ProtocolConfig,
+) -> Result<()> {
+    let version = config.contract_version;
+    let bank_in = bank_box_in.get_box();
+
+    // Ensure the configured version matches the deployed Bank contract
+    let possible_versions = ContractVersion::possible_versions(&bank_in.ergo_tree);
+    if !possible_versions.is_empty() && !possible_versions.contains(&version) {
+        return Err(ProtocolError::InvalidConfig(format!(
+            "The Bank box was compiled from contract {}, but {} is configured.",
+            possible_versions[0].name(),
+            version.name()
+        )));
+    }
+
+    // validDataInput && mandatoryRateConditions
+    let oracle_tokens = oracle_box.tokens();
+    let valid_data_input = oracle_tokens
+        .first()
+        .map(|t| {
+            let token_id: String = t.token_id.0.clone().into();
+            token_id == config.oracle_pool_nft_id
+        })
+        .unwrap_or(false);
+    ensure(
+        valid_data_input,
+        BankCondition::ValidDataInput,
+        "The data-input does not hold the oracle pool NFT.",
+    )?;
+    let rate = version.oracle_rate(oracle_box) as i64;
+    validate_exchange(
+        bank_box_in,
+        rate,
+        bank_box_out,
+        receipt_box,
+        current_height,
+        config,
+    )
+}
+
+/// Evaluate the conditions of the Bank contract which follow the
+/// data-input checks, with `rate` being the oracle rate read from the
+/// data-input.
+fn validate_exchange(
+    bank_box_in: &BankBox,
+    rate: i64,
+    bank_box_out: &ErgoBoxCandidate,
+    receipt_box: &ErgoBoxCandidate,
+    current_height: BlockHeight,
+    config: &ProtocolConfig,
+) -> Result<()> {
+    let version = config.contract_version;
+    let bank_in = bank_box_in.get_box();
+
+    // Contracts before v0.3 exclude the storage rent from the reserves
+    let storage_rent = if version.reserves_exclude_storage_rent() {
+        long(config.min_box_value, BankCondition::ValidDeltas)?
+    } else {
+        0
+    };
+
+    // Values of the input Bank box
+    let sc_circ_in = bank_box_in.num_circulating_stablecoins() as i64;
+    let rc_circ_in = bank_box_in.num_circulating_reservecoins() as i64;
+    let bc_reserve_in = long(*bank_in.value.as_u64(), BankCondition::ValidDeltas)? - storage_rent;
+    let sc_tokens_in = token_amount(&bank_in.tokens, 0, BankCondition::TokenIdsConserved)?;
+    let rc_tokens_in = token_amount(&bank_in.tokens, 1, BankCondition::TokenIdsConserved)?;
+
+    // Values of the output Bank box
+    let sc_circ_out = register(
+        &bank_box_out.additional_registers,
+        NonMandatoryRegisterId::R4,
+    )?;
+    let rc_circ_out = register(
+        &bank_box_out.additional_registers,
+        NonMandatoryRegisterId::R5,
+    )?;
+    let bc_reserve_out =
+        long(*bank_box_out.value.as_u64(), BankCondition::ValidDeltas)? - storage_rent;
+    let sc_tokens_out = token_amount(&bank_box_out.tokens, 0, BankCondition::TokenIdsConserved)?;
+    let rc_tokens_out = token_amount(&bank_box_out.tokens, 1, BankCondition::TokenIdsConserved)?;
+
+    // mandatoryBankConditions
+    ensure(
+        *bank_box_out.value.as_u64() >= config.min_box_value,
+        BankCondition::MinStorageRent,
+        "The output Bank box holds less than the min storage rent.",
+    )?;
+    ensure(
+        !version.checks_bank_output_integrity() || bank_box_out.ergo_tree == bank_in.ergo_tree,
+        BankCondition::PropositionConserved,
+        "The output Bank box has a different contract than the input Bank box.",
+    )?;
+
+    let rc_exchange = rc_tokens_in != rc_tokens_out;
+    let sc_exchange = sc_tokens_in != sc_tokens_out;
+    ensure(
+        rc_exchange != sc_exchange,
+        BankCondition::RcExchangeXorScExchange,
+        "Exactly one of StableCoins or ReserveCoins must be exchanged.",
+    )?;
+
+    let cond = BankCondition::CoinsConserved;
+    ensure(
+        add(rc_tokens_in, rc_circ_in, cond)? == add(rc_tokens_out, rc_circ_out, cond)?
+            && add(sc_tokens_in, sc_circ_in, cond)? == add(sc_tokens_out, sc_circ_out, cond)?,
+        cond,
+        "The total number of StableCoins/ReserveCoins is not conserved.",
+    )?;
+
+    let circ_delta = register(
+        &receipt_box.additional_registers,
+        NonMandatoryRegisterId::R4,
+    )?;
+    let bc_reserve_delta = register(
+        &receipt_box.additional_registers,
+        NonMandatoryRegisterId::R5,
+    )?;
+    let rc_circ_delta = if rc_exchange { circ_delta } else { 0 };
+    let sc_circ_delta = if rc_exchange { 0 } else { circ_delta };
+    let cond = BankCondition::ValidDeltas;
+    ensure(
+        add(sc_circ_in, sc_circ_delta, cond)? == sc_circ_out
+            && add(rc_circ_in, rc_circ_delta, cond)? == rc_circ_out
+            && add(bc_reserve_in, bc_reserve_delta, cond)? == bc_reserve_out
+            && (!version.checks_bank_output_integrity() || (sc_circ_out >= 0 && rc_circ_out >= 0)),
+        cond,
+        "The output Bank box does not match the deltas held in the Receipt box.",
+    )?;
+
+    let token_ids_conserved =
+        (0..3).all(
+            |i| match (bank_box_out.tokens.get(i), bank_in.tokens.get(i)) {
+                (Some(out), Some(input)) => out.token_id == input.token_id,
+                _ => false,
+            },
+        );
+    ensure(
+        token_ids_conserved,
+        BankCondition::TokenIdsConserved,
+        "The output Bank box does not hold the same tokens as the input Bank box.",
+    )?;
+
+    // Exchange equations
+    let cond = BankCondition::ValidReserveRatio;
+    let bc_reserve_needed_out = mul(sc_circ_out, rate, cond)?;
+    let bc_reserve_needed_in = mul(sc_circ_in, rate, BankCondition::BrDeltaExpectedWithFee)?;
+    let mut liabilities_in = std::cmp::min(bc_reserve_in, bc_reserve_needed_in);
+    if version.clamps_liabilities() {
+        liabilities_in = std::cmp::max(liabilities_in, 0);
+    }
+
+    let max_reserve_ratio = if config.in_cooling_off_period(current_height) {
+        INF_RESERVE_RATIO
+    } else {
+        long(config.max_reserve_ratio, cond)?
+    };
+    let min_reserve_ratio = long(config.min_reserve_ratio, cond)?;
+    let reserve_ratio_out = if bc_reserve_needed_out == 0 {
+        max_reserve_ratio
+    } else {
+        mul(bc_reserve_out, 100, cond)? / bc_reserve_needed_out
+    };
+
+    let valid_reserve_ratio = if sc_exchange {
+        sc_circ_delta <= 0 || reserve_ratio_out >= min_reserve_ratio
+    } else if rc_circ_delta > 0 {
+        reserve_ratio_out <= max_reserve_ratio
+    } else {
+        reserve_ratio_out >= min_reserve_ratio
+    };
+    ensure(
+        valid_reserve_ratio,
+        cond,
+        &format!(
+            "The output reserve ratio of {}% is outside of the allowed bounds.",
+            reserve_ratio_out
+        ),
+    )?;
+
+    // brDeltaExpectedWithFee
+    let cond = BankCondition::BrDeltaExpectedWithFee;
+    let br_delta_expected = if sc_exchange {
+        let liable_rate = if sc_circ_in == 0 {
+            i64::MAX
+        } else {
+            liabilities_in / sc_circ_in
+        };
+        let sc_nominal_price = std::cmp::min(rate, liable_rate);
+        mul(sc_nominal_price, sc_circ_delta, cond)?
+    } else {
+        let rc_default_price = long(config.reservecoin_default_price, cond)?;
+        let equity_in = bc_reserve_in - liabilities_in;
+        let rc_nominal_price = if equity_in == 0 || rc_circ_in == 0 {
+            rc_default_price
+        } else {
+            equity_in / rc_circ_in
+        };
+        mul(rc_nominal_price, rc_circ_delta, cond)?
+    };
+    let fee_percent = long(config.fee_schedule().protocol_fee_percent, cond)?;
+    let fee = mul(br_delta_expected, fee_percent, cond)? / 100;
+    let br_delta_expected_with_fee = add(br_delta_expected, fee.abs(), cond)?;
+    ensure(
+        bc_reserve_delta == br_delta_expected_with_fee,
+        cond,
+        &format!(
+            "The reserves changed by {} nanoErgs, but {} nanoErgs were expected.",
+            bc_reserve_delta, br_delta_expected_with_fee
+        ),
+    )
+}
+
+/// Errors with a `BankContractViolation` if `condition_holds` is false
+fn ensure(condition_holds: bool, condition: BankCondition, reason: &str) -> Result<()> {
+    if condition_holds {
+        return Ok(());
+    }
+    Err(ProtocolError::BankContractViolation(
+        condition,
+        reason.to_string(),
+    ))
+}
+
+/// Converts a value into a `Long`, failing `condition` if it does not fit
+fn long(value: u64, condition: BankCondition) -> Result<i64> {
+    i64::try_from(value).map_err(|_| long_overflow(condition))
+}
+
+/// Adds two `Long`s, failing `condition` on overflow
+fn add(a: i64, b: i64, condition: BankCondition) -> Result<i64> {
+    a.checked_add(b).ok_or_else(|| long_overflow(condition))
+}
+
+/// Multiplies two `Long`s, failing `condition` on overflow
+fn mul(a: i64, b: i64, condition: BankCondition) -> Result<i64> {
+    a.checked_mul(b).ok_or_else(|| long_overflow(condition))
+}
+
+fn long_overflow(condition: BankCondition) -> ProtocolError {
+    ProtocolError::BankContractViolation(condition, "A `Long` overflowed.".to_string())
+}
+
+/// The amount of the token at `index`, failing `condition` if missing
+fn token_amount(tokens: &[Token], index: usize, condition: BankCondition) -> Result<i64> {
+    let token = tokens.get(index).ok_or_else(|| {
+        ProtocolError::BankContractViolation(condition, format!("Token {} is missing.", index))
+    })?;
+    long(u64::from(token.amount), condition)
+}
+
+/// The `Long` held in a register, failing `ValidDeltas` if missing
+fn register(registers: &NonMandatoryRegisters, id: NonMandatoryRegisterId) -> Result<i64> {
+    registers
+        .get(id)
+        .and_then(|c| unwrap_long(c).ok())
+        .ok_or_else(|| {
+            ProtocolError::BankContractViolation(
+                BankCondition::ValidDeltas,
+                format!("Register {:?} does not hold a `Long`.", id),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ergo_headless_dapp_framework::create_candidate;
+    use ergo_headless_dapp_framework::encoding::{
+        build_token, deserialize_p2s_to_ergo_tree, serialize_hex_encoded_string,
+    };
+    use ergo_lib::chain::ergo_box::ErgoBox;
+    use ergo_lib::chain::transaction::TxId;
+    use ergo_lib::ergo_tree::ErgoTree;
+    use ergo_lib::serialization::SigmaSerializable;
+
+    /// A P2S address whose contract holds none of the oracle pool NFTs, so
+    /// that no `ContractVersion` is detected from it
+    const CONTRACT_ADDRESS: &str = "2iHkR7CWvD1R4j1yZg5bkeDRQavjAaVPeTDFGGLZduHyfWMuYpmhHocX8GJoaieTx78FntzJbCBVL6rf96ocJoZdmWBL2fci7NqWgAirppPQmZ7fN9V6z13Ay6brPriBKYqLp1bT2Fk4FkFLCfdPpe";
+    const SC_TOKEN_ID: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+    const RC_TOKEN_ID: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+    const BANK_NFT_ID: &str = "0303030303030303030303030303030303030303030303030303030303030303";
+    const OTHER_NFT_ID: &str = "0404040404040404040404040404040404040404040404040404040404040404";
+    /// The nanoErg price of 1 cent
+    const RATE: i64 = 2_000_000;
+    /// A height after the cooling off period
+    const HEIGHT: BlockHeight = 500_000;
+
+    /// A Bank box with a reserve ratio of 500% and a ReserveCoin nominal
+    /// price of 8000 nanoErgs
+    const BANK: Bank = Bank {
+        reserves: 10_000_000_000_000,
+        sc_tokens: 1_000_000_000_000,
+        rc_tokens: 1_000_000_000_000,
+        sc_circ: 1_000_000,
+        rc_circ: 1_000_000_000,
+    };
+
+    /// The state held by a Bank box
+    #[derive(Debug, Clone, Copy)]
+    struct Bank {
+        reserves: u64,
+        sc_tokens: u64,
+        rc_tokens: u64,
+        sc_circ: i64,
+        rc_circ: i64,
+    }
+
+    impl Bank {
+        /// The Bank box after `sc_delta` StableCoins and `rc_delta`
+        /// ReserveCoins were exchanged for `reserve_delta` nanoErgs
+        fn exchange(&self, sc_delta: i64, rc_delta: i64, reserve_delta: i64) -> Bank {
+            Bank {
+                reserves: (self.reserves as i64 + reserve_delta) as u64,
+                sc_tokens: (self.sc_tokens as i64 - sc_delta) as u64,
+                rc_tokens: (self.rc_tokens as i64 - rc_delta) as u64,
+                sc_circ: self.sc_circ + sc_delta,
+                rc_circ: self.rc_circ + rc_delta,
+            }
+        }
+
+        fn candidate(&self, nft_id: &str) -> ErgoBoxCandidate {
+            let tokens = vec![
+                build_token(SC_TOKEN_ID, self.sc_tokens).unwrap(),
+                build_token(RC_TOKEN_ID, self.rc_tokens).unwrap(),
+                build_token(nft_id, 1).unwrap(),
+            ];
+            let registers = vec![self.sc_circ.into(), self.rc_circ.into()];
+            create_candidate(
+                self.reserves,
+                &CONTRACT_ADDRESS.to_string(),
+                &tokens,
+                &registers,
+                HEIGHT,
+            )
+            .unwrap()
+        }
+    }
+
+    fn config() -> ProtocolConfig {
+        ProtocolConfig {
+            stablecoin_token_id: SC_TOKEN_ID.to_string(),
+            reservecoin_token_id: RC_TOKEN_ID.to_string(),
+            bank_nft_id: BANK_NFT_ID.to_string(),
+            oracle_pool_nft_id: ContractVersion::V0_4.oracle_pool_nft_id().to_string(),
+            ..ProtocolConfig::template()
+        }
+    }
+
+    /// An oracle pool box holding `nft_id` whose datapoint is `RATE` per cent
+    fn oracle_box(nft_id: &str) -> ErgUsdOraclePoolBox {
+        let candidate = create_candidate(
+            config().min_box_value,
+            &CONTRACT_ADDRESS.to_string(),
+            &vec![build_token(nft_id, 1).unwrap()],
+            &vec![(RATE * 100).into()],
+            HEIGHT,
+        )
+        .unwrap();
+        ErgUsdOraclePoolBox::new(&ErgoBox::from_box_candidate(&candidate, TxId::zero(), 0)).unwrap()
+    }
+
+    /// The placeholder contract with the oracle pool NFT ID of `version`
+    /// appended to its constants, as the Bank contract of that version
+    /// embeds it
+    fn contract_of(version: ContractVersion) -> ErgoTree {
+        let tree = deserialize_p2s_to_ergo_tree(CONTRACT_ADDRESS.to_string())
+            .unwrap()
+            .sigma_serialize_bytes();
+        // The 5 constants of the placeholder contract end at byte 67
+        let (constants, body) = tree.split_at(67);
+        let nft_id = serialize_hex_encoded_string(&version.oracle_pool_nft_id().to_string())
+            .unwrap()
+            .sigma_serialize_bytes();
+        let mut bytes = vec![constants[0], constants[1] + 1];
+        bytes.extend(&constants[2..]);
+        bytes.extend(nft_id);
+        bytes.extend(body);
+        ErgoTree::sigma_parse_bytes(bytes).unwrap()
+    }
+
+    /// Validate the Bank box `bank_in` turning into `bank_out` with a
+    /// Receipt box holding `circ_delta` and `reserve_delta`, reading the
+    /// rate from `oracle_box`
+    fn validate(
+        bank_in: &ErgoBoxCandidate,
+        bank_out: &ErgoBoxCandidate,
+        circ_delta: i64,
+        reserve_delta: i64,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<()> {
+        let bank_box_in = BankBox::new_with_config(
+            &ErgoBox::from_box_candidate(bank_in, TxId::zero(), 0),
+            config,
+        )
+        .unwrap();
+        let receipt_box = create_candidate(
+            config.min_box_value,
+            &CONTRACT_ADDRESS.to_string(),
+            &vec![],
+            &vec![circ_delta.into(), reserve_delta.into()],
+            current_height,
+        )
+        .unwrap();
+        validate_bank_transition(
+            &bank_box_in,
+            oracle_box,
+            bank_out,
+            &receipt_box,
+            current_height,
+            config,
+        )
+    }
+
+    /// Validate `bank_in` turning into `bank_out` (holding `out_nft_id`)
+    /// with a Receipt box holding `circ_delta` and `reserve_delta`
+    fn transition(
+        bank_in: &Bank,
+        bank_out: &Bank,
+        out_nft_id: &str,
+        circ_delta: i64,
+        reserve_delta: i64,
+        current_height: BlockHeight,
+    ) -> Result<()> {
+        let config = config();
+        validate(
+            &bank_in.candidate(BANK_NFT_ID),
+            &bank_out.candidate(out_nft_id),
+            circ_delta,
+            reserve_delta,
+            current_height,
+            &oracle_box(&config.oracle_pool_nft_id),
+            &config,
+        )
+    }
+
+    /// Exchange StableCoins for `reserve_delta` nanoErgs
+    fn exchange_stablecoins(bank_in: &Bank, delta: i64, reserve_delta: i64) -> Result<()> {
+        let bank_out = bank_in.exchange(delta, 0, reserve_delta);
+        transition(
+            bank_in,
+            &bank_out,
+            BANK_NFT_ID,
+            delta,
+            reserve_delta,
+            HEIGHT,
+        )
+    }
+
+    /// Exchange ReserveCoins for `reserve_delta` nanoErgs at `current_height`
+    fn exchange_reservecoins(
+        bank_in: &Bank,
+        delta: i64,
+        reserve_delta: i64,
+        current_height: BlockHeight,
+    ) -> Result<()> {
+        let bank_out = bank_in.exchange(0, delta, reserve_delta);
+        transition(
+            bank_in,
+            &bank_out,
+            BANK_NFT_ID,
+            delta,
+            reserve_delta,
+            current_height,
+        )
+    }
+
+    fn violated(result: Result<()>) -> Option<BankCondition> {
+        match result {
+            Err(ProtocolError::BankContractViolation(condition, _)) => Some(condition),
+            Ok(()) => None,
+            Err(e) => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn accepts_exchanges_at_the_nominal_price_plus_fee() {
+        // 1000 StableCoins at 2_000_000 plus the 2% fee
+        assert_eq!(
+            violated(exchange_stablecoins(&BANK, 1000, 2_040_000_000)),
+            None
+        );
+        // The fee is kept in the reserves when redeeming
+        assert_eq!(
+            violated(exchange_stablecoins(&BANK, -1000, -1_960_000_000)),
+            None
+        );
+        // 1_000_000 ReserveCoins at 8000 plus the 2% fee
+        assert_eq!(
+            violated(exchange_reservecoins(
+                &BANK,
+                1_000_000,
+                8_160_000_000,
+                HEIGHT
+            )),
+            None
+        );
+        assert_eq!(
+            violated(exchange_reservecoins(
+                &BANK,
+                -1_000_000,
+                -7_840_000_000,
+                HEIGHT
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn valid_data_input() {
+        let config = config();
+        let bank_out = BANK.exchange(1000, 0, 2_040_000_000);
+        let mint = |oracle_box: &ErgUsdOraclePoolBox, config: &ProtocolConfig| {
+            validate(
+                &BANK.candidate(BANK_NFT_ID),
+                &bank_out.candidate(BANK_NFT_ID),
+                1000,
+                2_040_000_000,
+                HEIGHT,
+                oracle_box,
+                config,
+            )
+        };
+        let oracle_box = oracle_box(&config.oracle_pool_nft_id);
+        assert_eq!(violated(mint(&oracle_box, &config)), None);
+        // The data-input holds an NFT other than the configured one
+        let other_config = ProtocolConfig {
+            oracle_pool_nft_id: OTHER_NFT_ID.to_string(),
+            ..config
+        };
+        assert_eq!(
+            violated(mint(&oracle_box, &other_config)),
+            Some(BankCondition::ValidDataInput)
+        );
+    }
+
+    #[test]
+    fn rejects_bank_boxes_of_other_versions() {
+        let config = config();
+        let bank_out = BANK.exchange(1000, 0, 2_040_000_000);
+        let mint = |version| {
+            let mut bank_in = BANK.candidate(BANK_NFT_ID);
+            bank_in.ergo_tree = contract_of(version);
+            let mut bank_box_out = bank_out.candidate(BANK_NFT_ID);
+            bank_box_out.ergo_tree = contract_of(version);
+            validate(
+                &bank_in,
+                &bank_box_out,
+                1000,
+                2_040_000_000,
+                HEIGHT,
+                &oracle_box(&config.oracle_pool_nft_id),
+                &config,
+            )
+        };
+        // v0.3 and v0.4 embed the same NFT ID
+        assert_eq!(violated(mint(ContractVersion::V0_4)), None);
+        assert_eq!(violated(mint(ContractVersion::V0_3)), None);
+        for version in &[ContractVersion::V0_1, ContractVersion::V0_2] {
+            match mint(*version) {
+                Err(ProtocolError::InvalidConfig(_)) => (),
+                result => panic!("expected InvalidConfig, got {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn min_storage_rent() {
+        let bank_out = Bank {
+            reserves: config().min_box_value - 1,
+            ..BANK.exchange(1000, 0, 2_040_000_000)
+        };
+        assert_eq!(
+            violated(transition(
+                &BANK,
+                &bank_out,
+                BANK_NFT_ID,
+                1000,
+                2_040_000_000,
+                HEIGHT
+            )),
+            Some(BankCondition::MinStorageRent)
+        );
+    }
+
+    #[test]
+    fn proposition_conserved() {
+        let config = config();
+        let mut bank_out = BANK.exchange(1000, 0, 2_040_000_000).candidate(BANK_NFT_ID);
+        bank_out.ergo_tree = contract_of(ContractVersion::V0_4);
+        assert_eq!(
+            violated(validate(
+                &BANK.candidate(BANK_NFT_ID),
+                &bank_out,
+                1000,
+                2_040_000_000,
+                HEIGHT,
+                &oracle_box(&config.oracle_pool_nft_id),
+                &config,
+            )),
+            Some(BankCondition::PropositionConserved)
+        );
+    }
+
+    #[test]
+    fn rc_exchange_xor_sc_exchange() {
+        // Both StableCoins and ReserveCoins are exchanged
+        let bank_out = BANK.exchange(1000, 1_000_000, 10_200_000_000);
+        assert_eq!(
+            violated(transition(
+                &BANK,
+                &bank_out,
+                BANK_NFT_ID,
+                1000,
+                10_200_000_000,
+                HEIGHT
+            )),
+            Some(BankCondition::RcExchangeXorScExchange)
+        );
+        // Neither is exchanged
+        assert_eq!(
+            violated(transition(&BANK, &BANK, BANK_NFT_ID, 0, 0, HEIGHT)),
+            Some(BankCondition::RcExchangeXorScExchange)
+        );
+    }
+
+    #[test]
+    fn coins_conserved() {
+        let mut bank_out = BANK.exchange(1000, 0, 2_040_000_000);
+        bank_out.sc_tokens += 1;
+        assert_eq!(
+            violated(transition(
+                &BANK,
+                &bank_out,
+                BANK_NFT_ID,
+                1000,
+                2_040_000_000,
+                HEIGHT
+            )),
+            Some(BankCondition::CoinsConserved)
+        );
+    }
+
+    #[test]
+    fn token_ids_conserved() {
+        let bank_out = BANK.exchange(1000, 0, 2_040_000_000);
+        assert_eq!(
+            violated(transition(
+                &BANK,
+                &bank_out,
+                OTHER_NFT_ID,
+                1000,
+                2_040_000_000,
+                HEIGHT
+            )),
+            Some(BankCondition::TokenIdsConserved)
+        );
+    }
+
+    #[test]
+    fn valid_deltas() {
+        // The reserves move by 1 nanoErg more than the Receipt box claims
+        let bank_out = BANK.exchange(1000, 0, 2_040_000_001);
+        assert_eq!(
+            violated(transition(
+                &BANK,
+                &bank_out,
+                BANK_NFT_ID,
+                1000,
+                2_040_000_000,
+                HEIGHT
+            )),
+            Some(BankCondition::ValidDeltas)
+        );
+        // The circulating StableCoins move by 1 more than the Receipt box claims
+        let bank_out = BANK.exchange(1000, 0, 2_040_000_000);
+        assert_eq!(
+            violated(transition(
+                &BANK,
+                &bank_out,
+                BANK_NFT_ID,
+                999,
+                2_040_000_000,
+                HEIGHT
+            )),
+            Some(BankCondition::ValidDeltas)
+        );
+    }
+
+    #[test]
+    fn valid_reserve_ratio_min() {
+        // Minting 2_000_000 StableCoins takes the reserve ratio to 234%
+        assert_eq!(
+            violated(exchange_stablecoins(&BANK, 2_000_000, 4_080_000_000_000)),
+            Some(BankCondition::ValidReserveRatio)
+        );
+        // Redeeming 300_000_000 ReserveCoins takes the reserve ratio to 382%
+        assert_eq!(
+            violated(exchange_reservecoins(
+                &BANK,
+                -300_000_000,
+                -2_352_000_000_000,
+                HEIGHT
+            )),
+            Some(BankCondition::ValidReserveRatio)
+        );
+        // Redeeming StableCoins is allowed below the min reserve ratio
+        let undercollateralized = Bank {
+            reserves: 6_000_000_000_000,
+            ..BANK
+        };
+        assert_eq!(
+            violated(exchange_stablecoins(
+                &undercollateralized,
+                -1000,
+                -1_960_000_000
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn valid_reserve_ratio_max_after_cooling_off() {
+        // Minting 750_000_000 ReserveCoins takes the reserve ratio to 806%
+        let mint = |height| {
+            violated(exchange_reservecoins(
+                &BANK,
+                750_000_000,
+                6_120_000_000_000,
+                height,
+            ))
+        };
+        let cooling_off_height = config().cooling_off_height;
+        assert_eq!(mint(cooling_off_height - 1), None);
+        assert_eq!(
+            mint(cooling_off_height),
+            Some(BankCondition::ValidReserveRatio)
+        );
+        assert_eq!(
+            mint(cooling_off_height + 1),
+            Some(BankCondition::ValidReserveRatio)
+        );
+    }
+
+    #[test]
+    fn br_delta_expected_with_fee() {
+        // Without the protocol fee
+        assert_eq!(
+            violated(exchange_stablecoins(&BANK, 1000, 2_000_000_000)),
+            Some(BankCondition::BrDeltaExpectedWithFee)
+        );
+        // Charging the fee on top of a redemption instead of keeping it
+        assert_eq!(
+            violated(exchange_stablecoins(&BANK, -1000, -2_040_000_000)),
+            Some(BankCondition::BrDeltaExpectedWithFee)
+        );
+        assert_eq!(
+            violated(exchange_reservecoins(
+                &BANK,
+                1_000_000,
+                8_160_000_001,
+                HEIGHT
+            )),
+            Some(BankCondition::BrDeltaExpectedWithFee)
+        );
+    }
+
+    #[test]
+    fn default_reservecoin_price_without_circulating_reservecoins() {
+        let bank = Bank { rc_circ: 0, ..BANK };
+        // 1000 ReserveCoins at the default price of 1_000_000 plus the 2% fee
+        assert_eq!(
+            violated(exchange_reservecoins(&bank, 1000, 1_020_000_000, HEIGHT)),
+            None
+        );
+        // Rather than at the price of the equity
+        assert_eq!(
+            violated(exchange_reservecoins(&bank, 1000, 8_160_000, HEIGHT)),
+            Some(BankCondition::BrDeltaExpectedWithFee)
+        );
+    }
+
+    #[test]
+    fn default_reservecoin_price_without_equity() {
+        // The reserves exactly cover the liabilities
+        let bank = Bank {
+            reserves: 2_000_000_000_000,
+            ..BANK
+        };
+        assert_eq!(
+            violated(exchange_reservecoins(&bank, 1000, 1_020_000_000, HEIGHT)),
+            None
+        );
+        assert_eq!(
+            violated(exchange_reservecoins(&bank, 1000, 0, HEIGHT)),
+            Some(BankCondition::BrDeltaExpectedWithFee)
+        );
+    }
+}
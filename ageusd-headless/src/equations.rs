@@ -1,19 +1,785 @@
+use crate::error::{ProtocolError, Result};
 use ergo_headless_dapp_framework::NanoErg;
+use std::convert::TryFrom;
 
 /// Calculates the Reserve Ratio based on provided inputs.
+/// Returns `u64::MAX` if the ratio does not fit within a `u64`.
 pub fn reserve_ratio(
     base_reserves: NanoErg,
     circulating_stablecoins: u64,
     oracle_rate: NanoErg,
 ) -> u64 {
+    checked_reserve_ratio(base_reserves, circulating_stablecoins, oracle_rate).unwrap_or(u64::MAX)
+}
+
+/// Calculates the Reserve Ratio based on provided inputs using `u128`
+/// intermediate math. Errors if the resulting ratio does not fit within
+/// a `u64`.
+pub fn checked_reserve_ratio(
+    base_reserves: NanoErg,
+    circulating_stablecoins: u64,
+    oracle_rate: NanoErg,
+) -> Result<u64> {
     if base_reserves == 0 || oracle_rate == 0 {
-        return 0;
+        return Ok(0);
     }
 
     // If no stablecoins are circulating, treat the reserve ratio as if 1 is circulating to have a non-zero result
-    if circulating_stablecoins == 0 {
-        return (base_reserves * 100) / (1 * oracle_rate);
+    let circulating_stablecoins = std::cmp::max(circulating_stablecoins, 1);
+    let ratio =
+        (base_reserves as u128 * 100) / (circulating_stablecoins as u128 * oracle_rate as u128);
+    u64::try_from(ratio).map_err(|_| overflow("reserve ratio"))
+}
+
+/// The number of basis points in 1%
+pub const BPS_PER_PERCENT: u64 = 100;
+
+/// Calculates the Reserve Ratio in basis points (40090 == 400.9%) based on
+/// provided inputs. Rounds down, so that `reserve_ratio_bps / 100` is always
+/// equal to `reserve_ratio`.
+/// Returns `u64::MAX` if the ratio does not fit within a `u64`.
+pub fn reserve_ratio_bps(
+    base_reserves: NanoErg,
+    circulating_stablecoins: u64,
+    oracle_rate: NanoErg,
+) -> u64 {
+    checked_reserve_ratio_bps(base_reserves, circulating_stablecoins, oracle_rate)
+        .unwrap_or(u64::MAX)
+}
+
+/// Calculates the Reserve Ratio in basis points using `u128` intermediate
+/// math. Errors if the resulting ratio does not fit within a `u64`.
+pub fn checked_reserve_ratio_bps(
+    base_reserves: NanoErg,
+    circulating_stablecoins: u64,
+    oracle_rate: NanoErg,
+) -> Result<u64> {
+    if base_reserves == 0 || oracle_rate == 0 {
+        return Ok(0);
+    }
+
+    // Treat no circulating StableCoins as 1, as `checked_reserve_ratio` does
+    let circulating_stablecoins = std::cmp::max(circulating_stablecoins, 1);
+    let ratio = (base_reserves as u128 * 100 * BPS_PER_PERCENT as u128)
+        / (circulating_stablecoins as u128 * oracle_rate as u128);
+    u64::try_from(ratio).map_err(|_| overflow("reserve ratio in basis points"))
+}
+
+/// Calculates the Reserve Ratio exactly as the Bank contract does, being
+/// `bcReserve * 100 / bcReserveNeeded` using `Long` math. Returns `None` if
+/// no reserves are needed (no StableCoins are circulating or the rate is 0),
+/// in which case the contract uses the max reserve ratio instead.
+/// Errors if an intermediate value does not fit within a `Long`.
+pub fn contract_reserve_ratio(
+    base_reserves: NanoErg,
+    circulating_stablecoins: u64,
+    oracle_rate: NanoErg,
+) -> Result<Option<u64>> {
+    let reserves_needed = long_mul(circulating_stablecoins, oracle_rate, "reserves needed")?;
+    if reserves_needed == 0 {
+        return Ok(None);
+    }
+    let scaled_reserves = long_mul(base_reserves, 100, "scaled base reserves")?;
+    Ok(Some(scaled_reserves / reserves_needed))
+}
+
+/// Adds two values, erroring on overflow.
+pub fn checked_add(a: u64, b: u64, context: &str) -> Result<u64> {
+    a.checked_add(b).ok_or_else(|| overflow(context))
+}
+
+/// Subtracts `b` from `a`, erroring on underflow.
+pub fn checked_sub(a: u64, b: u64, context: &str) -> Result<u64> {
+    a.checked_sub(b).ok_or_else(|| overflow(context))
+}
+
+/// Multiplies two values as the on-chain contract would with `Long`s,
+/// erroring if the result does not fit within a `Long`.
+pub fn long_mul(a: u64, b: u64, context: &str) -> Result<u64> {
+    a.checked_mul(b)
+        .filter(|result| *result <= i64::MAX as u64)
+        .ok_or_else(|| overflow(context))
+}
+
+/// Adds two values as the on-chain contract would with `Long`s,
+/// erroring if the result does not fit within a `Long`.
+pub fn long_add(a: u64, b: u64, context: &str) -> Result<u64> {
+    a.checked_add(b)
+        .filter(|result| *result <= i64::MAX as u64)
+        .ok_or_else(|| overflow(context))
+}
+
+/// Calculates `a * b / c` using `u128` intermediate math, erroring if
+/// the result does not fit within a `u64` or if `c` is 0.
+pub fn mul_div(a: u64, b: u64, c: u64, context: &str) -> Result<u64> {
+    if c == 0 {
+        return Err(overflow(context));
+    }
+    u64::try_from(a as u128 * b as u128 / c as u128).map_err(|_| overflow(context))
+}
+
+/// Converts a value into a `Long` so that it can be encoded in a register,
+/// erroring if it does not fit.
+pub fn to_long(value: u64, context: &str) -> Result<i64> {
+    i64::try_from(value).map_err(|_| overflow(context))
+}
+
+/// The closed-form maximum number of StableCoins which can be minted while
+/// keeping the reserve ratio at or above `min_reserve_ratio`. Minting `n`
+/// adds `price * n` plus the protocol fee of `fee_percent` to the reserves,
+/// so the bound is solved from
+/// `100 * (reserves + cost(n)) >= min_reserve_ratio * (circulating + n) * rate`.
+/// The protocol fee is rounded down per mint, so the result may be 1 more
+/// than the true maximum. `None` if the reserve ratio does not bound minting.
+pub fn estimate_max_stablecoin_mint(
+    base_reserves: NanoErg,
+    circulating_stablecoins: u64,
+    oracle_rate: NanoErg,
+    nominal_price: NanoErg,
+    fee_percent: u64,
+    min_reserve_ratio: u64,
+) -> Option<u64> {
+    // The reserve ratio drops by `k / (circulating * rate)` per coin minted
+    let k = (min_reserve_ratio as i128).checked_mul(oracle_rate as i128)?
+        - (nominal_price as i128).checked_mul(100 + fee_percent as i128)?;
+    if k <= 0 {
+        return None;
+    }
+    let a = base_reserves as i128 * 100
+        - (min_reserve_ratio as i128)
+            .checked_mul(circulating_stablecoins as i128)?
+            .checked_mul(oracle_rate as i128)?;
+    Some(clamp_to_u64(a / k))
+}
+
+/// The closed-form maximum number of ReserveCoins which can be minted while
+/// keeping the reserve ratio at or below `max_reserve_ratio`, solved from
+/// `100 * (reserves + cost(n)) < (max_reserve_ratio + 1) * circulating * rate`.
+/// The protocol fee is rounded down per mint, so the result may be 1 more
+/// than the true maximum. `None` if the reserve ratio does not bound minting.
+pub fn estimate_max_reservecoin_mint(
+    base_reserves: NanoErg,
+    circulating_stablecoins: u64,
+    oracle_rate: NanoErg,
+    nominal_price: NanoErg,
+    fee_percent: u64,
+    max_reserve_ratio: u64,
+) -> Option<u64> {
+    // The reserve ratio grows by `k / (circulating * rate)` per coin minted
+    let k = (nominal_price as i128).checked_mul(100 + fee_percent as i128)?;
+    if k == 0 || oracle_rate == 0 {
+        return None;
+    }
+    // No circulating StableCoins are treated as 1, as `checked_reserve_ratio` does
+    let circulating_stablecoins = std::cmp::max(circulating_stablecoins, 1);
+    let b = (max_reserve_ratio as i128 + 1)
+        .checked_mul(circulating_stablecoins as i128)?
+        .checked_mul(oracle_rate as i128)?
+        - 1
+        - base_reserves as i128 * 100;
+    if b < 0 {
+        return Some(0);
+    }
+    // The rounded down fee lowers the cost by up to 99/100 of a nanoErg
+    Some(clamp_to_u64((b + 99) / k))
+}
+
+/// The closed-form maximum number of ReserveCoins which can be redeemed
+/// while keeping the reserve ratio at or above `min_reserve_ratio`.
+/// Redeeming `n` removes `price * n` minus the protocol fee of
+/// `fee_percent` from the reserves, so the bound is solved from
+/// `100 * (reserves - amount(n)) >= min_reserve_ratio * circulating * rate`.
+/// The protocol fee is rounded down per redeem, so the result may be 1 more
+/// than the true maximum. `None` if the reserve ratio does not bound redeeming.
+pub fn estimate_max_reservecoin_redeem(
+    base_reserves: NanoErg,
+    circulating_stablecoins: u64,
+    oracle_rate: NanoErg,
+    nominal_price: NanoErg,
+    fee_percent: u64,
+    min_reserve_ratio: u64,
+) -> Option<u64> {
+    // The reserve ratio drops by `k / (circulating * rate)` per coin redeemed
+    let k = nominal_price as i128 * (100 - std::cmp::min(fee_percent, 100) as i128);
+    if k == 0 || min_reserve_ratio == 0 {
+        return None;
+    }
+    // No circulating StableCoins are treated as 1, as `checked_reserve_ratio` does
+    let circulating_stablecoins = std::cmp::max(circulating_stablecoins, 1);
+    let a = base_reserves as i128 * 100
+        - (min_reserve_ratio as i128)
+            .checked_mul(circulating_stablecoins as i128)?
+            .checked_mul(oracle_rate as i128)?;
+    if a < 0 {
+        return Some(0);
+    }
+    Some(clamp_to_u64(a / k))
+}
+
+/// Settle on the largest amount within `0..=cap` which satisfies
+/// `predicate`, starting from a closed-form `estimate` which may be off by
+/// one. Falls back to a binary search if the estimate is further off (ie. due
+/// to rounding dominating in a degenerate state) or if there is none.
+/// If `predicate` is not monotone (ie. the rounded down protocol fee lets
+/// the reserve ratio after minting StableCoins step back over the limit),
+/// the amount settled on satisfies `predicate` while 1 more does not, but
+/// it may not be the largest such amount.
+pub fn settle_maximum<F: Fn(u64) -> bool>(estimate: Option<u64>, cap: u64, predicate: F) -> u64 {
+    let satisfies = |n: u64| n == 0 || predicate(n);
+    if let Some(estimate) = estimate {
+        let estimate = std::cmp::min(estimate, cap);
+        let candidates = [
+            estimate.saturating_add(1),
+            estimate,
+            estimate.saturating_sub(1),
+        ];
+        for n in candidates.iter().copied().filter(|n| *n <= cap) {
+            if satisfies(n) && (n == cap || !predicate(n + 1)) {
+                return n;
+            }
+        }
+    }
+    last_satisfying(0, cap, satisfies).unwrap_or(0)
+}
+
+/// Finds the largest value within `low..=high` which satisfies `predicate`,
+/// given that `predicate` holds up until some value and fails thereafter.
+pub(crate) fn last_satisfying<F: Fn(u64) -> bool>(
+    low: u64,
+    high: u64,
+    predicate: F,
+) -> Option<u64> {
+    if low > high || !predicate(low) {
+        return None;
+    }
+    let (mut low, mut high) = (low, high);
+    while low < high {
+        // Round up so that the search always progresses
+        let mid = high - (high - low) / 2;
+        if predicate(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Some(low)
+}
+
+/// Finds the smallest value within `low..=high` which satisfies `predicate`,
+/// given that `predicate` fails up until some value and holds thereafter.
+pub(crate) fn first_satisfying<F: Fn(u64) -> bool>(
+    low: u64,
+    high: u64,
+    predicate: F,
+) -> Option<u64> {
+    if low > high || !predicate(high) {
+        return None;
+    }
+    let (mut low, mut high) = (low, high);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if predicate(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Some(low)
+}
+
+fn clamp_to_u64(value: i128) -> u64 {
+    u64::try_from(std::cmp::max(value, 0)).unwrap_or(u64::MAX)
+}
+
+fn overflow(context: &str) -> ProtocolError {
+    ProtocolError::ArithmeticOverflow(context.to_string())
+}
+
+/// Values around the `Long`/`u64` overflow boundaries of the pricing math
+/// alongside pseudo-random values of every magnitude, used as the inputs of
+/// the property tests which compare against a `u128` reference model
+#[cfg(test)]
+pub(crate) fn sample_values() -> Vec<u64> {
+    let long_max = i64::MAX as u64;
+    let mut values = vec![
+        0,
+        1,
+        2,
+        99,
+        100,
+        101,
+        10_000,
+        3_037_000_499,
+        3_037_000_500,
+        4_294_967_296,
+        long_max / 10_000,
+        long_max / 100,
+        long_max / 100 + 1,
+        long_max / 2,
+        long_max / 2 + 1,
+        long_max - 1,
+        long_max,
+        long_max + 1,
+        u64::MAX / 100,
+        u64::MAX / 2,
+        u64::MAX - 1,
+        u64::MAX,
+    ];
+    // xorshift64, truncated to a varying number of bits
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    for bits in (4..64).step_by(3) {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        values.push(state >> (64 - bits));
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fees::FeeSchedule;
+
+    /// The largest amount the search is allowed to settle on
+    const CAP: u64 = 300;
+
+    fn ratio(base_reserves: u64, circulating_stablecoins: u64, oracle_rate: u64) -> u64 {
+        checked_reserve_ratio(base_reserves, circulating_stablecoins, oracle_rate).unwrap()
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct State {
+        reserves: u64,
+        stablecoins: u64,
+        rate: u64,
+        price: u64,
+        fee_percent: u64,
+        limit: u64,
+    }
+
+    // The reserve ratio checks of `BankBox`, which settle the maximum amounts
+
+    impl State {
+        fn fee_schedule(&self) -> FeeSchedule {
+            FeeSchedule::new(self.fee_percent, 0)
+        }
+
+        fn mint_stablecoin_ok(&self, n: u64) -> bool {
+            let new_ratio = self
+                .fee_schedule()
+                .base_mint_cost(self.price, n)
+                .and_then(|cost| checked_add(self.reserves, cost, ""))
+                .and_then(|reserves| {
+                    checked_reserve_ratio(reserves, self.stablecoins + n, self.rate)
+                });
+            matches!(new_ratio, Ok(new_ratio) if new_ratio >= self.limit)
+        }
+
+        fn mint_reservecoin_ok(&self, n: u64) -> bool {
+            let new_ratio = self
+                .fee_schedule()
+                .base_mint_cost(self.price, n)
+                .and_then(|cost| checked_add(self.reserves, cost, ""))
+                .and_then(|reserves| checked_reserve_ratio(reserves, self.stablecoins, self.rate));
+            matches!(new_ratio, Ok(new_ratio) if new_ratio <= self.limit)
+        }
+
+        fn redeem_reservecoin_ok(&self, n: u64) -> bool {
+            let new_ratio = self
+                .fee_schedule()
+                .base_redeem_amount(self.price, n)
+                .and_then(|amount| {
+                    let reserves = self.reserves.saturating_sub(amount);
+                    checked_reserve_ratio(reserves, self.stablecoins, self.rate)
+                });
+            matches!(new_ratio, Ok(new_ratio) if new_ratio >= self.limit)
+        }
+    }
+
+    // The reserve ratio checks of the Bank contract (`validReserveRatio`),
+    // with the reserves changed by `brDeltaExpectedWithFee` using `Long` math
+
+    impl State {
+        /// The reserves after exchanging `n` coins, `None` if a `Long`
+        /// overflows or the reserves would turn negative
+        fn contract_reserves_out(&self, n: i128) -> Option<u64> {
+            let long = |v: i128| match v >= i64::MIN as i128 && v <= i64::MAX as i128 {
+                true => Some(v),
+                false => None,
+            };
+            let br_delta_expected = long(self.price as i128 * n)?;
+            // `Long` division truncates towards 0
+            let fee = long(br_delta_expected * self.fee_percent as i128)? / 100;
+            let reserves_out = long(self.reserves as i128 + long(br_delta_expected + fee.abs())?)?;
+            u64::try_from(reserves_out).ok()
+        }
+
+        fn contract_mint_stablecoin_ok(&self, n: u64) -> bool {
+            let new_ratio = self.contract_reserves_out(n as i128).and_then(|reserves| {
+                contract_reserve_ratio(reserves, self.stablecoins + n, self.rate).ok()
+            });
+            matches!(new_ratio, Some(Some(new_ratio)) if new_ratio >= self.limit)
+        }
+
+        fn contract_mint_reservecoin_ok(&self, n: u64) -> bool {
+            let new_ratio = self.contract_reserves_out(n as i128).and_then(|reserves| {
+                contract_reserve_ratio(reserves, self.stablecoins, self.rate).ok()
+            });
+            match new_ratio {
+                Some(Some(new_ratio)) => new_ratio <= self.limit,
+                // No reserves are needed, so the max reserve ratio is used
+                Some(None) => true,
+                None => false,
+            }
+        }
+
+        fn contract_redeem_reservecoin_ok(&self, n: u64) -> bool {
+            let new_ratio = self
+                .contract_reserves_out(-(n as i128))
+                .and_then(|reserves| {
+                    contract_reserve_ratio(reserves, self.stablecoins, self.rate).ok()
+                });
+            match new_ratio {
+                Some(Some(new_ratio)) => new_ratio >= self.limit,
+                // No reserves are needed, so the max reserve ratio is used
+                Some(None) => true,
+                None => false,
+            }
+        }
+    }
+
+    /// Every combination of small protocol states, with the nominal price
+    /// never above the oracle rate (as is the case for StableCoins)
+    fn states(limits: &[u64]) -> Vec<State> {
+        let mut states = vec![];
+        for reserves in (0..=400).step_by(4) {
+            for stablecoins in 0..=6 {
+                for rate in 1..=6 {
+                    for price in 1..=rate {
+                        for fee_percent in [0, 2, 5].iter().copied() {
+                            for limit in limits.iter().copied() {
+                                states.push(State {
+                                    reserves,
+                                    stablecoins,
+                                    rate,
+                                    price,
+                                    fee_percent,
+                                    limit,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        states
+    }
+
+    /// Checks the amount settled on by `predicate` (the `BankBox` check)
+    /// against the Bank contract check `contract` and a brute force search
+    /// of `0..=CAP`.
+    ///
+    /// The rounded down fee can make the reserve ratio after minting
+    /// StableCoins step back over the limit, so that `predicate` is not
+    /// monotone. The search then only guarantees an amount which
+    /// satisfies `predicate` while 1 more does not, which may be below the
+    /// largest satisfying amount.
+    fn check<F: Fn(u64) -> bool, G: Fn(u64) -> bool>(
+        state: &State,
+        estimate: Option<u64>,
+        predicate: F,
+        contract: G,
+    ) {
+        let satisfies: Vec<bool> = (0..=CAP).map(|n| n == 0 || predicate(n)).collect();
+        let settled = settle_maximum(estimate, CAP, &predicate);
+        assert!(
+            settled <= CAP && satisfies[settled as usize],
+            "{:?} settled on {} which fails",
+            state,
+            settled
+        );
+        // The settled amount is accepted by the Bank contract
+        assert!(
+            settled == 0 || contract(settled),
+            "{:?} settled on {} which the contract rejects",
+            state,
+            settled
+        );
+        // 1 more is rejected by the Bank contract. Without circulating
+        // StableCoins the contract does not bound the reserve ratio, but
+        // `BankBox` treats them as 1 and is stricter.
+        if settled < CAP && state.stablecoins > 0 {
+            assert!(
+                !contract(settled + 1),
+                "{:?} settled on {} but the contract accepts 1 more",
+                state,
+                settled
+            );
+        }
+        let monotone = satisfies.windows(2).all(|w| w[0] || !w[1]);
+        if monotone {
+            let brute_force = satisfies.iter().rposition(|ok| *ok).unwrap() as u64;
+            assert_eq!(settled, brute_force, "{:?}", state);
+            // Without the rounded down fee the estimate is off by at most 1
+            if let (Some(estimate), 0) = (estimate, state.fee_percent) {
+                let estimate = std::cmp::min(estimate, CAP);
+                assert!(
+                    estimate + 1 >= brute_force && estimate <= brute_force + 1,
+                    "{:?} estimated {} instead of {}",
+                    state,
+                    estimate,
+                    brute_force
+                );
+            }
+        } else {
+            assert!(
+                state.fee_percent > 0,
+                "{:?} is not monotone without a fee",
+                state
+            );
+            assert!(
+                settled == CAP || !satisfies[settled as usize + 1],
+                "{:?} settled on {} which is not a local maximum",
+                state,
+                settled
+            );
+        }
+    }
+
+    #[test]
+    fn stablecoin_mint_maximum_matches_brute_force() {
+        for state in states(&[0, 100, 150, 400]) {
+            let estimate = estimate_max_stablecoin_mint(
+                state.reserves,
+                state.stablecoins,
+                state.rate,
+                state.price,
+                state.fee_percent,
+                state.limit,
+            );
+            check(
+                &state,
+                estimate,
+                |n| state.mint_stablecoin_ok(n),
+                |n| state.contract_mint_stablecoin_ok(n),
+            );
+        }
+    }
+
+    #[test]
+    fn reservecoin_mint_maximum_matches_brute_force() {
+        for state in states(&[0, 400, 800, 2000]) {
+            let estimate = estimate_max_reservecoin_mint(
+                state.reserves,
+                state.stablecoins,
+                state.rate,
+                state.price,
+                state.fee_percent,
+                state.limit,
+            );
+            check(
+                &state,
+                estimate,
+                |n| state.mint_reservecoin_ok(n),
+                |n| state.contract_mint_reservecoin_ok(n),
+            );
+        }
+    }
+
+    #[test]
+    fn reservecoin_redeem_maximum_matches_brute_force() {
+        for state in states(&[100, 400]) {
+            let estimate = estimate_max_reservecoin_redeem(
+                state.reserves,
+                state.stablecoins,
+                state.rate,
+                state.price,
+                state.fee_percent,
+                state.limit,
+            );
+            check(
+                &state,
+                estimate,
+                |n| state.redeem_reservecoin_ok(n),
+                |n| state.contract_redeem_reservecoin_ok(n),
+            );
+        }
+    }
+
+    #[test]
+    fn reserve_ratio_precisions_agree() {
+        for reserves in (0..=2000).step_by(7) {
+            for stablecoins in 0..=6 {
+                for rate in 0..=6 {
+                    let percent = ratio(reserves, stablecoins, rate);
+                    let bps = checked_reserve_ratio_bps(reserves, stablecoins, rate).unwrap();
+                    assert_eq!(bps / BPS_PER_PERCENT, percent);
+                    let contract = contract_reserve_ratio(reserves, stablecoins, rate).unwrap();
+                    if stablecoins == 0 || rate == 0 {
+                        assert_eq!(contract, None);
+                    } else {
+                        assert_eq!(contract, Some(percent));
+                    }
+                }
+            }
+        }
+        // 400.9% rounds down to 400% but is kept in basis points
+        assert_eq!(reserve_ratio(4009, 10, 100), 400);
+        assert_eq!(reserve_ratio_bps(4009, 10, 100), 40090);
+    }
+
+    #[test]
+    fn unbounded_estimates_fall_back_to_searching() {
+        let always = |_: u64| true;
+        let below_ten = |n: u64| n < 10;
+        assert_eq!(settle_maximum(None, CAP, always), CAP);
+        assert_eq!(settle_maximum(None, CAP, below_ten), 9);
+        // An estimate which is far off still settles on the maximum
+        assert_eq!(settle_maximum(Some(200), CAP, below_ten), 9);
+        assert_eq!(settle_maximum(Some(u64::MAX), CAP, always), CAP);
+        assert_eq!(settle_maximum(Some(5), CAP, |_: u64| false), 0);
+    }
+
+    #[test]
+    fn estimates_do_not_overflow_at_the_limits() {
+        let max = u64::MAX;
+        // Bounds which do not fit within an `i128` are left to the search
+        assert_eq!(
+            estimate_max_stablecoin_mint(max, max, max, max, max, max),
+            None
+        );
+        assert_eq!(
+            estimate_max_reservecoin_mint(max, max, max, max, max, max),
+            None
+        );
+        assert_eq!(
+            estimate_max_reservecoin_redeem(max, max, max, max, max, max),
+            None
+        );
+        assert_eq!(
+            estimate_max_stablecoin_mint(max, 1, max, 1, 2, 400),
+            Some(0)
+        );
+        assert_eq!(
+            estimate_max_reservecoin_mint(max, 1, 1, max, 2, 800),
+            Some(0)
+        );
+        assert_eq!(
+            estimate_max_reservecoin_redeem(0, 1, max, 1, 2, 400),
+            Some(0)
+        );
+    }
+
+    /// `u128` reference of a product evaluated with `Long` semantics
+    fn reference_long(value: u128) -> Option<u64> {
+        match value <= i64::MAX as u128 {
+            true => Some(value as u64),
+            false => None,
+        }
+    }
+
+    /// `u128` reference of a value which must fit within a `u64`
+    fn reference_u64(value: u128) -> Option<u64> {
+        match value <= u64::MAX as u128 {
+            true => Some(value as u64),
+            false => None,
+        }
+    }
+
+    fn reference_reserve_ratio(
+        reserves: u64,
+        stablecoins: u64,
+        rate: u64,
+        scale: u128,
+    ) -> Option<u64> {
+        if reserves == 0 || rate == 0 {
+            return Some(0);
+        }
+        let needed = std::cmp::max(stablecoins, 1) as u128 * rate as u128;
+        reference_u64(reserves as u128 * scale / needed)
+    }
+
+    fn reference_contract_reserve_ratio(
+        reserves: u64,
+        stablecoins: u64,
+        rate: u64,
+    ) -> Option<Option<u64>> {
+        let needed = reference_long(stablecoins as u128 * rate as u128)?;
+        if needed == 0 {
+            return Some(None);
+        }
+        let scaled = reference_long(reserves as u128 * 100)?;
+        Some(Some(scaled / needed))
+    }
+
+    #[test]
+    fn checked_arithmetic_matches_the_reference() {
+        let values = sample_values();
+        for a in values.iter().copied() {
+            for b in values.iter().copied() {
+                let (wide_a, wide_b) = (a as u128, b as u128);
+                assert_eq!(checked_add(a, b, "").ok(), reference_u64(wide_a + wide_b));
+                assert_eq!(checked_sub(a, b, "").ok(), a.checked_sub(b));
+                assert_eq!(long_mul(a, b, "").ok(), reference_long(wide_a * wide_b));
+                assert_eq!(long_add(a, b, "").ok(), reference_long(wide_a + wide_b));
+                assert_eq!(
+                    to_long(a, "").ok(),
+                    reference_long(wide_a).map(|v| v as i64)
+                );
+                for c in values.iter().copied().filter(|c| *c != 0).step_by(5) {
+                    assert_eq!(
+                        mul_div(a, b, c, "").ok(),
+                        reference_u64(wide_a * wide_b / c as u128),
+                        "{} * {} / {}",
+                        a,
+                        b,
+                        c
+                    );
+                }
+                assert!(mul_div(a, b, 0, "").is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn checked_reserve_ratios_match_the_reference() {
+        let values = sample_values();
+        for reserves in values.iter().copied() {
+            for stablecoins in values.iter().copied() {
+                for rate in values.iter().copied() {
+                    let inputs = (reserves, stablecoins, rate);
+                    assert_eq!(
+                        checked_reserve_ratio(reserves, stablecoins, rate).ok(),
+                        reference_reserve_ratio(reserves, stablecoins, rate, 100),
+                        "{:?}",
+                        inputs
+                    );
+                    assert_eq!(
+                        checked_reserve_ratio_bps(reserves, stablecoins, rate).ok(),
+                        reference_reserve_ratio(reserves, stablecoins, rate, 100 * 100),
+                        "{:?}",
+                        inputs
+                    );
+                    assert_eq!(
+                        contract_reserve_ratio(reserves, stablecoins, rate).ok(),
+                        reference_contract_reserve_ratio(reserves, stablecoins, rate),
+                        "{:?}",
+                        inputs
+                    );
+                }
+            }
+        }
+        // The ratio overflows a `u64` exactly past `u64::MAX / 100` reserves
+        assert_eq!(
+            checked_reserve_ratio(u64::MAX / 100, 1, 1).unwrap(),
+            u64::MAX / 100 * 100
+        );
+        assert!(checked_reserve_ratio(u64::MAX / 100 + 1, 1, 1).is_err());
+        assert!(checked_reserve_ratio_bps(u64::MAX / 10_000 + 1, 1, 1).is_err());
+        // The contract errors once `bcReserve * 100` overflows a `Long`
+        assert!(contract_reserve_ratio(i64::MAX as u64 / 100, 1, 1).is_ok());
+        assert!(contract_reserve_ratio(i64::MAX as u64 / 100 + 1, 1, 1).is_err());
     }
-    let per_stablecoin_rate = (base_reserves * 100) / circulating_stablecoins;
-    per_stablecoin_rate / oracle_rate
 }
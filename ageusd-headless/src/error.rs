@@ -1,3 +1,4 @@
+use crate::contract_model::BankCondition;
 use ergo_headless_dapp_framework::{encoding::EncodingError, HeadlessDappError};
 use ergo_headless_dapp_framework::{NanoErg, P2PKAddressString, P2SAddressString};
 use thiserror::Error;
@@ -40,6 +41,22 @@ pub enum ProtocolError {
     InvalidTokens(String),
     #[error("Invalid Registers: {0}")]
     InvalidRegisters(String),
+    #[error("Invalid Protocol Config: {0}")]
+    InvalidConfig(String),
+    #[error("An arithmetic overflow/underflow occurred while calculating the {0}.")]
+    ArithmeticOverflow(String),
+    #[error("The provided Quote no longer matches the current state of the protocol: {0}")]
+    StaleQuote(String),
+    #[error(
+        "The price moved beyond the accepted slippage. Expected: {expected}, Actual: {actual}"
+    )]
+    SlippageExceeded { expected: u64, actual: u64 },
+    #[error("The Bank contract would reject the transaction, failing `{0:?}`: {1}")]
+    BankContractViolation(BankCondition, String),
+    #[error("The update has {votes} votes, but {required} are required to collect them.")]
+    QuorumNotReached { votes: u64, required: u64 },
+    #[error("Invalid Proposal Registry: {0}")]
+    InvalidProposalRegistry(String),
     #[error("{0}")]
     Other(String),
     #[error(transparent)]
@@ -0,0 +1,398 @@
+// This file holds the `FeeSchedule`, which defines every fee charged when
+// minting/redeeming via the protocol. All fees are calculated with integer
+// arithmetic so that the quote shown to a user and the transaction built
+// for them always agree to the nanoErg.
+use crate::equations::{checked_add, checked_sub, long_add, long_mul, mul_div};
+use crate::error::{ProtocolError, Result};
+use crate::parameters::ProtocolConfig;
+use ergo_headless_dapp_framework::NanoErg;
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+/// The number of basis points which make up 100%
+pub const BASIS_POINTS: u64 = 10000;
+
+/// The fees charged on each minting/redeeming Action
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// The fee percentage charged by the Bank contract. 1 == 1%
+    pub protocol_fee_percent: u64,
+    /// The fee paid to the frontend implementor in basis points. 1 == 0.01%
+    pub implementor_fee_bps: u64,
+}
+
+/// WASM-supported methods related to `FeeSchedule`
+#[wasm_bindgen]
+impl FeeSchedule {
+    /// Create a new `FeeSchedule`
+    #[wasm_bindgen(constructor)]
+    pub fn new(protocol_fee_percent: u64, implementor_fee_bps: u64) -> FeeSchedule {
+        FeeSchedule {
+            protocol_fee_percent,
+            implementor_fee_bps,
+        }
+    }
+
+    /// The `FeeSchedule` of the provided `ProtocolConfig`
+    #[wasm_bindgen]
+    pub fn w_from_config(config: &ProtocolConfig) -> FeeSchedule {
+        FeeSchedule::from_config(config)
+    }
+
+    /// The protocol fee charged on top of a feeless amount
+    #[wasm_bindgen]
+    pub fn w_protocol_fee(&self, feeless_amount: NanoErg) -> std::result::Result<NanoErg, JsValue> {
+        self.protocol_fee(feeless_amount)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// The implementor fee charged on the base amount of an Action
+    #[wasm_bindgen]
+    pub fn w_implementor_fee(&self, base_amount: NanoErg) -> std::result::Result<NanoErg, JsValue> {
+        self.implementor_fee(base_amount)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+}
+
+/// Rust methods related to `FeeSchedule`
+impl FeeSchedule {
+    /// The `FeeSchedule` of the provided `ProtocolConfig`. Contract versions
+    /// which do not charge a protocol fee have a `protocol_fee_percent` of 0.
+    pub fn from_config(config: &ProtocolConfig) -> FeeSchedule {
+        let protocol_fee_percent = if config.contract_version.charges_protocol_fee() {
+            config.fee_percent
+        } else {
+            0
+        };
+        FeeSchedule::new(protocol_fee_percent, config.implementor_fee_bps)
+    }
+
+    /// The protocol fee charged on top of a feeless amount, calculated
+    /// with `Long` semantics as the Bank contract does.
+    pub fn protocol_fee(&self, feeless_amount: NanoErg) -> Result<NanoErg> {
+        Ok(long_mul(feeless_amount, self.protocol_fee_percent, "protocol fee")? / 100)
+    }
+
+    /// The implementor fee charged on the base amount of an Action.
+    /// Rounds down to the nearest nanoErg.
+    pub fn implementor_fee(&self, base_amount: NanoErg) -> Result<NanoErg> {
+        mul_div(
+            base_amount,
+            self.implementor_fee_bps,
+            BASIS_POINTS,
+            "implementor fee",
+        )
+    }
+
+    /// The nanoErgs added to the reserves when minting `amount` coins at
+    /// `nominal_price`, being the feeless cost plus the protocol fee.
+    /// Calculated with `Long` semantics as the Bank contract does.
+    pub fn base_mint_cost(&self, nominal_price: NanoErg, amount: u64) -> Result<NanoErg> {
+        let feeless_cost = long_mul(nominal_price, amount, "feeless cost")?;
+        long_add(feeless_cost, self.protocol_fee(feeless_cost)?, "base cost")
+    }
+
+    /// The nanoErgs removed from the reserves when redeeming `amount` coins
+    /// at `nominal_price`, being the feeless amount minus the protocol fee.
+    /// Calculated with `Long` semantics as the Bank contract does.
+    pub fn base_redeem_amount(&self, nominal_price: NanoErg, amount: u64) -> Result<NanoErg> {
+        let feeless_amount = long_mul(nominal_price, amount, "feeless amount")?;
+        checked_sub(
+            feeless_amount,
+            self.protocol_fee(feeless_amount)?,
+            "base amount",
+        )
+    }
+
+    /// The total cost of a minting Action, covering the base cost, the tx fee,
+    /// the implementor fee and the min box value of the Receipt and change boxes.
+    pub fn total_mint_cost(
+        &self,
+        base_cost: NanoErg,
+        transaction_fee: NanoErg,
+        min_box_value: NanoErg,
+    ) -> Result<NanoErg> {
+        let total = base_cost as u128
+            + transaction_fee as u128
+            + min_box_value as u128 * 2
+            + self.implementor_fee(base_cost)? as u128;
+        u64::try_from(total)
+            .map_err(|_| ProtocolError::ArithmeticOverflow("total cost".to_string()))
+    }
+
+    /// The sum of the protocol, tx and implementor fees of an Action
+    pub fn total_fees(
+        &self,
+        feeless_amount: NanoErg,
+        base_amount: NanoErg,
+        transaction_fee: NanoErg,
+    ) -> Result<NanoErg> {
+        let total = self.protocol_fee(feeless_amount)? as u128
+            + transaction_fee as u128
+            + self.implementor_fee(base_amount)? as u128;
+        u64::try_from(total)
+            .map_err(|_| ProtocolError::ArithmeticOverflow("total fees".to_string()))
+    }
+
+    /// The amount received from a redeeming Action after paying the tx fee
+    /// and implementor fee. Errors if the fees exceed the base amount.
+    pub fn total_redeem_amount(
+        &self,
+        base_amount: NanoErg,
+        transaction_fee: NanoErg,
+    ) -> Result<NanoErg> {
+        let fees = checked_add(
+            transaction_fee,
+            self.implementor_fee(base_amount)?,
+            "total redeem fees",
+        )?;
+        checked_sub(base_amount, fees, "total redeem amount")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2% protocol fee and a 0.25% implementor fee
+    fn fees() -> FeeSchedule {
+        FeeSchedule::new(2, 25)
+    }
+
+    #[test]
+    fn protocol_fee_rounds_down() {
+        assert_eq!(fees().protocol_fee(0).unwrap(), 0);
+        assert_eq!(fees().protocol_fee(49).unwrap(), 0);
+        assert_eq!(fees().protocol_fee(50).unwrap(), 1);
+        assert_eq!(fees().protocol_fee(99).unwrap(), 1);
+        assert_eq!(fees().protocol_fee(100).unwrap(), 2);
+        assert_eq!(FeeSchedule::new(0, 25).protocol_fee(99).unwrap(), 0);
+    }
+
+    #[test]
+    fn protocol_fee_overflows_a_long() {
+        // The product must fit within a `Long`, as in the Bank contract
+        let max_feeless = i64::MAX as u64 / 2;
+        assert_eq!(fees().protocol_fee(max_feeless).unwrap(), 92233720368547758);
+        assert!(fees().protocol_fee(max_feeless + 1).is_err());
+        assert!(fees().protocol_fee(u64::MAX).is_err());
+        assert_eq!(FeeSchedule::new(0, 25).protocol_fee(u64::MAX).unwrap(), 0);
+    }
+
+    #[test]
+    fn implementor_fee_rounds_down() {
+        assert_eq!(fees().implementor_fee(0).unwrap(), 0);
+        assert_eq!(fees().implementor_fee(399).unwrap(), 0);
+        assert_eq!(fees().implementor_fee(400).unwrap(), 1);
+        assert_eq!(fees().implementor_fee(799).unwrap(), 1);
+        assert_eq!(fees().implementor_fee(800).unwrap(), 2);
+        assert_eq!(FeeSchedule::new(2, 0).implementor_fee(799).unwrap(), 0);
+    }
+
+    #[test]
+    fn implementor_fee_at_the_limits() {
+        assert_eq!(fees().implementor_fee(u64::MAX).unwrap(), 46116860184273879);
+        let full = FeeSchedule::new(2, BASIS_POINTS);
+        assert_eq!(full.implementor_fee(u64::MAX).unwrap(), u64::MAX);
+        let double = FeeSchedule::new(2, 2 * BASIS_POINTS);
+        assert_eq!(double.implementor_fee(u64::MAX / 2).unwrap(), u64::MAX - 1);
+        assert!(double.implementor_fee(u64::MAX / 2 + 1).is_err());
+    }
+
+    #[test]
+    fn total_mint_cost() {
+        // 1000 + 2 + 2 * 10 + floor(1000 * 0.25%)
+        assert_eq!(fees().total_mint_cost(1000, 2, 10).unwrap(), 1024);
+        assert_eq!(fees().total_mint_cost(399, 0, 0).unwrap(), 399);
+        assert_eq!(fees().total_mint_cost(400, 0, 0).unwrap(), 401);
+
+        let feeless = FeeSchedule::new(0, 0);
+        assert_eq!(feeless.total_mint_cost(u64::MAX, 0, 0).unwrap(), u64::MAX);
+        assert!(feeless.total_mint_cost(u64::MAX, 1, 0).is_err());
+        assert!(feeless.total_mint_cost(u64::MAX - 1, 0, 1).is_err());
+        assert!(fees().total_mint_cost(u64::MAX, 0, 0).is_err());
+        assert!(feeless.total_mint_cost(0, u64::MAX, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn total_redeem_amount() {
+        // 1000 - 2 - floor(1000 * 0.25%)
+        assert_eq!(fees().total_redeem_amount(1000, 2).unwrap(), 996);
+        assert_eq!(fees().total_redeem_amount(399, 0).unwrap(), 399);
+        assert_eq!(fees().total_redeem_amount(400, 0).unwrap(), 399);
+        // The fees matching the base amount leaves nothing to receive
+        assert_eq!(fees().total_redeem_amount(400, 399).unwrap(), 0);
+        assert_eq!(fees().total_redeem_amount(5, 5).unwrap(), 0);
+        // The fees exceeding the base amount can not be paid
+        assert!(fees().total_redeem_amount(400, 400).is_err());
+        assert!(fees().total_redeem_amount(3, 5).is_err());
+
+        let feeless = FeeSchedule::new(0, 0);
+        assert_eq!(feeless.total_redeem_amount(u64::MAX, 0).unwrap(), u64::MAX);
+        assert_eq!(feeless.total_redeem_amount(u64::MAX, u64::MAX).unwrap(), 0);
+        assert!(feeless.total_redeem_amount(u64::MAX - 1, u64::MAX).is_err());
+        assert_eq!(
+            fees().total_redeem_amount(u64::MAX, 0).unwrap(),
+            u64::MAX - 46116860184273879
+        );
+        assert!(fees().total_redeem_amount(u64::MAX, u64::MAX).is_err());
+    }
+
+    /// `u128` reference model of the `FeeSchedule`, `None` on overflow
+    struct Reference(FeeSchedule);
+
+    impl Reference {
+        fn long(value: u128) -> Option<u128> {
+            Some(value).filter(|v| *v <= i64::MAX as u128)
+        }
+
+        fn word(value: u128) -> Option<u64> {
+            Some(value)
+                .filter(|v| *v <= u64::MAX as u128)
+                .map(|v| v as u64)
+        }
+
+        fn protocol_fee(&self, feeless: u64) -> Option<u128> {
+            Some(Self::long(feeless as u128 * self.0.protocol_fee_percent as u128)? / 100)
+        }
+
+        fn implementor_fee(&self, base: u64) -> Option<u128> {
+            let fee = base as u128 * self.0.implementor_fee_bps as u128 / BASIS_POINTS as u128;
+            Self::word(fee).map(u128::from)
+        }
+
+        fn base_mint_cost(&self, price: u64, amount: u64) -> Option<u64> {
+            let feeless = Self::long(price as u128 * amount as u128)?;
+            let cost = Self::long(feeless + self.protocol_fee(feeless as u64)?)?;
+            Some(cost as u64)
+        }
+
+        fn base_redeem_amount(&self, price: u64, amount: u64) -> Option<u64> {
+            let feeless = Self::long(price as u128 * amount as u128)?;
+            Self::word(feeless.checked_sub(self.protocol_fee(feeless as u64)?)?)
+        }
+
+        fn total_mint_cost(&self, base: u64, tx_fee: u64, min_box_value: u64) -> Option<u64> {
+            let fee = self.implementor_fee(base)?;
+            Self::word(base as u128 + tx_fee as u128 + 2 * min_box_value as u128 + fee)
+        }
+
+        fn total_fees(&self, feeless: u64, base: u64, tx_fee: u64) -> Option<u64> {
+            let fees = self.protocol_fee(feeless)? + self.implementor_fee(base)?;
+            Self::word(fees + tx_fee as u128)
+        }
+
+        fn total_redeem_amount(&self, base: u64, tx_fee: u64) -> Option<u64> {
+            let fees = Self::word(tx_fee as u128 + self.implementor_fee(base)?)?;
+            base.checked_sub(fees)
+        }
+    }
+
+    fn schedules() -> Vec<FeeSchedule> {
+        let mut schedules = vec![];
+        for percent in [0, 1, 2, 100, 101, u64::MAX].iter().copied() {
+            for bps in [0, 1, 25, BASIS_POINTS, 3 * BASIS_POINTS, u64::MAX]
+                .iter()
+                .copied()
+            {
+                schedules.push(FeeSchedule::new(percent, bps));
+            }
+        }
+        schedules
+    }
+
+    #[test]
+    fn checked_pricing_matches_the_reference() {
+        let values = crate::equations::sample_values();
+        for fees in schedules() {
+            let reference = Reference(fees);
+            for a in values.iter().copied() {
+                assert_eq!(
+                    fees.protocol_fee(a).ok(),
+                    reference.protocol_fee(a).map(|f| f as u64),
+                    "{:?} {}",
+                    fees,
+                    a
+                );
+                assert_eq!(
+                    fees.implementor_fee(a).ok(),
+                    reference.implementor_fee(a).map(|f| f as u64),
+                    "{:?} {}",
+                    fees,
+                    a
+                );
+                for b in values.iter().copied() {
+                    let inputs = (fees, a, b);
+                    assert_eq!(
+                        fees.base_mint_cost(a, b).ok(),
+                        reference.base_mint_cost(a, b),
+                        "{:?}",
+                        inputs
+                    );
+                    assert_eq!(
+                        fees.base_redeem_amount(a, b).ok(),
+                        reference.base_redeem_amount(a, b),
+                        "{:?}",
+                        inputs
+                    );
+                    assert_eq!(
+                        fees.total_redeem_amount(a, b).ok(),
+                        reference.total_redeem_amount(a, b),
+                        "{:?}",
+                        inputs
+                    );
+                    for c in values.iter().copied().step_by(4) {
+                        let inputs = (fees, a, b, c);
+                        assert_eq!(
+                            fees.total_mint_cost(a, b, c).ok(),
+                            reference.total_mint_cost(a, b, c),
+                            "{:?}",
+                            inputs
+                        );
+                        assert_eq!(
+                            fees.total_fees(a, b, c).ok(),
+                            reference.total_fees(a, b, c),
+                            "{:?}",
+                            inputs
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn base_amounts_at_the_long_boundary() {
+        // `feeless * 2` must fit within a `Long` to calculate the 2% fee
+        let max_feeless = i64::MAX as u64 / 2;
+        assert_eq!(
+            fees().base_mint_cost(max_feeless, 1).unwrap(),
+            max_feeless + 92233720368547758
+        );
+        assert!(fees().base_mint_cost(max_feeless + 1, 1).is_err());
+        assert_eq!(
+            fees().base_redeem_amount(max_feeless, 1).unwrap(),
+            max_feeless - 92233720368547758
+        );
+        assert!(fees().base_redeem_amount(max_feeless + 1, 1).is_err());
+        // The cost itself must also fit within a `Long`
+        let feeless = FeeSchedule::new(0, 0);
+        assert_eq!(
+            feeless.base_mint_cost(i64::MAX as u64, 1).unwrap(),
+            i64::MAX as u64
+        );
+        assert!(feeless.base_mint_cost(i64::MAX as u64, 2).is_err());
+        assert!(FeeSchedule::new(100, 0)
+            .base_mint_cost(i64::MAX as u64 / 100, 1)
+            .is_ok());
+        assert!(FeeSchedule::new(100, 0)
+            .base_mint_cost(i64::MAX as u64 / 2 + 1, 1)
+            .is_err());
+        // 1000 * 3 + floor(3000 * 2%) and 3000 - floor(3000 * 2%)
+        assert_eq!(fees().base_mint_cost(1000, 3).unwrap(), 3060);
+        assert_eq!(fees().base_redeem_amount(1000, 3).unwrap(), 2940);
+        // A fee above 100% cannot be paid out of the feeless amount
+        assert!(FeeSchedule::new(101, 0).base_redeem_amount(100, 1).is_err());
+    }
+}
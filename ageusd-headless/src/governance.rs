@@ -0,0 +1,630 @@
+// This file holds the `GovernanceHistory`, an audit trail of the Update box
+// reconstructed from the transactions which spent/created it. Each time the
+// Update box moved either the votes for a new address hash were collected
+// (`voteSuccessPath`, with the ballots as data-inputs) or the Bank box was
+// moved to the collected contract (`updatePath`, with no data-inputs).
+// Transactions are read from their JSON as served by either an Ergo Node or
+// the Ergo Explorer API. Node transactions only reference data-inputs by
+// box id, so ballots are resolved from boxes seen elsewhere in the history.
+use crate::contract_diff::{decode_constant_hex, TreeConstant};
+use crate::error::{ProtocolError, Result};
+use crate::parameters::ProtocolConfig;
+use ergo_headless_dapp_framework::BlockHeight;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// What happened to the Update box in a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GovernanceEventKind {
+    /// The Update box first appears in the history (ie. at genesis)
+    UpdateBoxCreated,
+    /// The votes for a new address hash were collected into R4
+    VotesCollected,
+    /// The Bank box was moved to the contract collected in R4
+    ProtocolUpdated,
+}
+
+impl GovernanceEventKind {
+    /// A human readable name of the event kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            GovernanceEventKind::UpdateBoxCreated => "Update Box Created",
+            GovernanceEventKind::VotesCollected => "Votes Collected",
+            GovernanceEventKind::ProtocolUpdated => "Protocol Updated",
+        }
+    }
+}
+
+/// A ballot used as a data-input when collecting votes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BallotRecord {
+    /// The box id of the Ballot box
+    pub box_id: String,
+    /// The voting power (ballot tokens) of the Ballot box
+    pub voting_power: u64,
+    /// The address hash the ballot voted for
+    pub address_hash_voted_for: String,
+    /// Whether the Update contract counted the ballot, meaning it voted
+    /// for the collected address hash with the spent Update box id
+    pub counted: bool,
+}
+
+/// A single move of the Update box
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GovernanceEvent {
+    /// The id of the transaction which moved the Update box
+    pub tx_id: String,
+    /// The height the transaction was included at, if known
+    pub height: Option<BlockHeight>,
+    /// The timestamp (ms) of the block the transaction was included in, if known
+    pub timestamp: Option<u64>,
+    pub kind: GovernanceEventKind,
+    /// The box id of the Update box created by the transaction
+    pub update_box_id: String,
+    /// The box id of the Update box spent by the transaction, if known
+    pub previous_update_box_id: Option<String>,
+    /// The address hash held in R4 of the created Update box
+    pub address_hash: String,
+    /// The ballots used as data-inputs which could be resolved
+    pub ballots: Vec<BallotRecord>,
+    /// The box ids of the data-inputs which could not be resolved
+    pub unresolved_data_inputs: Vec<String>,
+    /// The voting power of the counted ballots
+    pub voting_power: u64,
+    /// The box id of the Bank box created by a `ProtocolUpdated` event
+    pub bank_box_id: Option<String>,
+}
+
+/// The timeline of every move of the Update box, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GovernanceHistory {
+    pub events: Vec<GovernanceEvent>,
+}
+
+impl GovernanceHistory {
+    /// The events in which the Bank contract was swapped
+    pub fn protocol_updates(&self) -> Vec<&GovernanceEvent> {
+        self.events
+            .iter()
+            .filter(|e| e.kind == GovernanceEventKind::ProtocolUpdated)
+            .collect()
+    }
+
+    /// Serialize the `GovernanceHistory` into a JSON string
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| ProtocolError::Other(e.to_string()))
+    }
+}
+
+/// A box as held within the JSON of a transaction
+#[derive(Debug, Clone)]
+struct JsonBox {
+    box_id: String,
+    tokens: Vec<(String, u64)>,
+    registers: HashMap<String, String>,
+    /// Whether the JSON held the full box, rather than just its id
+    is_full: bool,
+}
+
+impl JsonBox {
+    fn parse(json: &Value) -> Option<JsonBox> {
+        let box_id = json.get("boxId")?.as_str()?.to_string();
+        let tokens = json
+            .get("assets")
+            .and_then(|a| a.as_array())
+            .map(|assets| {
+                assets
+                    .iter()
+                    .filter_map(|a| {
+                        Some((
+                            a.get("tokenId")?.as_str()?.to_string(),
+                            json_u64(a.get("amount")?)?,
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        // The node serves registers as serialized hex, the explorer as
+        // objects holding the serialized hex
+        let registers = json
+            .get("additionalRegisters")
+            .and_then(|r| r.as_object())
+            .map(|registers| {
+                registers
+                    .iter()
+                    .filter_map(|(id, v)| {
+                        let hex = v.as_str().or_else(|| v.get("serializedValue")?.as_str())?;
+                        Some((id.clone(), hex.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(JsonBox {
+            box_id,
+            tokens,
+            registers,
+            is_full: json.get("assets").is_some(),
+        })
+    }
+
+    /// The amount of the token held in the box
+    fn token_amount(&self, token_id: &str) -> u64 {
+        self.tokens
+            .iter()
+            .filter(|(id, _)| id == token_id)
+            .fold(0, |acc, (_, amount)| acc.saturating_add(*amount))
+    }
+
+    /// The hex of the `Coll[Byte]` held in the register
+    fn bytes_register(&self, register_id: &str) -> Option<String> {
+        match decode_constant_hex(self.registers.get(register_id)?)? {
+            TreeConstant::Bytes(hex) => Some(hex),
+            _ => None,
+        }
+    }
+}
+
+/// A transaction as held within its JSON
+#[derive(Debug, Clone)]
+struct JsonTransaction {
+    id: String,
+    height: Option<BlockHeight>,
+    timestamp: Option<u64>,
+    inputs: Vec<JsonBox>,
+    data_inputs: Vec<JsonBox>,
+    outputs: Vec<JsonBox>,
+}
+
+impl JsonTransaction {
+    fn parse(json: &Value) -> Result<JsonTransaction> {
+        let id = json
+            .get("id")
+            .and_then(|id| id.as_str())
+            .ok_or_else(|| invalid("A transaction is missing its `id`."))?
+            .to_string();
+        let boxes = |field: &str| -> Vec<JsonBox> {
+            json.get(field)
+                .and_then(|b| b.as_array())
+                .map(|boxes| boxes.iter().filter_map(JsonBox::parse).collect())
+                .unwrap_or_default()
+        };
+        Ok(JsonTransaction {
+            id,
+            height: json.get("inclusionHeight").and_then(json_u64),
+            timestamp: json.get("timestamp").and_then(json_u64),
+            inputs: boxes("inputs"),
+            data_inputs: boxes("dataInputs"),
+            outputs: boxes("outputs"),
+        })
+    }
+}
+
+/// Builds a `GovernanceHistory` from a sequence of transactions
+#[derive(Debug, Clone)]
+pub struct GovernanceHistoryBuilder {
+    config: ProtocolConfig,
+    transactions: Vec<JsonTransaction>,
+    /// Full boxes seen anywhere, used to resolve data-inputs by box id
+    known_boxes: HashMap<String, JsonBox>,
+}
+
+impl GovernanceHistoryBuilder {
+    /// Create a new `GovernanceHistoryBuilder` which identifies the
+    /// Update/Bank/Ballot boxes via the token IDs of `config`
+    pub fn new(config: &ProtocolConfig) -> GovernanceHistoryBuilder {
+        GovernanceHistoryBuilder {
+            config: config.clone(),
+            transactions: vec![],
+            known_boxes: HashMap::new(),
+        }
+    }
+
+    /// Add a transaction from its JSON
+    pub fn add_transaction_json(&mut self, json: &str) -> Result<&mut GovernanceHistoryBuilder> {
+        let value: Value = serde_json::from_str(json).map_err(|e| invalid(&e.to_string()))?;
+        self.add_transaction_value(&value)?;
+        Ok(self)
+    }
+
+    /// Add several transactions from a JSON array. An object holding the
+    /// array in `items` (as served by the explorer) is accepted too.
+    pub fn add_transactions_json(&mut self, json: &str) -> Result<&mut GovernanceHistoryBuilder> {
+        let value: Value = serde_json::from_str(json).map_err(|e| invalid(&e.to_string()))?;
+        let transactions = value
+            .as_array()
+            .or_else(|| value.get("items")?.as_array())
+            .ok_or_else(|| invalid("Expected an array of transactions."))?;
+        for tx in transactions {
+            self.add_transaction_value(tx)?;
+        }
+        Ok(self)
+    }
+
+    /// Add a box from its JSON so that data-inputs which reference it by
+    /// box id can be resolved
+    pub fn add_box_json(&mut self, json: &str) -> Result<&mut GovernanceHistoryBuilder> {
+        let value: Value = serde_json::from_str(json).map_err(|e| invalid(&e.to_string()))?;
+        let json_box =
+            JsonBox::parse(&value).ok_or_else(|| invalid("A box is missing its `boxId`."))?;
+        self.known_boxes.insert(json_box.box_id.clone(), json_box);
+        Ok(self)
+    }
+
+    fn add_transaction_value(&mut self, value: &Value) -> Result<()> {
+        let tx = JsonTransaction::parse(value)?;
+        for b in tx.inputs.iter().chain(&tx.data_inputs).chain(&tx.outputs) {
+            if b.is_full {
+                self.known_boxes.insert(b.box_id.clone(), b.clone());
+            }
+        }
+        // The same transaction may be provided more than once
+        if !self.transactions.iter().any(|t| t.id == tx.id) {
+            self.transactions.push(tx);
+        }
+        Ok(())
+    }
+
+    /// Build the `GovernanceHistory`. Transactions with a known inclusion
+    /// height are ordered by it among themselves, while those without keep
+    /// the position they were added at. The first Update box whose input
+    /// can't be resolved (ie. the spent box wasn't seen) is treated as
+    /// created.
+    pub fn build(&self) -> GovernanceHistory {
+        let mut transactions = self.transactions.clone();
+        let known_slots: Vec<usize> = (0..transactions.len())
+            .filter(|i| transactions[*i].height.is_some())
+            .collect();
+        let mut known: Vec<JsonTransaction> = known_slots
+            .iter()
+            .map(|i| transactions[*i].clone())
+            .collect();
+        known.sort_by_key(|tx| tx.height);
+        for (slot, tx) in known_slots.into_iter().zip(known) {
+            transactions[slot] = tx;
+        }
+
+        let update_nft_id = &self.config.update_nft_id;
+        let mut events: Vec<GovernanceEvent> = vec![];
+        for tx in &transactions {
+            let update_box = match tx
+                .outputs
+                .iter()
+                .find(|b| b.token_amount(update_nft_id) > 0)
+            {
+                Some(update_box) => update_box,
+                None => continue,
+            };
+            let previous_update_box_id = tx
+                .inputs
+                .iter()
+                .map(|b| self.known_boxes.get(&b.box_id).unwrap_or(b))
+                .find(|b| b.token_amount(update_nft_id) > 0)
+                .map(|b| b.box_id.clone());
+            let address_hash = update_box.bytes_register("R4").unwrap_or_default();
+            let bank_box = tx
+                .outputs
+                .iter()
+                .find(|b| b.token_amount(&self.config.bank_nft_id) > 0);
+
+            // The `updatePath` is only valid with no data-inputs, while the
+            // `voteSuccessPath` requires the ballots as data-inputs
+            let kind = if previous_update_box_id.is_none() {
+                GovernanceEventKind::UpdateBoxCreated
+            } else if !tx.data_inputs.is_empty() {
+                GovernanceEventKind::VotesCollected
+            } else if bank_box.is_some() {
+                GovernanceEventKind::ProtocolUpdated
+            } else {
+                GovernanceEventKind::VotesCollected
+            };
+
+            let mut ballots = vec![];
+            let mut unresolved_data_inputs = vec![];
+            if kind == GovernanceEventKind::VotesCollected {
+                for data_input in &tx.data_inputs {
+                    let resolved = self
+                        .known_boxes
+                        .get(&data_input.box_id)
+                        .unwrap_or(data_input);
+                    match self.ballot_record(resolved, &address_hash, &previous_update_box_id) {
+                        Some(ballot) => ballots.push(ballot),
+                        None => unresolved_data_inputs.push(data_input.box_id.clone()),
+                    }
+                }
+            }
+            let voting_power = ballots
+                .iter()
+                .filter(|b| b.counted)
+                .fold(0u64, |acc, b| acc.saturating_add(b.voting_power));
+
+            events.push(GovernanceEvent {
+                tx_id: tx.id.clone(),
+                height: tx.height,
+                timestamp: tx.timestamp,
+                kind,
+                update_box_id: update_box.box_id.clone(),
+                previous_update_box_id,
+                address_hash,
+                ballots,
+                unresolved_data_inputs,
+                voting_power,
+                bank_box_id: match kind {
+                    GovernanceEventKind::ProtocolUpdated => bank_box.map(|b| b.box_id.clone()),
+                    _ => None,
+                },
+            });
+        }
+        GovernanceHistory { events }
+    }
+
+    /// The `BallotRecord` of a data-input, `None` if it is not a full
+    /// Ballot box
+    fn ballot_record(
+        &self,
+        data_input: &JsonBox,
+        address_hash: &str,
+        previous_update_box_id: &Option<String>,
+    ) -> Option<BallotRecord> {
+        let voting_power = data_input.token_amount(&self.config.update_ballot_token_id);
+        if !data_input.is_full || voting_power == 0 {
+            return None;
+        }
+        let address_hash_voted_for = data_input.bytes_register("R4").unwrap_or_default();
+        let update_box_id = data_input.bytes_register("R5");
+        Some(BallotRecord {
+            box_id: data_input.box_id.clone(),
+            voting_power,
+            counted: address_hash_voted_for == address_hash
+                && update_box_id.is_some()
+                && update_box_id == *previous_update_box_id,
+            address_hash_voted_for,
+        })
+    }
+}
+
+/// A `u64` which is encoded as either a JSON number or string
+fn json_u64(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+fn invalid(message: &str) -> ProtocolError {
+    ProtocolError::InvalidInputValue(format!("Invalid transaction JSON: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A 32 byte ID made of the repeated byte
+    fn id(byte: &str) -> String {
+        byte.repeat(32)
+    }
+
+    /// A 32 byte `Coll[Byte]` register value serialized as hex
+    fn coll(hex: &str) -> String {
+        format!("0e20{}", hex)
+    }
+
+    fn config() -> ProtocolConfig {
+        let mut config = ProtocolConfig::template();
+        config.update_nft_id = id("aa");
+        config.bank_nft_id = id("bb");
+        config.update_ballot_token_id = id("cc");
+        config
+    }
+
+    /// A full box as served by the explorer, with registers as objects
+    fn explorer_box(box_id: &str, assets: &[(&str, u64)], registers: &[(&str, &str)]) -> Value {
+        let registers: serde_json::Map<String, Value> = registers
+            .iter()
+            .map(|(id, hex)| {
+                (
+                    id.to_string(),
+                    json!({ "serializedValue": hex, "sigmaType": "Coll[SByte]" }),
+                )
+            })
+            .collect();
+        json!({
+            "boxId": box_id,
+            "value": 1000000,
+            "assets": assets
+                .iter()
+                .map(|(token_id, amount)| json!({ "tokenId": token_id, "amount": amount }))
+                .collect::<Vec<Value>>(),
+            "additionalRegisters": registers,
+        })
+    }
+
+    /// A full box as served by the node, with registers as serialized hex
+    /// and amounts as strings
+    fn node_box(box_id: &str, assets: &[(&str, u64)], registers: &[(&str, &str)]) -> Value {
+        let registers: serde_json::Map<String, Value> = registers
+            .iter()
+            .map(|(id, hex)| (id.to_string(), json!(hex)))
+            .collect();
+        json!({
+            "boxId": box_id,
+            "value": 1000000,
+            "assets": assets
+                .iter()
+                .map(|(token_id, amount)| json!({ "tokenId": token_id, "amount": amount.to_string() }))
+                .collect::<Vec<Value>>(),
+            "additionalRegisters": registers,
+        })
+    }
+
+    /// The Update box is created at genesis (explorer JSON)
+    fn genesis_tx() -> Value {
+        json!({
+            "id": id("10"),
+            "inclusionHeight": 100,
+            "timestamp": 1600000000000u64,
+            "inputs": [explorer_box(&id("06"), &[], &[])],
+            "dataInputs": [],
+            "outputs": [explorer_box(&id("01"), &[(&id("aa"), 1)], &[("R4", &coll(&id("ee")))])],
+        })
+    }
+
+    /// The votes for `dd..` are collected with two full ballots as
+    /// data-inputs, one of which voted for another hash (unconfirmed
+    /// explorer JSON, so without an inclusion height)
+    fn votes_tx() -> Value {
+        json!({
+            "id": id("11"),
+            "inputs": [explorer_box(&id("01"), &[(&id("aa"), 1)], &[("R4", &coll(&id("ee")))])],
+            "dataInputs": [
+                explorer_box(
+                    &id("04"),
+                    &[(&id("cc"), 600)],
+                    &[("R4", &coll(&id("dd"))), ("R5", &coll(&id("01")))],
+                ),
+                explorer_box(
+                    &id("05"),
+                    &[(&id("cc"), 100)],
+                    &[("R4", &coll(&id("ef"))), ("R5", &coll(&id("01")))],
+                ),
+            ],
+            "outputs": [explorer_box(&id("02"), &[(&id("aa"), 1)], &[("R4", &coll(&id("dd")))])],
+        })
+    }
+
+    /// The Bank box is moved to the collected contract (node JSON, whose
+    /// inputs only hold their box ids)
+    fn update_tx() -> Value {
+        json!({
+            "id": id("12"),
+            "inclusionHeight": 200,
+            "inputs": [
+                { "boxId": id("07"), "spendingProof": { "proofBytes": "", "extension": {} } },
+                { "boxId": id("02"), "spendingProof": { "proofBytes": "", "extension": {} } },
+            ],
+            "dataInputs": [],
+            "outputs": [
+                node_box(&id("08"), &[(&id("bb"), 1)], &[("R4", "0500"), ("R5", "0500")]),
+                node_box(&id("03"), &[(&id("aa"), 1)], &[("R4", &coll(&id("dd")))]),
+            ],
+        })
+    }
+
+    #[test]
+    fn explorer_transactions_are_parsed() {
+        let mut builder = GovernanceHistoryBuilder::new(&config());
+        builder
+            .add_transactions_json(&json!({ "items": [genesis_tx(), votes_tx()] }).to_string())
+            .unwrap();
+        let history = builder.build();
+        assert_eq!(history.events.len(), 2);
+
+        let created = &history.events[0];
+        assert_eq!(created.kind, GovernanceEventKind::UpdateBoxCreated);
+        assert_eq!(created.height, Some(100));
+        assert_eq!(created.timestamp, Some(1600000000000));
+        assert_eq!(created.update_box_id, id("01"));
+        assert_eq!(created.previous_update_box_id, None);
+        assert_eq!(created.address_hash, id("ee"));
+
+        let votes = &history.events[1];
+        assert_eq!(votes.kind, GovernanceEventKind::VotesCollected);
+        assert_eq!(votes.height, None);
+        assert_eq!(votes.previous_update_box_id, Some(id("01")));
+        assert_eq!(votes.address_hash, id("dd"));
+        assert_eq!(
+            votes.ballots,
+            vec![
+                BallotRecord {
+                    box_id: id("04"),
+                    voting_power: 600,
+                    address_hash_voted_for: id("dd"),
+                    counted: true,
+                },
+                BallotRecord {
+                    box_id: id("05"),
+                    voting_power: 100,
+                    address_hash_voted_for: id("ef"),
+                    counted: false,
+                },
+            ]
+        );
+        assert!(votes.unresolved_data_inputs.is_empty());
+        assert_eq!(votes.voting_power, 600);
+    }
+
+    #[test]
+    fn node_transactions_are_parsed() {
+        let mut builder = GovernanceHistoryBuilder::new(&config());
+        builder
+            .add_transaction_json(&votes_tx().to_string())
+            .unwrap()
+            .add_transaction_json(&update_tx().to_string())
+            .unwrap();
+        let history = builder.build();
+        let updates = history.protocol_updates();
+        assert_eq!(updates.len(), 1);
+        let update = updates[0];
+        assert_eq!(update.height, Some(200));
+        // The spent Update box is resolved from the outputs of the votes
+        assert_eq!(update.previous_update_box_id, Some(id("02")));
+        assert_eq!(update.update_box_id, id("03"));
+        assert_eq!(update.bank_box_id, Some(id("08")));
+        assert!(update.ballots.is_empty());
+    }
+
+    #[test]
+    fn node_data_inputs_are_resolved_from_added_boxes() {
+        let tx = json!({
+            "id": id("13"),
+            "inclusionHeight": 300,
+            "inputs": [{ "boxId": id("03") }],
+            "dataInputs": [{ "boxId": id("09") }, { "boxId": id("0a") }],
+            "outputs": [node_box(&id("0b"), &[(&id("aa"), 1)], &[("R4", &coll(&id("de")))])],
+        });
+        let ballot = node_box(
+            &id("09"),
+            &[(&id("cc"), 700)],
+            &[("R4", &coll(&id("de"))), ("R5", &coll(&id("03")))],
+        );
+        let mut builder = GovernanceHistoryBuilder::new(&config());
+        builder
+            .add_transaction_json(&update_tx().to_string())
+            .unwrap()
+            .add_transaction_json(&tx.to_string())
+            .unwrap()
+            .add_box_json(&ballot.to_string())
+            .unwrap();
+        let history = builder.build();
+        let votes = &history.events[1];
+        assert_eq!(votes.kind, GovernanceEventKind::VotesCollected);
+        assert_eq!(votes.ballots.len(), 1);
+        assert!(votes.ballots[0].counted);
+        assert_eq!(votes.voting_power, 700);
+        assert_eq!(votes.unresolved_data_inputs, vec![id("0a")]);
+    }
+
+    #[test]
+    fn transactions_without_a_height_keep_their_position() {
+        let mut builder = GovernanceHistoryBuilder::new(&config());
+        for tx in &[update_tx(), votes_tx(), genesis_tx()] {
+            builder.add_transaction_json(&tx.to_string()).unwrap();
+        }
+        let history = builder.build();
+        let tx_ids: Vec<String> = history.events.iter().map(|e| e.tx_id.clone()).collect();
+        assert_eq!(tx_ids, vec![id("10"), id("11"), id("12")]);
+    }
+
+    #[test]
+    fn invalid_json_is_rejected() {
+        let mut builder = GovernanceHistoryBuilder::new(&config());
+        assert!(builder.add_transaction_json("{").is_err());
+        assert!(builder
+            .add_transaction_json(&json!({ "inputs": [] }).to_string())
+            .is_err());
+        assert!(builder
+            .add_transactions_json(&json!({ "total": 0 }).to_string())
+            .is_err());
+    }
+}
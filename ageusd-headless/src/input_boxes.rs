@@ -1,7 +1,7 @@
 /// This file holds structs that implement `SpecifiedBox` which are to be
 /// used as inputs to Actions.
 use crate::error::{ProtocolError, Result};
-use crate::parameters::{MIN_BOX_VALUE, RESERVECOIN_TOKEN_ID, STABLECOIN_TOKEN_ID};
+use crate::parameters::ProtocolConfig;
 pub use ergo_headless_dapp_framework::box_traits::{ExplorerFindable, SpecifiedBox, WrappedBox};
 pub use ergo_headless_dapp_framework::specified_boxes::{ErgUsdOraclePoolBox, ErgsBox};
 use ergo_headless_dapp_framework::{
@@ -24,17 +24,48 @@ pub struct ReserveCoinBox {
 /// WASM ReserveCoinBox Methods
 #[wasm_bindgen]
 impl ReserveCoinBox {
-    /// Get the amount of tokens within the box
+    /// Wrap an `ErgoBox` which holds the ReserveCoins of the deployment
+    /// described by `config`
+    #[wasm_bindgen]
+    pub fn w_new_with_config(
+        wrapped_box: WErgoBox,
+        config: &ProtocolConfig,
+    ) -> std::result::Result<ReserveCoinBox, JsValue> {
+        let ergo_box: ErgoBox = wrapped_box.into();
+        ReserveCoinBox::new_with_config(&ergo_box, config)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// Get the amount of tokens within the box.
+    /// Only reads the ReserveCoins of the default (mainnet) deployment and
+    /// panics if the box holds none, as is the case for a box wrapped with
+    /// the `ProtocolConfig` of another deployment. Use
+    /// `token_amount_with_config` for those.
     #[wasm_bindgen(getter)]
     pub fn token_amount(&self) -> u64 {
-        Self::extract_token_amount(&self.get_box()).unwrap()
+        self.token_amount_with_config(&ProtocolConfig::default())
+    }
+
+    /// Get the amount of the ReserveCoins of the deployment described by
+    /// `config` within the box
+    #[wasm_bindgen]
+    pub fn token_amount_with_config(&self, config: &ProtocolConfig) -> u64 {
+        // Using unwrap because `new_with_config` guarantees the box holds
+        // the ReserveCoins of `config`
+        Self::extract_token_amount(&self.get_box(), &config.reservecoin_token_id).unwrap()
     }
 }
 
 /// SpecifiedBox impl
 impl SpecifiedBox for ReserveCoinBox {
-    /// A `BoxSpec` that checks that ReserveCoins are in the box via a
-    /// predicate.
+    /// A `BoxSpec` that checks that ReserveCoins of the default (mainnet)
+    /// deployment are in the box via a predicate. See `ReserveCoinBox::new_with_config`.
+    ///
+    /// Beware: the derived `ReserveCoinBox::new`, `ReserveCoinBox::w_new` and
+    /// `process_explorer_response` use this spec, so they reject the boxes
+    /// of every other deployment (testnet, devnet or any custom
+    /// `ProtocolConfig`). Use `ReserveCoinBox::new_with_config` and
+    /// `ReserveCoinBox::w_new_with_config` instead.
     fn box_spec() -> BoxSpec {
         BoxSpec::new_predicated(None, None, vec![], vec![], Some(Self::predicate))
     }
@@ -42,16 +73,26 @@ impl SpecifiedBox for ReserveCoinBox {
 
 /// Rust ReserveCoinBox Methods
 impl ReserveCoinBox {
-    /// Predicate to check that the box has ReserveCoins in it
+    /// Predicate to check that the box has ReserveCoins of the default
+    /// (mainnet) deployment in it
     fn predicate(b: &ErgoBox) -> bool {
-        Self::extract_token_amount(b).is_ok()
+        Self::extract_token_amount(b, &ProtocolConfig::default().reservecoin_token_id).is_ok()
+    }
+
+    /// Wrap an `ErgoBox` which holds the ReserveCoins of the deployment
+    /// described by `config`
+    pub fn new_with_config(b: &ErgoBox, config: &ProtocolConfig) -> Result<ReserveCoinBox> {
+        Self::extract_token_amount(b, &config.reservecoin_token_id)?;
+        Ok(ReserveCoinBox {
+            ergo_box: b.clone(),
+        })
     }
 
-    /// Acquires the token amount
-    fn extract_token_amount(b: &ErgoBox) -> Result<u64> {
+    /// Acquires the amount of the `reservecoin_token_id` token
+    fn extract_token_amount(b: &ErgoBox, reservecoin_token_id: &str) -> Result<u64> {
         for t in &b.tokens {
             let token_id_string: String = t.token_id.0.clone().into();
-            if token_id_string == RESERVECOIN_TOKEN_ID {
+            if token_id_string == reservecoin_token_id {
                 return Ok(u64::from(t.amount));
             }
         }
@@ -61,21 +102,26 @@ impl ReserveCoinBox {
     }
 
     /// Sums the nanoErg value of a list of `ReserveCoinBox`es
-    pub fn sum_nano_ergs_value(boxes: &Vec<ReserveCoinBox>) -> u64 {
-        boxes.into_iter().fold(0, |acc, pb| pb.nano_ergs() + acc)
+    pub fn sum_nano_ergs_value(boxes: &[ReserveCoinBox]) -> u64 {
+        boxes.iter().fold(0, |acc, pb| pb.nano_ergs() + acc)
     }
 
     /// Sums the token amount of a list of `ReserveCoinBox`es
-    pub fn sum_token_amount(boxes: &Vec<ReserveCoinBox>) -> u64 {
-        boxes.into_iter().fold(0, |acc, b| b.token_amount() + acc)
+    pub fn sum_token_amount(boxes: &[ReserveCoinBox], config: &ProtocolConfig) -> u64 {
+        boxes
+            .iter()
+            .fold(0, |acc, b| b.token_amount_with_config(config) + acc)
     }
 
     /// Converts from the WASM wrapper `ErgoBoxes`.
-    pub fn convert_from_ergo_boxes(ergo_boxes: &ErgoBoxes) -> Result<Vec<ReserveCoinBox>> {
+    pub fn convert_from_ergo_boxes(
+        ergo_boxes: &ErgoBoxes,
+        config: &ProtocolConfig,
+    ) -> Result<Vec<ReserveCoinBox>> {
         let mut boxes: Vec<ReserveCoinBox> = vec![];
         let unwrapped_boxes: Vec<ErgoBox> = ergo_boxes.clone().into();
         for b in unwrapped_boxes {
-            let ergs_box = ReserveCoinBox::new(&b)?;
+            let ergs_box = ReserveCoinBox::new_with_config(&b, config)?;
             boxes.push(ergs_box);
         }
         Ok(boxes)
@@ -84,11 +130,14 @@ impl ReserveCoinBox {
     /// This is useful for using with protocols as a placeholder so that
     /// an assembler spec can be created (and this placeholder box thrown out
     /// and replaced with the user's actual input box from the assembler)
-    pub fn create_placeholder_box(num_reservecoins: u64) -> Option<ReserveCoinBox> {
+    pub fn create_placeholder_box(
+        num_reservecoins: u64,
+        config: &ProtocolConfig,
+    ) -> Option<ReserveCoinBox> {
         let placeholder_address = "2iHkR7CWvD1R4j1yZg5bkeDRQavjAaVPeTDFGGLZduHyfWMuYpmhHocX8GJoaieTx78FntzJbCBVL6rf96ocJoZdmWBL2fci7NqWgAirppPQmZ7fN9V6z13Ay6brPriBKYqLp1bT2Fk4FkFLCfdPpe".to_string();
         let ergo_tree = deserialize_p2s_to_ergo_tree(placeholder_address).ok()?;
-        let box_value = BoxValue::new(MIN_BOX_VALUE).ok()?;
-        let token = build_token(RESERVECOIN_TOKEN_ID, num_reservecoins).ok()?;
+        let box_value = BoxValue::new(config.min_box_value).ok()?;
+        let token = build_token(&config.reservecoin_token_id, num_reservecoins).ok()?;
         let placeholder_box = ErgoBox::new(
             box_value,
             ergo_tree,
@@ -98,7 +147,7 @@ impl ReserveCoinBox {
             TxId::zero(),
             0,
         );
-        ReserveCoinBox::new(&placeholder_box).ok()
+        ReserveCoinBox::new_with_config(&placeholder_box, config).ok()
     }
 }
 
@@ -112,17 +161,48 @@ pub struct StableCoinBox {
 /// WASM StableCoinBox Methods
 #[wasm_bindgen]
 impl StableCoinBox {
+    /// Wrap an `ErgoBox` which holds the StableCoins of the deployment
+    /// described by `config`
+    #[wasm_bindgen]
+    pub fn w_new_with_config(
+        wrapped_box: WErgoBox,
+        config: &ProtocolConfig,
+    ) -> std::result::Result<StableCoinBox, JsValue> {
+        let ergo_box: ErgoBox = wrapped_box.into();
+        StableCoinBox::new_with_config(&ergo_box, config)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// Get the amount of tokens within the box.
+    /// Only reads the StableCoins of the default (mainnet) deployment and
+    /// panics if the box holds none, as is the case for a box wrapped with
+    /// the `ProtocolConfig` of another deployment. Use
+    /// `token_amount_with_config` for those.
     #[wasm_bindgen(getter)]
-    /// Get the amount of tokens within the box
     pub fn token_amount(&self) -> u64 {
-        Self::extract_token_amount(&self.get_box()).unwrap()
+        self.token_amount_with_config(&ProtocolConfig::default())
+    }
+
+    /// Get the amount of the StableCoins of the deployment described by
+    /// `config` within the box
+    #[wasm_bindgen]
+    pub fn token_amount_with_config(&self, config: &ProtocolConfig) -> u64 {
+        // Using unwrap because `new_with_config` guarantees the box holds
+        // the StableCoins of `config`
+        Self::extract_token_amount(&self.get_box(), &config.stablecoin_token_id).unwrap()
     }
 }
 
 /// SpecifiedBox impl
 impl SpecifiedBox for StableCoinBox {
-    /// A `BoxSpec` that checks that StableCoins are in the box via a
-    /// predicate.
+    /// A `BoxSpec` that checks that StableCoins of the default (mainnet)
+    /// deployment are in the box via a predicate. See `StableCoinBox::new_with_config`.
+    ///
+    /// Beware: the derived `StableCoinBox::new`, `StableCoinBox::w_new` and
+    /// `process_explorer_response` use this spec, so they reject the boxes
+    /// of every other deployment (testnet, devnet or any custom
+    /// `ProtocolConfig`). Use `StableCoinBox::new_with_config` and
+    /// `StableCoinBox::w_new_with_config` instead.
     fn box_spec() -> BoxSpec {
         BoxSpec::new_predicated(None, None, vec![], vec![], Some(Self::predicate))
     }
@@ -130,16 +210,26 @@ impl SpecifiedBox for StableCoinBox {
 
 /// Rust StableCoinBox Methods
 impl StableCoinBox {
-    /// Predicate to check that the box has StableCoins in it
+    /// Predicate to check that the box has StableCoins of the default
+    /// (mainnet) deployment in it
     fn predicate(b: &ErgoBox) -> bool {
-        Self::extract_token_amount(b).is_ok()
+        Self::extract_token_amount(b, &ProtocolConfig::default().stablecoin_token_id).is_ok()
+    }
+
+    /// Wrap an `ErgoBox` which holds the StableCoins of the deployment
+    /// described by `config`
+    pub fn new_with_config(b: &ErgoBox, config: &ProtocolConfig) -> Result<StableCoinBox> {
+        Self::extract_token_amount(b, &config.stablecoin_token_id)?;
+        Ok(StableCoinBox {
+            ergo_box: b.clone(),
+        })
     }
 
-    /// Acquires the token amount
-    fn extract_token_amount(b: &ErgoBox) -> Result<u64> {
+    /// Acquires the amount of the `stablecoin_token_id` token
+    fn extract_token_amount(b: &ErgoBox, stablecoin_token_id: &str) -> Result<u64> {
         for t in &b.tokens {
             let token_id_string: String = t.token_id.0.clone().into();
-            if token_id_string == STABLECOIN_TOKEN_ID {
+            if token_id_string == stablecoin_token_id {
                 return Ok(u64::from(t.amount));
             }
         }
@@ -149,21 +239,26 @@ impl StableCoinBox {
     }
 
     /// Sums the nanoErg value of a list of `StableCoinBox`es
-    pub fn sum_nano_ergs_value(boxes: &Vec<StableCoinBox>) -> u64 {
-        boxes.into_iter().fold(0, |acc, pb| pb.nano_ergs() + acc)
+    pub fn sum_nano_ergs_value(boxes: &[StableCoinBox]) -> u64 {
+        boxes.iter().fold(0, |acc, pb| pb.nano_ergs() + acc)
     }
 
     /// Sums the token amount of a list of `StableCoinBox`es
-    pub fn sum_token_amount(boxes: &Vec<StableCoinBox>) -> u64 {
-        boxes.into_iter().fold(0, |acc, b| b.token_amount() + acc)
+    pub fn sum_token_amount(boxes: &[StableCoinBox], config: &ProtocolConfig) -> u64 {
+        boxes
+            .iter()
+            .fold(0, |acc, b| b.token_amount_with_config(config) + acc)
     }
 
     /// Converts from the WASM wrapper `ErgoBoxes`.
-    pub fn convert_from_ergo_boxes(ergo_boxes: &ErgoBoxes) -> Result<Vec<StableCoinBox>> {
+    pub fn convert_from_ergo_boxes(
+        ergo_boxes: &ErgoBoxes,
+        config: &ProtocolConfig,
+    ) -> Result<Vec<StableCoinBox>> {
         let mut boxes: Vec<StableCoinBox> = vec![];
         let unwrapped_boxes: Vec<ErgoBox> = ergo_boxes.clone().into();
         for b in unwrapped_boxes {
-            let ergs_box = StableCoinBox::new(&b)?;
+            let ergs_box = StableCoinBox::new_with_config(&b, config)?;
             boxes.push(ergs_box);
         }
         Ok(boxes)
@@ -173,11 +268,14 @@ impl StableCoinBox {
     /// This is useful for using with protocols as a placeholder so that
     /// an assembler spec can be created (and this placeholder box thrown out
     /// and replaced with the user's actual input box from the assembler)
-    pub fn create_placeholder_box(num_stablecoins: u64) -> Option<StableCoinBox> {
+    pub fn create_placeholder_box(
+        num_stablecoins: u64,
+        config: &ProtocolConfig,
+    ) -> Option<StableCoinBox> {
         let placeholder_address = "2iHkR7CWvD1R4j1yZg5bkeDRQavjAaVPeTDFGGLZduHyfWMuYpmhHocX8GJoaieTx78FntzJbCBVL6rf96ocJoZdmWBL2fci7NqWgAirppPQmZ7fN9V6z13Ay6brPriBKYqLp1bT2Fk4FkFLCfdPpe".to_string();
         let ergo_tree = deserialize_p2s_to_ergo_tree(placeholder_address).ok()?;
-        let box_value = BoxValue::new(MIN_BOX_VALUE).ok()?;
-        let token = build_token(STABLECOIN_TOKEN_ID, num_stablecoins).ok()?;
+        let box_value = BoxValue::new(config.min_box_value).ok()?;
+        let token = build_token(&config.stablecoin_token_id, num_stablecoins).ok()?;
         let placeholder_box = ErgoBox::new(
             box_value,
             ergo_tree,
@@ -187,6 +285,6 @@ impl StableCoinBox {
             TxId::zero(),
             0,
         );
-        StableCoinBox::new(&placeholder_box).ok()
+        StableCoinBox::new_with_config(&placeholder_box, config).ok()
     }
 }
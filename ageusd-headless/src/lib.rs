@@ -1,8 +1,25 @@
+// The actions take every transaction input positionally to stay callable
+// from WASM, where grouping them into structs is not practical
+#![allow(clippy::too_many_arguments)]
+
 pub mod bank;
+pub mod block_time;
+pub mod bootstrap;
+pub mod capacity;
+pub mod coin_selection;
+pub mod contract_diff;
+pub mod contract_model;
 pub mod equations;
 pub mod error;
+pub mod fees;
+pub mod governance;
 pub mod input_boxes;
 pub mod parameters;
 pub mod protocol;
+pub mod quote;
 mod receipt;
+pub mod registers;
+pub mod scenario;
+pub mod solvency;
 pub mod update;
+pub mod voting;
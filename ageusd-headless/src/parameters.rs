@@ -1,33 +1,707 @@
-// This file holds hard-coded parameters of the protocol.
-use ergo_headless_dapp_framework::NanoErg;
-
-/// The minimum value a box will hold. (aka. Min to cover storage rent)
-pub static MIN_BOX_VALUE: NanoErg = 10000000;
-
-// Default price of a ReserveCoin if 0 ReserveCoins are in circulation.
-// Primarily set for edgecase to be covered.
-pub static RESERVECOIN_DEFAULT_PRICE: NanoErg = 1000000;
-
-// Reserve Ratios
-pub static MIN_RESERVE_RATIO: u64 = 400;
-pub static MAX_RESERVE_RATIO: u64 = 800;
-
-// The Block Height that the "Bootstrap Cool-Off" period completes & the
-// Maximum Reserve Ratio is officially applied thenceforth
-pub static COOLING_OFF_HEIGHT: u64 = 377770;
-
-// The fee percentage that is charge on each minting/redeeming action as a
-// u64. 1 == 1%
-pub static FEE_PERCENT: u64 = 2;
-// The fee percentage that users pay to the frontend implementor as a f64.
-// 1 == 100%, 0.01 == 1%
-pub static IMPLEMENTOR_FEE_PERCENT: f64 = 0.0025;
-
-// Token IDs
-pub static STABLECOIN_TOKEN_ID: &str = "";
-pub static RESERVECOIN_TOKEN_ID: &str = "";
-pub static BANK_NFT_ID: &str = "";
-pub static ORACLE_POOL_NFT_ID: &str =
-    "008a94c8c76bbaa1f0a346697d1794eb31d94b37e5533af9cc0b6932bf159339";
-pub static UPDATE_NFT_ID: &str = "";
-pub static UPDATE_BALLOT_TOKEN_ID: &str = "";
+// This file holds the parameters of a deployment of the protocol.
+// A `ProtocolConfig` carries every token ID and economic constant that
+// the headless dApp relies upon. It is passed explicitly to the `BoxSpec`s,
+// the `BankBox` pricing methods, and held by the `StableCoinProtocol`.
+use crate::error::{ProtocolError, Result};
+use crate::fees::{FeeSchedule, BASIS_POINTS};
+use ergo_headless_dapp_framework::{BlockHeight, ErgUsdOraclePoolBox, NanoErg};
+use ergo_lib::ergo_tree::ErgoTree;
+use ergo_lib::serialization::SigmaSerializable;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+/// The parameters of a single deployment of the protocol.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtocolConfig {
+    /// The minimum value a box will hold. (aka. Min to cover storage rent)
+    pub min_box_value: NanoErg,
+    /// Default price of a ReserveCoin if 0 ReserveCoins are in circulation.
+    /// Primarily set for edgecase to be covered.
+    pub reservecoin_default_price: NanoErg,
+    /// The minimum reserve ratio as a percent
+    pub min_reserve_ratio: u64,
+    /// The maximum reserve ratio as a percent
+    pub max_reserve_ratio: u64,
+    /// The Block Height that the "Bootstrap Cool-Off" period completes & the
+    /// Maximum Reserve Ratio is officially applied thenceforth
+    pub cooling_off_height: BlockHeight,
+    /// The fee percentage that is charge on each minting/redeeming action as a
+    /// u64. 1 == 1%
+    pub fee_percent: u64,
+    /// The fee that users pay to the frontend implementor in basis points.
+    /// 1 == 0.01%, 100 == 1%
+    pub implementor_fee_bps: u64,
+    /// The version of the Bank contract which is deployed
+    pub contract_version: ContractVersion,
+    /// The voting power (number of ballot tokens) required to collect the
+    /// votes for an update
+    pub min_votes: u64,
+    /// Token ID of the StableCoin
+    #[wasm_bindgen(skip)]
+    pub stablecoin_token_id: String,
+    /// Token ID of the ReserveCoin
+    #[wasm_bindgen(skip)]
+    pub reservecoin_token_id: String,
+    /// Token ID of the NFT which identifies the Bank box
+    #[wasm_bindgen(skip)]
+    pub bank_nft_id: String,
+    /// Token ID of the NFT which identifies the Erg-USD Oracle Pool box
+    #[wasm_bindgen(skip)]
+    pub oracle_pool_nft_id: String,
+    /// Token ID of the NFT which identifies the Update box
+    #[wasm_bindgen(skip)]
+    pub update_nft_id: String,
+    /// Token ID of the ballot tokens used to vote on protocol updates
+    #[wasm_bindgen(skip)]
+    pub update_ballot_token_id: String,
+}
+
+impl Default for ProtocolConfig {
+    fn default() -> ProtocolConfig {
+        ProtocolConfig::mainnet()
+    }
+}
+
+/// The networks which the protocol can be deployed on. Only mainnet ships
+/// with a preset `ProtocolConfig`, the parameters of a deployment on any
+/// other network are provided via a config file.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+}
+
+impl Network {
+    /// All of the known networks
+    pub fn all() -> Vec<Network> {
+        vec![Network::Mainnet, Network::Testnet, Network::Devnet]
+    }
+
+    /// The name of the network as used on the command line/in config files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Devnet => "devnet",
+        }
+    }
+
+    /// The URL of the public Ergo Explorer API of the network, `None` for a
+    /// devnet which has no public explorer
+    pub fn explorer_api_url(&self) -> Option<&'static str> {
+        match self {
+            Network::Mainnet => Some("https://api.ergoplatform.com/api"),
+            Network::Testnet => Some("https://api-testnet.ergoplatform.com/api"),
+            Network::Devnet => None,
+        }
+    }
+
+    /// Parse a network from its name. `local` is accepted as an alias
+    /// for `devnet`.
+    pub fn from_name(name: &str) -> Result<Network> {
+        match name.to_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "devnet" | "local" => Ok(Network::Devnet),
+            _ => Err(ProtocolError::InvalidConfig(format!(
+                "Unknown network `{}`. Expected one of: mainnet, testnet, devnet.",
+                name
+            ))),
+        }
+    }
+}
+
+/// The generations of the protocol contracts held in `ageusd-smart-contracts/`.
+/// Each version prices coins and validates exchanges slightly differently.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContractVersion {
+    #[serde(rename = "v0.1")]
+    V0_1,
+    #[serde(rename = "v0.2")]
+    V0_2,
+    #[serde(rename = "v0.3")]
+    V0_3,
+    #[serde(rename = "v0.4")]
+    V0_4,
+}
+
+impl ContractVersion {
+    /// All of the contract versions, oldest first
+    pub fn all() -> Vec<ContractVersion> {
+        vec![
+            ContractVersion::V0_1,
+            ContractVersion::V0_2,
+            ContractVersion::V0_3,
+            ContractVersion::V0_4,
+        ]
+    }
+
+    /// The name of the version as used on the command line/in config files
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContractVersion::V0_1 => "v0.1",
+            ContractVersion::V0_2 => "v0.2",
+            ContractVersion::V0_3 => "v0.3",
+            ContractVersion::V0_4 => "v0.4",
+        }
+    }
+
+    /// Parse a version from its name. The leading `v` is optional.
+    pub fn from_name(name: &str) -> Result<ContractVersion> {
+        let name = name.to_lowercase();
+        let name = name.trim_start_matches('v');
+        ContractVersion::all()
+            .into_iter()
+            .find(|v| &v.name()[1..] == name)
+            .ok_or_else(|| {
+                ProtocolError::InvalidConfig(format!(
+                    "Unknown contract version `{}`. Expected one of: v0.1, v0.2, v0.3, v0.4.",
+                    name
+                ))
+            })
+    }
+
+    /// The nanoErg price of a single StableCoin (1 cent) according to the
+    /// oracle pool box. Before v0.3 the oracle posted the price per cent,
+    /// from v0.3 onwards it posts the price per dollar.
+    pub fn oracle_rate(&self, oracle_box: &ErgUsdOraclePoolBox) -> NanoErg {
+        match self {
+            ContractVersion::V0_1 | ContractVersion::V0_2 => oracle_box.datapoint(),
+            ContractVersion::V0_3 | ContractVersion::V0_4 => oracle_box.datapoint_in_cents(),
+        }
+    }
+
+    /// The protocol fee percentage hard-coded into the contract sources
+    pub fn contract_fee_percent(&self) -> u64 {
+        match self {
+            ContractVersion::V0_1 | ContractVersion::V0_2 => 0,
+            ContractVersion::V0_3 => 1,
+            ContractVersion::V0_4 => 2,
+        }
+    }
+
+    /// Whether the storage rent (min box value) of the Bank box is excluded
+    /// from the base reserves, as was the case before v0.3
+    pub fn reserves_exclude_storage_rent(&self) -> bool {
+        matches!(self, ContractVersion::V0_1 | ContractVersion::V0_2)
+    }
+
+    /// Whether the Bank contract charges the protocol fee (from v0.3)
+    pub fn charges_protocol_fee(&self) -> bool {
+        !self.reserves_exclude_storage_rent()
+    }
+
+    /// Whether the max reserve ratio is only applied after the cooling off
+    /// height (from v0.3). Before then it is always applied.
+    pub fn has_cooling_off_period(&self) -> bool {
+        !self.reserves_exclude_storage_rent()
+    }
+
+    /// Whether the Bank contract floors the liabilities at 0 (from v0.3)
+    pub fn clamps_liabilities(&self) -> bool {
+        !self.reserves_exclude_storage_rent()
+    }
+
+    /// Whether the Bank contract requires the output Bank box to keep the
+    /// same contract and non-negative circulating supplies (from v0.4)
+    pub fn checks_bank_output_integrity(&self) -> bool {
+        *self == ContractVersion::V0_4
+    }
+
+    /// The oracle pool NFT ID hard-coded into the contract sources
+    pub fn oracle_pool_nft_id(&self) -> &'static str {
+        match self {
+            ContractVersion::V0_1 => {
+                "b662db51cf2dc39f110a021c2a31c74f0a1a18ffffbf73e8a051a7b8c0f09ebc"
+            }
+            ContractVersion::V0_2 => {
+                "08b59b14e4fdd60e5952314adbaa8b4e00bc0f0b676872a5224d3bf8591074cd"
+            }
+            ContractVersion::V0_3 | ContractVersion::V0_4 => {
+                "0fb1eca4646950743bc5a8c341c16871a0ad9b4077e3b276bf93855d51a042d1"
+            }
+        }
+    }
+
+    /// The versions which a Bank box `ErgoTree` may have been compiled from,
+    /// detected via the oracle pool NFT ID embedded within it.
+    /// v0.3 and v0.4 share the same NFT ID and so cannot be told apart.
+    /// Empty if the `ErgoTree` does not embed any known NFT ID.
+    pub fn possible_versions(bank_ergo_tree: &ErgoTree) -> Vec<ContractVersion> {
+        let tree_hex: String = bank_ergo_tree
+            .sigma_serialize_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        ContractVersion::all()
+            .into_iter()
+            .filter(|v| {
+                tree_hex
+                    .match_indices(v.oracle_pool_nft_id())
+                    .any(|(i, _)| i % 2 == 0)
+            })
+            .collect()
+    }
+}
+
+/// A field of a `ProtocolConfig` whose value differs between two configs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDifference {
+    /// The name of the field as used in config files
+    pub field: String,
+    /// The value held in the config being compared
+    pub value: String,
+    /// The value held in the config being compared against
+    pub expected: String,
+}
+
+/// WASM-supported methods related to `ProtocolConfig`
+#[wasm_bindgen]
+impl ProtocolConfig {
+    /// Create a new `ProtocolConfig` holding the default parameters
+    #[wasm_bindgen(constructor)]
+    pub fn w_new() -> ProtocolConfig {
+        ProtocolConfig::default()
+    }
+
+    /// Parse a `ProtocolConfig` from a JSON string
+    #[wasm_bindgen]
+    pub fn w_from_json(json: &str) -> std::result::Result<ProtocolConfig, JsValue> {
+        ProtocolConfig::from_json(json).map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// Parse a `ProtocolConfig` from a TOML string
+    #[wasm_bindgen]
+    pub fn w_from_toml(toml: &str) -> std::result::Result<ProtocolConfig, JsValue> {
+        ProtocolConfig::from_toml(toml).map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// Serialize the `ProtocolConfig` into a JSON string
+    #[wasm_bindgen]
+    pub fn w_to_json(&self) -> std::result::Result<String, JsValue> {
+        self.to_json()
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// Acquire the preset `ProtocolConfig` of a known network by name
+    #[wasm_bindgen]
+    pub fn w_preset(network_name: &str) -> std::result::Result<ProtocolConfig, JsValue> {
+        Network::from_name(network_name)
+            .and_then(ProtocolConfig::preset)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// Acquire the parameters of the v0.4 contracts without any token IDs
+    #[wasm_bindgen]
+    pub fn w_template() -> ProtocolConfig {
+        ProtocolConfig::template()
+    }
+
+    /// Check that the parameters are sensible
+    #[wasm_bindgen]
+    pub fn w_validate(&self) -> std::result::Result<(), JsValue> {
+        self.validate()
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn stablecoin_token_id(&self) -> String {
+        self.stablecoin_token_id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_stablecoin_token_id(&mut self, token_id: String) {
+        self.stablecoin_token_id = token_id;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn reservecoin_token_id(&self) -> String {
+        self.reservecoin_token_id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_reservecoin_token_id(&mut self, token_id: String) {
+        self.reservecoin_token_id = token_id;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bank_nft_id(&self) -> String {
+        self.bank_nft_id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_bank_nft_id(&mut self, token_id: String) {
+        self.bank_nft_id = token_id;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn oracle_pool_nft_id(&self) -> String {
+        self.oracle_pool_nft_id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_oracle_pool_nft_id(&mut self, token_id: String) {
+        self.oracle_pool_nft_id = token_id;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn update_nft_id(&self) -> String {
+        self.update_nft_id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_update_nft_id(&mut self, token_id: String) {
+        self.update_nft_id = token_id;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn update_ballot_token_id(&self) -> String {
+        self.update_ballot_token_id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_update_ballot_token_id(&mut self, token_id: String) {
+        self.update_ballot_token_id = token_id;
+    }
+}
+
+/// Rust methods related to `ProtocolConfig`
+impl ProtocolConfig {
+    /// The preset `ProtocolConfig` of a known network. Only mainnet has a
+    /// preset, errors for any other network.
+    pub fn preset(network: Network) -> Result<ProtocolConfig> {
+        match network {
+            Network::Mainnet => Ok(ProtocolConfig::mainnet()),
+            _ => Err(ProtocolError::InvalidConfig(format!(
+                "There is no preset for the {} network. Provide the parameters of the deployment via a config file.",
+                network.name()
+            ))),
+        }
+    }
+
+    /// The parameters of the v0.4 contracts, mirroring the constants in
+    /// `ageusd-smart-contracts/v0.4/AgeUSD.scala`, with every token ID left
+    /// empty. Meant to be overridden with the token IDs of a deployment
+    /// which has no preset, it does not pass `validate` until then.
+    pub fn template() -> ProtocolConfig {
+        ProtocolConfig {
+            min_box_value: 10000000,
+            reservecoin_default_price: 1000000,
+            min_reserve_ratio: 400,
+            max_reserve_ratio: 800,
+            cooling_off_height: 377770,
+            fee_percent: 2,
+            implementor_fee_bps: 25,
+            contract_version: ContractVersion::V0_4,
+            min_votes: 5,
+            stablecoin_token_id: "".to_string(),
+            reservecoin_token_id: "".to_string(),
+            bank_nft_id: "".to_string(),
+            oracle_pool_nft_id: "".to_string(),
+            update_nft_id: "".to_string(),
+            update_ballot_token_id: "".to_string(),
+        }
+    }
+
+    /// The `ProtocolConfig` of the mainnet deployment of the v0.4 contracts
+    fn mainnet() -> ProtocolConfig {
+        ProtocolConfig {
+            stablecoin_token_id: "03faf2cb329f2e90d6d23b58d91bbb6c046aa143261cc21f52fbe2824bfcbf04"
+                .to_string(),
+            reservecoin_token_id:
+                "003bd19d0187117f130b62e1bcab0939929ff5c7709f843c5c4dd158949285d0".to_string(),
+            bank_nft_id: "7d672d1def471720ca5782fd6473e47e796d9ac0c138d9911346f118b2f6d9d9"
+                .to_string(),
+            oracle_pool_nft_id: "0fb1eca4646950743bc5a8c341c16871a0ad9b4077e3b276bf93855d51a042d1"
+                .to_string(),
+            update_nft_id: "7a51950e5f548549ec1aa63ffdc38279505b11e7e803d01bcf8347e0123c88b0"
+                .to_string(),
+            update_ballot_token_id:
+                "f7995f212216fcf21854f56df7a9a0a9fc9b7ae4c0f1cc40f5b406371286a5e0".to_string(),
+            ..ProtocolConfig::template()
+        }
+    }
+
+    /// Check that the parameters are sensible. Every token ID must be set
+    /// to 32 bytes encoded as base16.
+    pub fn validate(&self) -> Result<()> {
+        for (field, token_id) in self.token_ids() {
+            if token_id.is_empty() {
+                return Err(ProtocolError::InvalidConfig(format!(
+                    "`{}` is not set to the token ID of the deployment.",
+                    field
+                )));
+            }
+            let is_hex = token_id.chars().all(|c| c.is_ascii_hexdigit());
+            if token_id.len() != 64 || !is_hex {
+                return Err(ProtocolError::InvalidConfig(format!(
+                    "`{}` is not a valid token ID: {}",
+                    field, token_id
+                )));
+            }
+        }
+        if self.min_reserve_ratio >= self.max_reserve_ratio {
+            return Err(ProtocolError::InvalidConfig(
+                "`min_reserve_ratio` must be lower than `max_reserve_ratio`.".to_string(),
+            ));
+        }
+        if self.fee_percent >= 100 {
+            return Err(ProtocolError::InvalidConfig(
+                "`fee_percent` must be lower than 100.".to_string(),
+            ));
+        }
+        if self.min_votes == 0 {
+            return Err(ProtocolError::InvalidConfig(
+                "`min_votes` must be greater than 0.".to_string(),
+            ));
+        }
+        if self.implementor_fee_bps >= BASIS_POINTS {
+            return Err(ProtocolError::InvalidConfig(format!(
+                "`implementor_fee_bps` must be lower than {}.",
+                BASIS_POINTS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Creates a new `ProtocolConfig` targeting the provided `ContractVersion`,
+    /// with the fee percentage of that version's contract
+    pub fn with_contract_version(&self, version: ContractVersion) -> ProtocolConfig {
+        ProtocolConfig {
+            contract_version: version,
+            fee_percent: version.contract_fee_percent(),
+            ..self.clone()
+        }
+    }
+
+    /// Whether the max reserve ratio is not yet applied to a transaction
+    /// built at `current_height`. The Bank contract applies it once
+    /// `HEIGHT > coolingOffHeight`, where `HEIGHT` is the height of the
+    /// block which includes the transaction, so from `current_height + 1`.
+    pub fn in_cooling_off_period(&self, current_height: BlockHeight) -> bool {
+        self.contract_version.has_cooling_off_period() && current_height < self.cooling_off_height
+    }
+
+    /// The `FeeSchedule` defined by the config
+    pub fn fee_schedule(&self) -> FeeSchedule {
+        FeeSchedule::from_config(self)
+    }
+
+    /// The token IDs held in the config paired with their field names
+    pub fn token_ids(&self) -> Vec<(&'static str, &String)> {
+        vec![
+            ("stablecoin_token_id", &self.stablecoin_token_id),
+            ("reservecoin_token_id", &self.reservecoin_token_id),
+            ("bank_nft_id", &self.bank_nft_id),
+            ("oracle_pool_nft_id", &self.oracle_pool_nft_id),
+            ("update_nft_id", &self.update_nft_id),
+            ("update_ballot_token_id", &self.update_ballot_token_id),
+        ]
+    }
+
+    /// Lists every field which differs between `self` and `expected`
+    /// (ie. a preset).
+    pub fn differences(&self, expected: &ProtocolConfig) -> Vec<ConfigDifference> {
+        let (value, expected) = match (serde_json::to_value(self), serde_json::to_value(expected)) {
+            (Ok(Value::Object(value)), Ok(Value::Object(expected))) => (value, expected),
+            _ => return vec![],
+        };
+        value
+            .iter()
+            .filter(|(field, v)| expected.get(*field) != Some(v))
+            .map(|(field, v)| ConfigDifference {
+                field: field.clone(),
+                value: display_value(v),
+                expected: expected.get(field).map(display_value).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Parse a `ProtocolConfig` from a JSON string. Missing fields are
+    /// filled in with the defaults.
+    pub fn from_json(json: &str) -> Result<ProtocolConfig> {
+        ProtocolConfig::default().overlay_json(json)
+    }
+
+    /// Parse a `ProtocolConfig` from a TOML string. Missing fields are
+    /// filled in with the defaults.
+    pub fn from_toml(toml: &str) -> Result<ProtocolConfig> {
+        ProtocolConfig::default().overlay_toml(toml)
+    }
+
+    /// Creates a new `ProtocolConfig` by overriding the fields of `self`
+    /// with those present in the provided JSON string.
+    pub fn overlay_json(&self, json: &str) -> Result<ProtocolConfig> {
+        let overrides: Value =
+            serde_json::from_str(json).map_err(|e| ProtocolError::InvalidConfig(e.to_string()))?;
+        self.overlay(overrides)
+    }
+
+    /// Creates a new `ProtocolConfig` by overriding the fields of `self`
+    /// with those present in the provided TOML string.
+    pub fn overlay_toml(&self, toml: &str) -> Result<ProtocolConfig> {
+        let overrides: toml::Value =
+            toml::from_str(toml).map_err(|e| ProtocolError::InvalidConfig(e.to_string()))?;
+        let overrides = serde_json::to_value(overrides)
+            .map_err(|e| ProtocolError::InvalidConfig(e.to_string()))?;
+        self.overlay(overrides)
+    }
+
+    /// Overrides the fields of `self` with the fields of a JSON object
+    fn overlay(&self, overrides: Value) -> Result<ProtocolConfig> {
+        let mut base =
+            serde_json::to_value(self).map_err(|e| ProtocolError::InvalidConfig(e.to_string()))?;
+        match (&mut base, overrides) {
+            (Value::Object(base_fields), Value::Object(override_fields)) => {
+                for (field, value) in override_fields {
+                    base_fields.insert(field, value);
+                }
+            }
+            _ => {
+                return Err(ProtocolError::InvalidConfig(
+                    "The config must be a table of parameters.".to_string(),
+                ))
+            }
+        }
+        serde_json::from_value(base).map_err(|e| ProtocolError::InvalidConfig(e.to_string()))
+    }
+
+    /// Serialize the `ProtocolConfig` into a JSON string
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| ProtocolError::InvalidConfig(e.to_string()))
+    }
+
+    /// Serialize the `ProtocolConfig` into a TOML string
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| ProtocolError::InvalidConfig(e.to_string()))
+    }
+
+    /// Read a `ProtocolConfig` from a local file. Files ending in `.json`
+    /// are parsed as JSON, everything else is parsed as TOML.
+    pub fn load_from_file(path: &Path) -> Result<ProtocolConfig> {
+        ProtocolConfig::default().overlay_file(path)
+    }
+
+    /// Creates a new `ProtocolConfig` by overriding the fields of `self`
+    /// with those present in a local file. Files ending in `.json`
+    /// are parsed as JSON, everything else is parsed as TOML.
+    pub fn overlay_file(&self, path: &Path) -> Result<ProtocolConfig> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ProtocolError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => self.overlay_json(&contents),
+            _ => self.overlay_toml(&contents),
+        }
+    }
+}
+
+/// Renders a JSON value of a config field for display (without quoting strings)
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// The source of the v0.4 contracts which the presets target
+    const CONTRACT_SOURCE: &str = include_str!("../../ageusd-smart-contracts/v0.4/AgeUSD.scala");
+
+    /// The top-level `val name = value` constants of the contract source,
+    /// with quotes, type ascriptions, `L` suffixes and comments stripped
+    fn contract_constants() -> HashMap<String, String> {
+        CONTRACT_SOURCE
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim().strip_prefix("val ")?;
+                let (name, value) = line.split_at(line.find('=')?);
+                let name = name.split(':').next()?.trim();
+                let value = value[1..].split("//").next()?.trim();
+                let value = value.trim_matches('"').trim_end_matches('L');
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
+    fn contract_u64(constants: &HashMap<String, String>, name: &str) -> u64 {
+        constants[name].parse().unwrap()
+    }
+
+    #[test]
+    fn presets_match_the_contract_constants() {
+        let constants = contract_constants();
+        let presets: Vec<ProtocolConfig> = Network::all()
+            .into_iter()
+            .filter_map(|network| ProtocolConfig::preset(network).ok())
+            .collect();
+        assert!(!presets.is_empty());
+        for config in presets {
+            assert_eq!(config.contract_version, ContractVersion::V0_4);
+            assert_eq!(config.oracle_pool_nft_id, constants["oraclePoolNFT"]);
+            assert_eq!(
+                config.oracle_pool_nft_id,
+                ContractVersion::V0_4.oracle_pool_nft_id()
+            );
+            assert_eq!(
+                config.cooling_off_height,
+                contract_u64(&constants, "coolingOffHeight")
+            );
+            assert_eq!(config.fee_percent, contract_u64(&constants, "feePercent"));
+            assert_eq!(
+                config.min_reserve_ratio,
+                contract_u64(&constants, "minReserveRatioPercent")
+            );
+            assert_eq!(
+                config.max_reserve_ratio,
+                contract_u64(&constants, "defaultMaxReserveRatioPercent")
+            );
+            assert_eq!(config.min_votes, contract_u64(&constants, "minVotes"));
+            assert_eq!(
+                config.reservecoin_default_price,
+                contract_u64(&constants, "rcDefaultPrice")
+            );
+            assert_eq!(
+                config.min_box_value,
+                contract_u64(&constants, "minStorageRent")
+            );
+        }
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        for network in Network::all() {
+            if let Ok(config) = ProtocolConfig::preset(network) {
+                config.validate().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn only_mainnet_has_a_preset() {
+        assert!(ProtocolConfig::preset(Network::Mainnet).is_ok());
+        assert!(ProtocolConfig::preset(Network::Testnet).is_err());
+        assert!(ProtocolConfig::preset(Network::Devnet).is_err());
+    }
+
+    #[test]
+    fn rejects_unset_token_ids() {
+        assert!(ProtocolConfig::template().validate().is_err());
+        let config = ProtocolConfig {
+            update_ballot_token_id: "".to_string(),
+            ..ProtocolConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
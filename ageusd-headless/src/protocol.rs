@@ -1,11 +1,11 @@
 use crate::bank::BankBox;
-use crate::equations::reserve_ratio;
+use crate::contract_model::validate_bank_transition;
+use crate::equations::{checked_add, checked_reserve_ratio, checked_sub};
 use crate::error::ProtocolError;
+use crate::fees::FeeSchedule;
 use crate::input_boxes::{ReserveCoinBox, StableCoinBox};
-use crate::parameters::{
-    BANK_NFT_ID, COOLING_OFF_HEIGHT, IMPLEMENTOR_FEE_PERCENT, MAX_RESERVE_RATIO, MIN_BOX_VALUE,
-    MIN_RESERVE_RATIO, RESERVECOIN_DEFAULT_PRICE, RESERVECOIN_TOKEN_ID, STABLECOIN_TOKEN_ID,
-};
+use crate::parameters::ProtocolConfig;
+use crate::quote::Quote;
 use crate::receipt::ReceiptBox;
 use ergo_headless_dapp_framework::{
     create_candidate, ErgUsdOraclePoolBox, ErgsBox, TokensChangeBox, TxAssemblerSpecBuilder,
@@ -22,50 +22,115 @@ use wasm_bindgen::prelude::*;
 
 /// The struct which represents our multi-stage smart contract protocol
 #[wasm_bindgen]
-pub struct StableCoinProtocol {}
+pub struct StableCoinProtocol {
+    pub(crate) config: ProtocolConfig,
+}
+
+impl Default for StableCoinProtocol {
+    fn default() -> StableCoinProtocol {
+        StableCoinProtocol::new()
+    }
+}
 
 /// WASM-supported methods related to `StableCoinProtocol`
 #[wasm_bindgen]
 impl StableCoinProtocol {
-    /// Create a new StableCoinProtocol
+    /// Create a new StableCoinProtocol using the default (mainnet)
+    /// `ProtocolConfig`
     #[wasm_bindgen(constructor)]
     pub fn new() -> StableCoinProtocol {
-        StableCoinProtocol {}
+        StableCoinProtocol {
+            config: ProtocolConfig::default(),
+        }
+    }
+
+    /// Create a new StableCoinProtocol for the deployment described by
+    /// `config`
+    #[wasm_bindgen]
+    pub fn new_with_config(config: &ProtocolConfig) -> StableCoinProtocol {
+        StableCoinProtocol {
+            config: config.clone(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn config(&self) -> ProtocolConfig {
+        self.config.clone()
     }
 
     #[wasm_bindgen(getter)]
     pub fn min_box_value(&self) -> u64 {
-        MIN_BOX_VALUE
+        self.config.min_box_value
     }
 
     #[wasm_bindgen(getter)]
     pub fn reservecoin_default_price(&self) -> u64 {
-        RESERVECOIN_DEFAULT_PRICE
+        self.config.reservecoin_default_price
     }
 
     #[wasm_bindgen(getter)]
     pub fn min_reserve_ratio(&self) -> u64 {
-        MIN_RESERVE_RATIO
+        self.config.min_reserve_ratio
     }
 
     #[wasm_bindgen(getter)]
     pub fn max_reserve_ratio(&self) -> u64 {
-        MAX_RESERVE_RATIO
+        self.config.max_reserve_ratio
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cooling_off_height(&self) -> u64 {
+        self.config.cooling_off_height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn fee_percent(&self) -> u64 {
+        self.config.fee_percent
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn implementor_fee_bps(&self) -> u64 {
+        self.config.implementor_fee_bps
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min_votes(&self) -> u64 {
+        self.config.min_votes
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn fee_schedule(&self) -> FeeSchedule {
+        self.config.fee_schedule()
     }
 
     #[wasm_bindgen(getter)]
     pub fn stablecoin_token_id(&self) -> String {
-        STABLECOIN_TOKEN_ID.to_string()
+        self.config.stablecoin_token_id.clone()
     }
 
     #[wasm_bindgen(getter)]
     pub fn reservecoin_token_id(&self) -> String {
-        RESERVECOIN_TOKEN_ID.to_string()
+        self.config.reservecoin_token_id.clone()
     }
 
     #[wasm_bindgen(getter)]
     pub fn bank_nft_id(&self) -> String {
-        BANK_NFT_ID.to_string()
+        self.config.bank_nft_id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn oracle_pool_nft_id(&self) -> String {
+        self.config.oracle_pool_nft_id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn update_nft_id(&self) -> String {
+        self.config.update_nft_id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn update_ballot_token_id(&self) -> String {
+        self.config.update_ballot_token_id.clone()
     }
 
     #[wasm_bindgen]
@@ -81,6 +146,9 @@ impl StableCoinProtocol {
         bank_box: &BankBox,
         total_input_nano_ergs: NanoErg,
         implementor_address: ErgoAddressString,
+        max_total_cost: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<String, JsValue> {
         // Creating a placeholder box which holds an amount of nanoErgs equal to
         // `total_input_nano_ergs` so the `UnsignedTransaction` can be created
@@ -93,14 +161,17 @@ impl StableCoinProtocol {
         }
         let unsigned_tx = self
             .action_mint_reservecoin(
-                amount_to_mint.clone(),
+                amount_to_mint,
                 user_address,
                 transaction_fee,
                 current_height,
-                &oracle_box,
-                &bank_box,
+                oracle_box,
+                bank_box,
                 &ergs_boxes,
                 implementor_address,
+                max_total_cost,
+                expected_reserve_ratio,
+                max_reserve_ratio_drift,
             )
             .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
         Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
@@ -119,6 +190,9 @@ impl StableCoinProtocol {
         bank_box: &BankBox,
         total_input_nano_ergs: NanoErg,
         implementor_address: ErgoAddressString,
+        max_total_cost: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<String, JsValue> {
         let mut ergs_boxes = vec![];
         if let Some(placeholder_box) =
@@ -128,14 +202,17 @@ impl StableCoinProtocol {
         }
         let unsigned_tx = self
             .action_mint_stablecoin(
-                amount_to_mint.clone(),
+                amount_to_mint,
                 user_address,
                 transaction_fee,
                 current_height,
-                &oracle_box,
-                &bank_box,
+                oracle_box,
+                bank_box,
                 &ergs_boxes,
                 implementor_address,
+                max_total_cost,
+                expected_reserve_ratio,
+                max_reserve_ratio_drift,
             )
             .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
         Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
@@ -153,12 +230,16 @@ impl StableCoinProtocol {
         oracle_box: &ErgUsdOraclePoolBox,
         bank_box: &BankBox,
         implementor_address: ErgoAddressString,
+        min_total_received: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<String, JsValue> {
         // Creating a placeholder box which holds an amount of ReserveCoins equal to
         // `amount_being_redeemed` so the `UnsignedTransaction` can be created
         // and then converted and used for an Assembler spec.
         let mut boxes = vec![];
-        if let Some(placeholder_box) = ReserveCoinBox::create_placeholder_box(amount_being_redeemed)
+        if let Some(placeholder_box) =
+            ReserveCoinBox::create_placeholder_box(amount_being_redeemed, &self.config)
         {
             boxes.push(placeholder_box)
         }
@@ -168,10 +249,13 @@ impl StableCoinProtocol {
                 user_address,
                 transaction_fee,
                 current_height,
-                &oracle_box,
-                &bank_box,
+                oracle_box,
+                bank_box,
                 &boxes,
                 implementor_address,
+                min_total_received,
+                expected_reserve_ratio,
+                max_reserve_ratio_drift,
             )
             .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
         Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
@@ -189,12 +273,16 @@ impl StableCoinProtocol {
         oracle_box: &ErgUsdOraclePoolBox,
         bank_box: &BankBox,
         implementor_address: ErgoAddressString,
+        min_total_received: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<String, JsValue> {
         // Creating a placeholder box which holds an amount of StableCoins equal to
         // `amount_being_redeemed` so the `UnsignedTransaction` can be created
         // and then converted and used for an Assembler spec.
         let mut boxes = vec![];
-        if let Some(placeholder_box) = StableCoinBox::create_placeholder_box(amount_being_redeemed)
+        if let Some(placeholder_box) =
+            StableCoinBox::create_placeholder_box(amount_being_redeemed, &self.config)
         {
             boxes.push(placeholder_box)
         }
@@ -204,10 +292,13 @@ impl StableCoinProtocol {
                 user_address,
                 transaction_fee,
                 current_height,
-                &oracle_box,
-                &bank_box,
+                oracle_box,
+                bank_box,
                 &boxes,
                 implementor_address,
+                min_total_received,
+                expected_reserve_ratio,
+                max_reserve_ratio_drift,
             )
             .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
         Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
@@ -226,20 +317,26 @@ impl StableCoinProtocol {
         bank_box: &BankBox,
         ergo_boxes: &ErgoBoxes,
         implementor_address: ErgoAddressString,
+        max_total_cost: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<WUnsignedTransaction, JsValue> {
         let ergs_boxes: Vec<ErgsBox> = ErgsBox::convert_from_ergo_boxes(ergo_boxes)
             .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
 
         let unsigned_tx = self
             .action_mint_reservecoin(
-                amount_to_mint.clone(),
+                amount_to_mint,
                 user_address,
                 transaction_fee,
                 current_height,
-                &oracle_box,
-                &bank_box,
+                oracle_box,
+                bank_box,
                 &ergs_boxes,
                 implementor_address,
+                max_total_cost,
+                expected_reserve_ratio,
+                max_reserve_ratio_drift,
             )
             .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
 
@@ -259,20 +356,26 @@ impl StableCoinProtocol {
         bank_box: &BankBox,
         ergo_boxes: &ErgoBoxes,
         implementor_address: ErgoAddressString,
+        max_total_cost: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<WUnsignedTransaction, JsValue> {
         let ergs_boxes: Vec<ErgsBox> = ErgsBox::convert_from_ergo_boxes(ergo_boxes)
             .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
 
         let unsigned_tx = self
             .action_mint_stablecoin(
-                amount_to_mint.clone(),
+                amount_to_mint,
                 user_address,
                 transaction_fee,
                 current_height,
-                &oracle_box,
-                &bank_box,
+                oracle_box,
+                bank_box,
                 &ergs_boxes,
                 implementor_address,
+                max_total_cost,
+                expected_reserve_ratio,
+                max_reserve_ratio_drift,
             )
             .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
 
@@ -292,20 +395,27 @@ impl StableCoinProtocol {
         bank_box: &BankBox,
         rc_boxes: &ErgoBoxes,
         implementor_address: ErgoAddressString,
+        min_total_received: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<WUnsignedTransaction, JsValue> {
-        let rc_boxes: Vec<ReserveCoinBox> = ReserveCoinBox::convert_from_ergo_boxes(rc_boxes)
-            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+        let rc_boxes: Vec<ReserveCoinBox> =
+            ReserveCoinBox::convert_from_ergo_boxes(rc_boxes, &self.config)
+                .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
 
         let unsigned_tx = self
             .action_redeem_reservecoin(
-                amount_to_redeem.clone(),
+                amount_to_redeem,
                 user_address,
                 transaction_fee,
                 current_height,
-                &oracle_box,
-                &bank_box,
+                oracle_box,
+                bank_box,
                 &rc_boxes,
                 implementor_address,
+                min_total_received,
+                expected_reserve_ratio,
+                max_reserve_ratio_drift,
             )
             .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
 
@@ -325,18 +435,150 @@ impl StableCoinProtocol {
         bank_box: &BankBox,
         sc_boxes: &ErgoBoxes,
         implementor_address: ErgoAddressString,
+        min_total_received: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<WUnsignedTransaction, JsValue> {
-        let sc_boxes: Vec<StableCoinBox> = StableCoinBox::convert_from_ergo_boxes(sc_boxes)
-            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+        let sc_boxes: Vec<StableCoinBox> =
+            StableCoinBox::convert_from_ergo_boxes(sc_boxes, &self.config)
+                .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
 
         let unsigned_tx = self
             .action_redeem_stablecoin(
-                amount_to_redeem.clone(),
+                amount_to_redeem,
                 user_address,
                 transaction_fee,
                 current_height,
-                &oracle_box,
-                &bank_box,
+                oracle_box,
+                bank_box,
+                &sc_boxes,
+                implementor_address,
+                min_total_received,
+                expected_reserve_ratio,
+                max_reserve_ratio_drift,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        Ok(unsigned_tx.into())
+    }
+    #[wasm_bindgen]
+    /// Action: Mint ReserveCoin by providing Ergs, using a previously acquired `Quote`.
+    /// This is the WASM wrapper function for said Action.
+    pub fn w_action_mint_reservecoin_with_quote(
+        &self,
+        quote: &Quote,
+        user_address: P2PKAddressString,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        ergo_boxes: &ErgoBoxes,
+        implementor_address: ErgoAddressString,
+    ) -> Result<WUnsignedTransaction, JsValue> {
+        let ergs_boxes: Vec<ErgsBox> = ErgsBox::convert_from_ergo_boxes(ergo_boxes)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        let unsigned_tx = self
+            .action_mint_reservecoin_with_quote(
+                quote,
+                user_address,
+                current_height,
+                oracle_box,
+                bank_box,
+                &ergs_boxes,
+                implementor_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        Ok(unsigned_tx.into())
+    }
+
+    #[wasm_bindgen]
+    /// Action: Mint StableCoins by providing Ergs, using a previously acquired `Quote`.
+    /// This is the WASM wrapper function for said Action.
+    pub fn w_action_mint_stablecoin_with_quote(
+        &self,
+        quote: &Quote,
+        user_address: P2PKAddressString,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        ergo_boxes: &ErgoBoxes,
+        implementor_address: ErgoAddressString,
+    ) -> Result<WUnsignedTransaction, JsValue> {
+        let ergs_boxes: Vec<ErgsBox> = ErgsBox::convert_from_ergo_boxes(ergo_boxes)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        let unsigned_tx = self
+            .action_mint_stablecoin_with_quote(
+                quote,
+                user_address,
+                current_height,
+                oracle_box,
+                bank_box,
+                &ergs_boxes,
+                implementor_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        Ok(unsigned_tx.into())
+    }
+
+    #[wasm_bindgen]
+    /// Action: Redeem ReserveCoins for Ergs, using a previously acquired `Quote`.
+    /// This is the WASM wrapper function for said Action.
+    pub fn w_action_redeem_reservecoin_with_quote(
+        &self,
+        quote: &Quote,
+        user_address: P2PKAddressString,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        rc_boxes: &ErgoBoxes,
+        implementor_address: ErgoAddressString,
+    ) -> Result<WUnsignedTransaction, JsValue> {
+        let rc_boxes: Vec<ReserveCoinBox> =
+            ReserveCoinBox::convert_from_ergo_boxes(rc_boxes, &self.config)
+                .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        let unsigned_tx = self
+            .action_redeem_reservecoin_with_quote(
+                quote,
+                user_address,
+                current_height,
+                oracle_box,
+                bank_box,
+                &rc_boxes,
+                implementor_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        Ok(unsigned_tx.into())
+    }
+
+    #[wasm_bindgen]
+    /// Action: Redeem StableCoins for Ergs, using a previously acquired `Quote`.
+    /// This is the WASM wrapper function for said Action.
+    pub fn w_action_redeem_stablecoin_with_quote(
+        &self,
+        quote: &Quote,
+        user_address: P2PKAddressString,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        sc_boxes: &ErgoBoxes,
+        implementor_address: ErgoAddressString,
+    ) -> Result<WUnsignedTransaction, JsValue> {
+        let sc_boxes: Vec<StableCoinBox> =
+            StableCoinBox::convert_from_ergo_boxes(sc_boxes, &self.config)
+                .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        let unsigned_tx = self
+            .action_redeem_stablecoin_with_quote(
+                quote,
+                user_address,
+                current_height,
+                oracle_box,
+                bank_box,
                 &sc_boxes,
                 implementor_address,
             )
@@ -348,7 +590,10 @@ impl StableCoinProtocol {
 
 /// Rust methods related to `StableCoinProtocol`
 impl StableCoinProtocol {
-    /// Action: Mint ReserveCoin by providing Ergs
+    /// Action: Mint ReserveCoin by providing Ergs.
+    /// Errors with `SlippageExceeded` if `max_total_cost` or
+    /// `max_reserve_ratio_drift` are provided and breached. The drift is
+    /// measured from `expected_reserve_ratio`, the quoted reserve ratio.
     pub fn action_mint_reservecoin(
         &self,
         amount_to_mint: u64,
@@ -359,27 +604,84 @@ impl StableCoinProtocol {
         bank_box: &BankBox,
         ergs_boxes: &Vec<ErgsBox>,
         implementor_address: ErgoAddressString,
+        max_total_cost: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<UnsignedTransaction, ProtocolError> {
+        let quote = bank_box.quote_mint_reservecoin(
+            amount_to_mint,
+            transaction_fee,
+            current_height,
+            oracle_box,
+            &self.config,
+        )?;
+        // Protect the user from the price moving since they were quoted
+        quote.verify_max_total_cost(max_total_cost)?;
+        quote.verify_reserve_ratio_drift(expected_reserve_ratio, max_reserve_ratio_drift)?;
+        self.action_mint_reservecoin_with_quote(
+            &quote,
+            user_address,
+            current_height,
+            oracle_box,
+            bank_box,
+            ergs_boxes,
+            implementor_address,
+        )
+    }
+
+    /// Action: Mint ReserveCoin by providing Ergs.
+    /// Built from the amounts of a previously acquired `Quote`. Errors if
+    /// the `Quote` no longer matches the current state of the protocol.
+    pub fn action_mint_reservecoin_with_quote(
+        &self,
+        quote: &Quote,
+        user_address: P2PKAddressString,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        ergs_boxes: &Vec<ErgsBox>,
+        implementor_address: ErgoAddressString,
+    ) -> Result<UnsignedTransaction, ProtocolError> {
+        // Verify the `Quote` still matches the current state of the protocol
+        let current_quote = bank_box.quote_mint_reservecoin(
+            quote.amount,
+            quote.transaction_fee,
+            current_height,
+            oracle_box,
+            &self.config,
+        )?;
+        quote.verify_matches(&current_quote)?;
+        let amount_to_mint = quote.amount;
+        let transaction_fee = quote.transaction_fee;
+
         //
         // Defining useful values
         //
         // Total ergs inside of `ergs_boxes`
-        let input_ergs_total = ErgsBox::sum_ergs_boxes_value(&ergs_boxes);
+        let input_ergs_total = ErgsBox::sum_ergs_boxes_value(ergs_boxes);
         // Number of ReserveCoins in circulation currently/in inputs
         let circulating_reservecoins_in = bank_box.num_circulating_reservecoins();
         // Amount of Ergs needed to cover amount_to_mint
-        let reservecoin_value_in_base =
-            bank_box.base_cost_to_mint_reservecoin(amount_to_mint, oracle_box);
+        let reservecoin_value_in_base = quote.value_in_base;
         // Amount to pay out implementor.
-        let implementor_fee = (reservecoin_value_in_base as f64 * IMPLEMENTOR_FEE_PERCENT) as u64;
+        let implementor_fee = quote.implementor_fee;
         // New ReserveCoins in circulation after minting
-        let circulating_reservecoins_out = circulating_reservecoins_in + amount_to_mint;
+        let circulating_reservecoins_out = checked_add(
+            circulating_reservecoins_in,
+            amount_to_mint,
+            "circulating ReserveCoins",
+        )?;
 
         //
         // Performing Checks
         //
         // Ensure Reserve Ratio is below maximum
-        if !bank_box.able_to_mint_reservecoin_amount(oracle_box, amount_to_mint, current_height) {
+        if !bank_box.able_to_mint_reservecoin_amount(
+            oracle_box,
+            amount_to_mint,
+            current_height,
+            &self.config,
+        ) {
             return Err(ProtocolError::InvalidReserveRatio());
         }
         // Ensure more than 0 ReserveCoins are attempted to be minted
@@ -390,15 +692,18 @@ impl StableCoinProtocol {
         }
 
         // Verify that at least 1 ErgsBox was provided
-        if ergs_boxes.len() == 0 {
+        if ergs_boxes.is_empty() {
             return Err(ProtocolError::InsufficientNumberOfBoxes());
         }
         // Verify that the provided ergs_boxes hold sufficient nanoErgs to
         // cover the minting, the tx fee, and to have MIN_BOX_VALUE in the
         // Receipt box.
-        if input_ergs_total
-            < (reservecoin_value_in_base + transaction_fee + self.min_box_value() + implementor_fee)
-        {
+        let required_nano_ergs = [transaction_fee, self.min_box_value(), implementor_fee]
+            .iter()
+            .try_fold(reservecoin_value_in_base, |acc, v| {
+                checked_add(acc, *v, "required nanoErgs")
+            })?;
+        if input_ergs_total < required_nano_ergs {
             return Err(ProtocolError::InsufficientNanoErgs(
                 reservecoin_value_in_base,
             ));
@@ -409,7 +714,7 @@ impl StableCoinProtocol {
         //
         // Define the tx input boxes
         let mut tx_input_boxes = vec![bank_box.get_box()];
-        tx_input_boxes.append(&mut ergs_boxes.into_iter().map(|b| b.get_box()).collect());
+        tx_input_boxes.append(&mut ergs_boxes.iter().map(|b| b.get_box()).collect());
         // Convert them into `UnsignedInput`s
         let tx_inputs: Vec<UnsignedInput> = tx_input_boxes
             .clone()
@@ -428,7 +733,8 @@ impl StableCoinProtocol {
             current_height,
             circulating_reservecoins_out,
             reservecoin_value_in_base,
-            &bank_box,
+            bank_box,
+            &self.config,
         )?;
 
         // Create the Receipt box candidate
@@ -437,7 +743,7 @@ impl StableCoinProtocol {
             &user_address,
             current_height,
             transaction_fee,
-            implementor_fee,
+            &self.config,
             reservecoin_value_in_base,
             bank_box,
             input_ergs_total,
@@ -465,6 +771,16 @@ impl StableCoinProtocol {
             current_height,
         )?;
 
+        // Verify the Bank contract accepts the output Bank & Receipt boxes
+        validate_bank_transition(
+            bank_box,
+            oracle_box,
+            &output_bank_candidate,
+            &receipt_box_candidate,
+            current_height,
+            &self.config,
+        )?;
+
         //
         // Creating the UnsignedTransaction
         //
@@ -483,7 +799,10 @@ impl StableCoinProtocol {
         Ok(unsigned_tx)
     }
 
-    /// Action: Mint StableCoin by providing Ergs
+    /// Action: Mint StableCoin by providing Ergs.
+    /// Errors with `SlippageExceeded` if `max_total_cost` or
+    /// `max_reserve_ratio_drift` are provided and breached. The drift is
+    /// measured from `expected_reserve_ratio`, the quoted reserve ratio.
     pub fn action_mint_stablecoin(
         &self,
         amount_to_mint: u64,
@@ -494,27 +813,77 @@ impl StableCoinProtocol {
         bank_box: &BankBox,
         ergs_boxes: &Vec<ErgsBox>,
         implementor_address: ErgoAddressString,
+        max_total_cost: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
+    ) -> Result<UnsignedTransaction, ProtocolError> {
+        let quote = bank_box.quote_mint_stablecoin(
+            amount_to_mint,
+            transaction_fee,
+            oracle_box,
+            &self.config,
+        )?;
+        // Protect the user from the price moving since they were quoted
+        quote.verify_max_total_cost(max_total_cost)?;
+        quote.verify_reserve_ratio_drift(expected_reserve_ratio, max_reserve_ratio_drift)?;
+        self.action_mint_stablecoin_with_quote(
+            &quote,
+            user_address,
+            current_height,
+            oracle_box,
+            bank_box,
+            ergs_boxes,
+            implementor_address,
+        )
+    }
+
+    /// Action: Mint StableCoin by providing Ergs.
+    /// Built from the amounts of a previously acquired `Quote`. Errors if
+    /// the `Quote` no longer matches the current state of the protocol.
+    pub fn action_mint_stablecoin_with_quote(
+        &self,
+        quote: &Quote,
+        user_address: P2PKAddressString,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        ergs_boxes: &Vec<ErgsBox>,
+        implementor_address: ErgoAddressString,
     ) -> Result<UnsignedTransaction, ProtocolError> {
+        // Verify the `Quote` still matches the current state of the protocol
+        let current_quote = bank_box.quote_mint_stablecoin(
+            quote.amount,
+            quote.transaction_fee,
+            oracle_box,
+            &self.config,
+        )?;
+        quote.verify_matches(&current_quote)?;
+        let amount_to_mint = quote.amount;
+        let transaction_fee = quote.transaction_fee;
+
         //
         // Defining useful values
         //
         // Total ergs inside of `ergs_boxes`
-        let input_ergs_total = ErgsBox::sum_ergs_boxes_value(&ergs_boxes);
+        let input_ergs_total = ErgsBox::sum_ergs_boxes_value(ergs_boxes);
         // Number of StableCoins in circulation currently/in inputs
         let circulating_stablecoins_in = bank_box.num_circulating_stablecoins();
         // Amount of Ergs needed to cover amount_to_mint
-        let stablecoin_value_in_base =
-            bank_box.base_cost_to_mint_stablecoin(amount_to_mint, oracle_box);
+        let stablecoin_value_in_base = quote.value_in_base;
         // Amount to pay out implementor.
-        let implementor_fee = (stablecoin_value_in_base as f64 * IMPLEMENTOR_FEE_PERCENT) as u64;
+        let implementor_fee = quote.implementor_fee;
         // New stablecoin in circulation after minting
-        let circulating_stablecoins_out = circulating_stablecoins_in + amount_to_mint;
+        let circulating_stablecoins_out = checked_add(
+            circulating_stablecoins_in,
+            amount_to_mint,
+            "circulating StableCoins",
+        )?;
 
         //
         // Performing Checks
         //
         // Ensure Reserve Ratio is above minimum
-        if !bank_box.able_to_mint_stablecoin_amount(oracle_box, amount_to_mint) {
+        if !bank_box.able_to_mint_stablecoin_amount(oracle_box, amount_to_mint, &self.config) {
             return Err(ProtocolError::InvalidReserveRatio());
         }
         // Ensure more than 0 StableCoins are attempted to be minted
@@ -524,18 +893,23 @@ impl StableCoinProtocol {
             ));
         }
         // Verify that at least 1 ErgsBox was provided
-        if ergs_boxes.len() == 0 {
+        if ergs_boxes.is_empty() {
             return Err(ProtocolError::InsufficientNumberOfBoxes());
         }
         // Verify that the provided ergs_boxes hold sufficient nanoErgs to
         // cover the minting, the tx fee, and to have MIN_BOX_VALUE in the
         // Receipt box.
-        if input_ergs_total
-            < (stablecoin_value_in_base
-                + transaction_fee
-                + (self.min_box_value() * 2)
-                + implementor_fee)
-        {
+        let required_nano_ergs = [
+            transaction_fee,
+            self.min_box_value(),
+            self.min_box_value(),
+            implementor_fee,
+        ]
+        .iter()
+        .try_fold(stablecoin_value_in_base, |acc, v| {
+            checked_add(acc, *v, "required nanoErgs")
+        })?;
+        if input_ergs_total < required_nano_ergs {
             return Err(ProtocolError::InsufficientNanoErgs(
                 stablecoin_value_in_base,
             ));
@@ -546,7 +920,7 @@ impl StableCoinProtocol {
         //
         // Define the tx input boxes
         let mut tx_input_boxes = vec![bank_box.get_box()];
-        tx_input_boxes.append(&mut ergs_boxes.into_iter().map(|b| b.get_box()).collect());
+        tx_input_boxes.append(&mut ergs_boxes.iter().map(|b| b.get_box()).collect());
         // Convert them into `UnsignedInput`s
         let tx_inputs: Vec<UnsignedInput> = tx_input_boxes
             .clone()
@@ -565,7 +939,8 @@ impl StableCoinProtocol {
             current_height,
             circulating_stablecoins_out,
             stablecoin_value_in_base,
-            &bank_box,
+            bank_box,
+            &self.config,
         )?;
 
         // Create the Receipt box candidate
@@ -574,7 +949,7 @@ impl StableCoinProtocol {
             &user_address,
             current_height,
             transaction_fee,
-            implementor_fee,
+            &self.config,
             stablecoin_value_in_base,
             bank_box,
             input_ergs_total,
@@ -602,6 +977,16 @@ impl StableCoinProtocol {
             current_height,
         )?;
 
+        // Verify the Bank contract accepts the output Bank & Receipt boxes
+        validate_bank_transition(
+            bank_box,
+            oracle_box,
+            &output_bank_candidate,
+            &receipt_box_candidate,
+            current_height,
+            &self.config,
+        )?;
+
         //
         // Creating the UnsignedTransaction
         //
@@ -620,7 +1005,10 @@ impl StableCoinProtocol {
         Ok(unsigned_tx)
     }
 
-    /// Action: Redeem ReserveCoin for Ergs
+    /// Action: Redeem ReserveCoin for Ergs.
+    /// Errors with `SlippageExceeded` if `min_total_received` or
+    /// `max_reserve_ratio_drift` are provided and breached. The drift is
+    /// measured from `expected_reserve_ratio`, the quoted reserve ratio.
     pub fn action_redeem_reservecoin(
         &self,
         amount_to_redeem: u64,
@@ -629,44 +1017,91 @@ impl StableCoinProtocol {
         current_height: BlockHeight,
         oracle_box: &ErgUsdOraclePoolBox,
         bank_box: &BankBox,
-        rc_boxes: &Vec<ReserveCoinBox>,
+        rc_boxes: &[ReserveCoinBox],
         implementor_address: ErgoAddressString,
+        min_total_received: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
     ) -> Result<UnsignedTransaction, ProtocolError> {
+        let quote = bank_box.quote_redeem_reservecoin(
+            amount_to_redeem,
+            transaction_fee,
+            oracle_box,
+            &self.config,
+        )?;
+        // Protect the user from the price moving since they were quoted
+        quote.verify_min_total_received(min_total_received)?;
+        quote.verify_reserve_ratio_drift(expected_reserve_ratio, max_reserve_ratio_drift)?;
+        self.action_redeem_reservecoin_with_quote(
+            &quote,
+            user_address,
+            current_height,
+            oracle_box,
+            bank_box,
+            rc_boxes,
+            implementor_address,
+        )
+    }
+
+    /// Action: Redeem ReserveCoin for Ergs.
+    /// Built from the amounts of a previously acquired `Quote`. Errors if
+    /// the `Quote` no longer matches the current state of the protocol.
+    pub fn action_redeem_reservecoin_with_quote(
+        &self,
+        quote: &Quote,
+        user_address: P2PKAddressString,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        rc_boxes: &[ReserveCoinBox],
+        implementor_address: ErgoAddressString,
+    ) -> Result<UnsignedTransaction, ProtocolError> {
+        // Verify the `Quote` still matches the current state of the protocol
+        let current_quote = bank_box.quote_redeem_reservecoin(
+            quote.amount,
+            quote.transaction_fee,
+            oracle_box,
+            &self.config,
+        )?;
+        quote.verify_matches(&current_quote)?;
+        let amount_to_redeem = quote.amount;
+        let transaction_fee = quote.transaction_fee;
+
         //
         // Defining useful values
         //
         // The total number of ReserveCoins provided as inputs in rc_boxes
-        let input_reservecoins_total = ReserveCoinBox::sum_token_amount(&rc_boxes);
+        let input_reservecoins_total = ReserveCoinBox::sum_token_amount(rc_boxes, &self.config);
         // Oracle datapoint
-        let oracle_rate = oracle_box.datapoint_in_cents();
+        let oracle_rate = bank_box.oracle_rate(oracle_box, &self.config);
         // Erg Reserves in Bank Box
-        let base_reserves_in = bank_box.base_reserves();
+        let base_reserves_in = bank_box.base_reserves(&self.config);
         // Number of ReserveCoins in circulation currently/in inputs
         let circulating_reservecoins_in = bank_box.num_circulating_reservecoins();
         // Amount of Ergs the user will receive based on amount_to_redeem
-        let reservecoin_value_in_base =
-            bank_box.base_amount_from_redeeming_reservecoin(amount_to_redeem, oracle_box);
+        let reservecoin_value_in_base = quote.value_in_base;
         // Amount to pay out implementor.
-        let implementor_fee = (reservecoin_value_in_base as f64 * IMPLEMENTOR_FEE_PERCENT) as u64;
+        let implementor_fee = quote.implementor_fee;
         // Check that sufficient number of circulating ReserveCoins
 
         if circulating_reservecoins_in < amount_to_redeem {
             return Err(ProtocolError::InsufficientReserveCoins(amount_to_redeem));
         }
         // Check that sufficient number of ReserveCoins are circulating
-        if !bank_box.able_to_redeem_reservecoin_amount(oracle_box, amount_to_redeem) {
+        if !bank_box.able_to_redeem_reservecoin_amount(oracle_box, amount_to_redeem, &self.config) {
             return Err(ProtocolError::InsufficientBaseReserves(base_reserves_in));
         }
         // New Base Reserves Total After Redeeming
-        let base_reserves_out = base_reserves_in - reservecoin_value_in_base;
+        let base_reserves_out =
+            checked_sub(base_reserves_in, reservecoin_value_in_base, "base reserves")?;
         // New ReserveCoins in circulation after redeeming
         let circulating_reservecoins_out = circulating_reservecoins_in - amount_to_redeem;
         // The new reserve ratio that will be in the output Bank box
-        let mut reserve_ratio_out = reserve_ratio(
+        let mut reserve_ratio_out = checked_reserve_ratio(
             base_reserves_out,
             bank_box.num_circulating_stablecoins(),
             oracle_rate,
-        );
+        )?;
         // If number of circulating stablecoins == 0 then set reserve ratio
         // to max in order to allow redeeming.
         if bank_box.num_circulating_stablecoins() == 0 {
@@ -676,8 +1111,8 @@ impl StableCoinProtocol {
         //
         // Performing Checks
         //
-        // // Ensure output Reserve Ratio is above minimum
-        if reserve_ratio_out <= self.min_reserve_ratio() {
+        // // Ensure output Reserve Ratio is at or above minimum
+        if reserve_ratio_out < self.min_reserve_ratio() {
             return Err(ProtocolError::InvalidReserveRatio());
         }
         // Ensure more than 0 ReserveCoins are attempted to be redeemed
@@ -687,7 +1122,7 @@ impl StableCoinProtocol {
             ));
         }
         // Verify that at least 1 ErgsBox was provided
-        if rc_boxes.len() == 0 {
+        if rc_boxes.is_empty() {
             return Err(ProtocolError::InsufficientNumberOfBoxes());
         }
         // Verify that the provided rc_boxes hold sufficient ReserveCoins to
@@ -702,7 +1137,7 @@ impl StableCoinProtocol {
 
         // Define the tx input boxes
         let mut tx_input_boxes = vec![bank_box.get_box()];
-        tx_input_boxes.append(&mut rc_boxes.into_iter().map(|b| b.get_box()).collect());
+        tx_input_boxes.append(&mut rc_boxes.iter().map(|b| b.get_box()).collect());
         // Convert them into `UnsignedInput`s
         let tx_inputs: Vec<UnsignedInput> = tx_input_boxes
             .clone()
@@ -721,7 +1156,8 @@ impl StableCoinProtocol {
             current_height,
             circulating_reservecoins_out,
             reservecoin_value_in_base,
-            &bank_box,
+            bank_box,
+            &self.config,
         )?;
 
         // The Receipt box
@@ -735,7 +1171,7 @@ impl StableCoinProtocol {
             bank_box,
             rc_boxes,
             &no_bank_inputs,
-            implementor_fee,
+            &self.config,
         )?;
 
         // Create the Transaction Fee box candidate
@@ -751,6 +1187,16 @@ impl StableCoinProtocol {
             current_height,
         )?;
 
+        // Verify the Bank contract accepts the output Bank & Receipt boxes
+        validate_bank_transition(
+            bank_box,
+            oracle_box,
+            &output_bank_candidate,
+            &receipt_box_candidate,
+            current_height,
+            &self.config,
+        )?;
+
         //
         // Creating the UnsignedTransaction
         //
@@ -768,7 +1214,10 @@ impl StableCoinProtocol {
         Ok(unsigned_tx)
     }
 
-    /// Action: Redeem StableCoin for Ergs
+    /// Action: Redeem StableCoin for Ergs.
+    /// Errors with `SlippageExceeded` if `min_total_received` or
+    /// `max_reserve_ratio_drift` are provided and breached. The drift is
+    /// measured from `expected_reserve_ratio`, the quoted reserve ratio.
     pub fn action_redeem_stablecoin(
         &self,
         amount_to_redeem: u64,
@@ -777,23 +1226,69 @@ impl StableCoinProtocol {
         current_height: BlockHeight,
         oracle_box: &ErgUsdOraclePoolBox,
         bank_box: &BankBox,
-        sc_boxes: &Vec<StableCoinBox>,
+        sc_boxes: &[StableCoinBox],
+        implementor_address: ErgoAddressString,
+        min_total_received: Option<NanoErg>,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
+    ) -> Result<UnsignedTransaction, ProtocolError> {
+        let quote = bank_box.quote_redeem_stablecoin(
+            amount_to_redeem,
+            transaction_fee,
+            oracle_box,
+            &self.config,
+        )?;
+        // Protect the user from the price moving since they were quoted
+        quote.verify_min_total_received(min_total_received)?;
+        quote.verify_reserve_ratio_drift(expected_reserve_ratio, max_reserve_ratio_drift)?;
+        self.action_redeem_stablecoin_with_quote(
+            &quote,
+            user_address,
+            current_height,
+            oracle_box,
+            bank_box,
+            sc_boxes,
+            implementor_address,
+        )
+    }
+
+    /// Action: Redeem StableCoin for Ergs.
+    /// Built from the amounts of a previously acquired `Quote`. Errors if
+    /// the `Quote` no longer matches the current state of the protocol.
+    pub fn action_redeem_stablecoin_with_quote(
+        &self,
+        quote: &Quote,
+        user_address: P2PKAddressString,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        bank_box: &BankBox,
+        sc_boxes: &[StableCoinBox],
         implementor_address: ErgoAddressString,
     ) -> Result<UnsignedTransaction, ProtocolError> {
+        // Verify the `Quote` still matches the current state of the protocol
+        let current_quote = bank_box.quote_redeem_stablecoin(
+            quote.amount,
+            quote.transaction_fee,
+            oracle_box,
+            &self.config,
+        )?;
+        quote.verify_matches(&current_quote)?;
+        let amount_to_redeem = quote.amount;
+        let transaction_fee = quote.transaction_fee;
+
         //
         // Defining useful values
         //
         // The total number of StableCoins provided as inputs in rc_boxes
-        let input_stablecoins_total = StableCoinBox::sum_token_amount(&sc_boxes);
+        let input_stablecoins_total = StableCoinBox::sum_token_amount(sc_boxes, &self.config);
         // Base Reserves of Bank
-        let base_reserves_in = bank_box.base_reserves();
+        let base_reserves_in = bank_box.base_reserves(&self.config);
         // Number of StableCoins in circulation currently/in inputs
         let circulating_stablecoins_in = bank_box.num_circulating_stablecoins();
         // Amount of Ergs the user will receive based on amount_to_redeem
-        let stablecoin_value_in_base =
-            bank_box.base_amount_from_redeeming_stablecoin(amount_to_redeem, oracle_box);
+        let stablecoin_value_in_base = quote.value_in_base;
         // Amount to pay out implementor.
-        let implementor_fee = (stablecoin_value_in_base as f64 * IMPLEMENTOR_FEE_PERCENT) as u64;
+        let implementor_fee = quote.implementor_fee;
 
         // Check that sufficient number of StableCoins are circulating
         if circulating_stablecoins_in < amount_to_redeem {
@@ -816,7 +1311,7 @@ impl StableCoinProtocol {
             ));
         }
         // Verify that at least 1 ErgsBox was provided
-        if sc_boxes.len() == 0 {
+        if sc_boxes.is_empty() {
             return Err(ProtocolError::InsufficientNumberOfBoxes());
         }
         // Verify that the provided sc_boxes hold sufficient StableCoins to
@@ -830,7 +1325,7 @@ impl StableCoinProtocol {
         //
         // Define the tx input boxes
         let mut tx_input_boxes = vec![bank_box.get_box()];
-        tx_input_boxes.append(&mut sc_boxes.into_iter().map(|scb| scb.get_box()).collect());
+        tx_input_boxes.append(&mut sc_boxes.iter().map(|scb| scb.get_box()).collect());
         // Convert them into `UnsignedInput`s
         let tx_inputs: Vec<UnsignedInput> = tx_input_boxes
             .clone()
@@ -849,7 +1344,8 @@ impl StableCoinProtocol {
             current_height,
             circulating_stablecoins_out,
             stablecoin_value_in_base,
-            &bank_box,
+            bank_box,
+            &self.config,
         )?;
 
         // The Receipt box
@@ -863,7 +1359,7 @@ impl StableCoinProtocol {
             bank_box,
             sc_boxes,
             &no_bank_inputs,
-            implementor_fee,
+            &self.config,
         )?;
 
         // Create the Transaction Fee box candidate
@@ -879,6 +1375,16 @@ impl StableCoinProtocol {
             current_height,
         )?;
 
+        // Verify the Bank contract accepts the output Bank & Receipt boxes
+        validate_bank_transition(
+            bank_box,
+            oracle_box,
+            &output_bank_candidate,
+            &receipt_box_candidate,
+            current_height,
+            &self.config,
+        )?;
+
         //
         // Creating the UnsignedTransaction
         //
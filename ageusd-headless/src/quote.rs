@@ -0,0 +1,585 @@
+// This file holds the `Quote`, a breakdown of everything that will happen
+// when performing a minting/redeeming Action with the current state of the
+// Bank box. The Actions in `protocol.rs` are built from a `Quote`, so the
+// numbers displayed to a user are exactly the ones used in the transaction.
+use crate::bank::BankBox;
+use crate::equations::checked_reserve_ratio;
+use crate::error::{ProtocolError, Result};
+use crate::parameters::ProtocolConfig;
+use ergo_headless_dapp_framework::{BlockHeight, ErgUsdOraclePoolBox, NanoErg};
+use wasm_bindgen::prelude::*;
+
+/// The minting/redeeming Actions which can be quoted
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteAction {
+    MintStableCoin,
+    MintReserveCoin,
+    RedeemStableCoin,
+    RedeemReserveCoin,
+}
+
+/// A breakdown of the costs/proceeds of a minting/redeeming Action
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quote {
+    /// The Action being quoted
+    pub action: QuoteAction,
+    /// The number of StableCoins/ReserveCoins being minted/redeemed
+    pub amount: u64,
+    /// The nominal value of the coins without any fees
+    pub feeless_amount: NanoErg,
+    /// The fee charged by the Bank contract
+    pub protocol_fee: NanoErg,
+    /// The fee paid to the frontend implementor
+    pub implementor_fee: NanoErg,
+    /// The fee paid to the miner
+    pub transaction_fee: NanoErg,
+    /// The nanoErgs which must be locked in the min value of output boxes
+    pub min_box_overhead: NanoErg,
+    /// The nanoErgs added to (minting) or removed from (redeeming) the Bank box
+    pub value_in_base: NanoErg,
+    /// The total nanoErgs paid (minting) or received (redeeming) by the user
+    pub total: NanoErg,
+    /// The reserve ratio of the Bank box before the Action
+    pub reserve_ratio_before: u64,
+    /// The reserve ratio of the Bank box after the Action
+    pub reserve_ratio_after: u64,
+    /// The total nanoErgs paid/received per coin
+    pub effective_price: NanoErg,
+    /// Whether the Action is currently allowed by the protocol
+    pub allowed: bool,
+}
+
+/// Rust methods related to `Quote`
+impl Quote {
+    /// Verify that the nanoErg amounts of the `Quote` are identical to those
+    /// of `current`, a `Quote` freshly built from the current state.
+    pub fn verify_matches(&self, current: &Quote) -> Result<()> {
+        let fields = [
+            ("amount", self.amount, current.amount),
+            ("value in base", self.value_in_base, current.value_in_base),
+            ("protocol fee", self.protocol_fee, current.protocol_fee),
+            (
+                "implementor fee",
+                self.implementor_fee,
+                current.implementor_fee,
+            ),
+            (
+                "transaction fee",
+                self.transaction_fee,
+                current.transaction_fee,
+            ),
+            ("total", self.total, current.total),
+        ];
+        if self.action != current.action {
+            return Err(ProtocolError::StaleQuote(format!(
+                "the quoted Action {:?} does not match {:?}",
+                self.action, current.action
+            )));
+        }
+        for (field, quoted, actual) in fields.iter() {
+            if quoted != actual {
+                return Err(ProtocolError::StaleQuote(format!(
+                    "the quoted {} of {} is now {}",
+                    field, quoted, actual
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify that the total nanoErgs paid for a minting Action are no
+    /// more than `max_total_cost`, if provided.
+    pub fn verify_max_total_cost(&self, max_total_cost: Option<NanoErg>) -> Result<()> {
+        match max_total_cost {
+            Some(max) if self.total > max => Err(ProtocolError::SlippageExceeded {
+                expected: max,
+                actual: self.total,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Verify that the total nanoErgs received from a redeeming Action are
+    /// at least `min_total_received`, if provided.
+    pub fn verify_min_total_received(&self, min_total_received: Option<NanoErg>) -> Result<()> {
+        match min_total_received {
+            Some(min) if self.total < min => Err(ProtocolError::SlippageExceeded {
+                expected: min,
+                actual: self.total,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Verify that the current reserve ratio of the Bank (ie. before the
+    /// Action) has drifted by no more than `max_reserve_ratio_drift`
+    /// percentage points from `expected_reserve_ratio`, the reserve ratio
+    /// the user was quoted, if both are provided.
+    pub fn verify_reserve_ratio_drift(
+        &self,
+        expected_reserve_ratio: Option<u64>,
+        max_reserve_ratio_drift: Option<u64>,
+    ) -> Result<()> {
+        let (expected, max) = match (expected_reserve_ratio, max_reserve_ratio_drift) {
+            (Some(expected), Some(max)) => (expected, max),
+            (None, Some(_)) => {
+                return Err(ProtocolError::InvalidInputValue(
+                    "A max reserve ratio drift requires the expected reserve ratio.".to_string(),
+                ))
+            }
+            _ => return Ok(()),
+        };
+        let current = self.reserve_ratio_before;
+        let drift = std::cmp::max(expected, current) - std::cmp::min(expected, current);
+        match drift > max {
+            true => Err(ProtocolError::SlippageExceeded {
+                expected,
+                actual: current,
+            }),
+            false => Ok(()),
+        }
+    }
+
+    /// Build a `Quote` for an Action, calculating the fees from the
+    /// `FeeSchedule` of `config`
+    fn new(
+        action: QuoteAction,
+        amount: u64,
+        feeless_amount: NanoErg,
+        value_in_base: NanoErg,
+        transaction_fee: NanoErg,
+        config: &ProtocolConfig,
+    ) -> Result<Quote> {
+        let fee_schedule = config.fee_schedule();
+        Ok(Quote {
+            action,
+            amount,
+            feeless_amount,
+            protocol_fee: fee_schedule.protocol_fee(feeless_amount)?,
+            implementor_fee: fee_schedule.implementor_fee(value_in_base)?,
+            transaction_fee,
+            min_box_overhead: 0,
+            value_in_base,
+            total: 0,
+            reserve_ratio_before: 0,
+            reserve_ratio_after: 0,
+            effective_price: 0,
+            allowed: false,
+        })
+    }
+
+    /// Set the outcome of the quoted Action, calculating the effective price
+    fn with_outcome(
+        mut self,
+        total: NanoErg,
+        min_box_overhead: NanoErg,
+        reserve_ratios: (u64, u64),
+        allowed: bool,
+    ) -> Quote {
+        self.total = total;
+        self.min_box_overhead = min_box_overhead;
+        self.reserve_ratio_before = reserve_ratios.0;
+        self.reserve_ratio_after = reserve_ratios.1;
+        self.effective_price = total.checked_div(self.amount).unwrap_or(0);
+        self.allowed = allowed;
+        self
+    }
+}
+
+/// WASM-supported quoting methods related to `BankStage`
+#[wasm_bindgen]
+impl BankBox {
+    /// Quote minting `amount` StableCoins.
+    #[wasm_bindgen]
+    pub fn w_quote_mint_stablecoin(
+        &self,
+        amount: u64,
+        transaction_fee: NanoErg,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> std::result::Result<Quote, JsValue> {
+        self.quote_mint_stablecoin(amount, transaction_fee, oracle_box, config)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// Quote minting `amount` ReserveCoins.
+    #[wasm_bindgen]
+    pub fn w_quote_mint_reservecoin(
+        &self,
+        amount: u64,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> std::result::Result<Quote, JsValue> {
+        self.quote_mint_reservecoin(amount, transaction_fee, current_height, oracle_box, config)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// Quote redeeming `amount` StableCoins.
+    #[wasm_bindgen]
+    pub fn w_quote_redeem_stablecoin(
+        &self,
+        amount: u64,
+        transaction_fee: NanoErg,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> std::result::Result<Quote, JsValue> {
+        self.quote_redeem_stablecoin(amount, transaction_fee, oracle_box, config)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// Quote redeeming `amount` ReserveCoins.
+    #[wasm_bindgen]
+    pub fn w_quote_redeem_reservecoin(
+        &self,
+        amount: u64,
+        transaction_fee: NanoErg,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> std::result::Result<Quote, JsValue> {
+        self.quote_redeem_reservecoin(amount, transaction_fee, oracle_box, config)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+}
+
+/// Rust quoting methods related to `BankStage`
+impl BankBox {
+    /// Quote minting `amount` StableCoins.
+    pub fn quote_mint_stablecoin(
+        &self,
+        amount: u64,
+        transaction_fee: NanoErg,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<Quote> {
+        let value_in_base =
+            self.checked_base_cost_to_mint_stablecoin(amount, oracle_box, config)?;
+        // Already checked for overflow while calculating `value_in_base`
+        let feeless_amount = self.stablecoin_nominal_price(oracle_box, config) * amount;
+        let total = self.checked_total_cost_to_mint_stablecoin(
+            amount,
+            oracle_box,
+            transaction_fee,
+            config,
+        )?;
+        let reserve_ratios = (
+            self.checked_current_reserve_ratio(oracle_box, config)?,
+            self.checked_mint_stablecoin_reserve_ratio(oracle_box, amount, config)?,
+        );
+        let allowed = amount > 0 && self.able_to_mint_stablecoin_amount(oracle_box, amount, config);
+        let quote = Quote::new(
+            QuoteAction::MintStableCoin,
+            amount,
+            feeless_amount,
+            value_in_base,
+            transaction_fee,
+            config,
+        )?;
+        let min_box_overhead = config.min_box_value * 2;
+        Ok(quote.with_outcome(total, min_box_overhead, reserve_ratios, allowed))
+    }
+
+    /// Quote minting `amount` ReserveCoins.
+    pub fn quote_mint_reservecoin(
+        &self,
+        amount: u64,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<Quote> {
+        let value_in_base =
+            self.checked_base_cost_to_mint_reservecoin(amount, oracle_box, config)?;
+        // Already checked for overflow while calculating `value_in_base`
+        let feeless_amount = self.reservecoin_nominal_price(oracle_box, config) * amount;
+        let total = self.checked_total_cost_to_mint_reservecoin(
+            amount,
+            oracle_box,
+            transaction_fee,
+            config,
+        )?;
+        let reserve_ratios = (
+            self.checked_current_reserve_ratio(oracle_box, config)?,
+            self.checked_mint_reservecoin_reserve_ratio(oracle_box, amount, config)?,
+        );
+        let allowed = amount > 0
+            && self.able_to_mint_reservecoin_amount(oracle_box, amount, current_height, config);
+        let quote = Quote::new(
+            QuoteAction::MintReserveCoin,
+            amount,
+            feeless_amount,
+            value_in_base,
+            transaction_fee,
+            config,
+        )?;
+        let min_box_overhead = config.min_box_value * 2;
+        Ok(quote.with_outcome(total, min_box_overhead, reserve_ratios, allowed))
+    }
+
+    /// Quote redeeming `amount` StableCoins.
+    pub fn quote_redeem_stablecoin(
+        &self,
+        amount: u64,
+        transaction_fee: NanoErg,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<Quote> {
+        let value_in_base =
+            self.checked_base_amount_from_redeeming_stablecoin(amount, oracle_box, config)?;
+        // Already checked for overflow while calculating `value_in_base`
+        let feeless_amount = self.stablecoin_nominal_price(oracle_box, config) * amount;
+        let total = self.checked_total_amount_from_redeeming_stablecoin(
+            amount,
+            oracle_box,
+            transaction_fee,
+            config,
+        )?;
+        let circulating_stablecoins = self.num_circulating_stablecoins();
+        let reserve_ratios = (
+            self.checked_current_reserve_ratio(oracle_box, config)?,
+            checked_reserve_ratio(
+                self.base_reserves(config).saturating_sub(value_in_base),
+                circulating_stablecoins.saturating_sub(amount),
+                self.oracle_rate(oracle_box, config),
+            )?,
+        );
+        let allowed = amount > 0
+            && total > 0
+            && amount <= circulating_stablecoins
+            && value_in_base <= self.base_reserves(config);
+        let quote = Quote::new(
+            QuoteAction::RedeemStableCoin,
+            amount,
+            feeless_amount,
+            value_in_base,
+            transaction_fee,
+            config,
+        )?;
+        Ok(quote.with_outcome(total, 0, reserve_ratios, allowed))
+    }
+
+    /// Quote redeeming `amount` ReserveCoins.
+    pub fn quote_redeem_reservecoin(
+        &self,
+        amount: u64,
+        transaction_fee: NanoErg,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> Result<Quote> {
+        let value_in_base =
+            self.checked_base_amount_from_redeeming_reservecoin(amount, oracle_box, config)?;
+        // Already checked for overflow while calculating `value_in_base`
+        let feeless_amount = self.reservecoin_nominal_price(oracle_box, config) * amount;
+        let total = self.checked_total_amount_from_redeeming_reservecoin(
+            amount,
+            oracle_box,
+            transaction_fee,
+            config,
+        )?;
+        let reserve_ratios = (
+            self.checked_current_reserve_ratio(oracle_box, config)?,
+            checked_reserve_ratio(
+                self.base_reserves(config).saturating_sub(value_in_base),
+                self.num_circulating_stablecoins(),
+                self.oracle_rate(oracle_box, config),
+            )?,
+        );
+        let allowed = amount > 0
+            && total > 0
+            && amount <= self.num_circulating_reservecoins()
+            && self.able_to_redeem_reservecoin_amount(oracle_box, amount, config);
+        let quote = Quote::new(
+            QuoteAction::RedeemReserveCoin,
+            amount,
+            feeless_amount,
+            value_in_base,
+            transaction_fee,
+            config,
+        )?;
+        Ok(quote.with_outcome(total, 0, reserve_ratios, allowed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ergo_headless_dapp_framework::create_candidate;
+    use ergo_headless_dapp_framework::encoding::build_token;
+    use ergo_lib::chain::ergo_box::ErgoBox;
+    use ergo_lib::chain::transaction::TxId;
+
+    /// A P2S address to hold the Bank & oracle pool boxes
+    const ADDRESS: &str = "2iHkR7CWvD1R4j1yZg5bkeDRQavjAaVPeTDFGGLZduHyfWMuYpmhHocX8GJoaieTx78FntzJbCBVL6rf96ocJoZdmWBL2fci7NqWgAirppPQmZ7fN9V6z13Ay6brPriBKYqLp1bT2Fk4FkFLCfdPpe";
+
+    fn config() -> ProtocolConfig {
+        ProtocolConfig {
+            stablecoin_token_id: "01".repeat(32),
+            reservecoin_token_id: "02".repeat(32),
+            bank_nft_id: "03".repeat(32),
+            ..ProtocolConfig::default()
+        }
+    }
+
+    fn ergo_box(value: NanoErg, tokens: Vec<(String, u64)>, registers: Vec<i64>) -> ErgoBox {
+        let tokens = tokens
+            .iter()
+            .map(|(id, amount)| build_token(id, *amount).unwrap())
+            .collect();
+        let registers = registers.into_iter().map(|r| r.into()).collect();
+        let candidate =
+            create_candidate(value, &ADDRESS.to_string(), &tokens, &registers, 500_000).unwrap();
+        ErgoBox::from_box_candidate(&candidate, TxId::zero(), 0)
+    }
+
+    /// A Bank box with a reserve ratio of 500% and an oracle pool box
+    /// pricing 1 cent at 2000000 nanoErgs
+    fn boxes() -> (BankBox, ErgUsdOraclePoolBox) {
+        let config = config();
+        let bank_box = ergo_box(
+            10_000_000_000_000,
+            vec![
+                (config.stablecoin_token_id.clone(), 1_000_000_000_000),
+                (config.reservecoin_token_id.clone(), 1_000_000_000_000),
+                (config.bank_nft_id.clone(), 1),
+            ],
+            vec![1_000_000, 1_000_000_000],
+        );
+        let oracle_box = ergo_box(
+            config.min_box_value,
+            vec![(config.oracle_pool_nft_id.clone(), 1)],
+            vec![200_000_000],
+        );
+        (
+            BankBox::new_with_config(&bank_box, &config).unwrap(),
+            ErgUsdOraclePoolBox::new(&oracle_box).unwrap(),
+        )
+    }
+
+    #[test]
+    fn redeeming_for_nothing_is_not_allowed() {
+        let config = config();
+        let (bank_box, oracle_box) = boxes();
+        for action in &[
+            QuoteAction::RedeemStableCoin,
+            QuoteAction::RedeemReserveCoin,
+        ] {
+            let quote = |fee| match action {
+                QuoteAction::RedeemStableCoin => {
+                    bank_box.quote_redeem_stablecoin(1, fee, &oracle_box, &config)
+                }
+                _ => bank_box.quote_redeem_reservecoin(1, fee, &oracle_box, &config),
+            };
+            let received = quote(0).unwrap();
+            assert!(received.allowed);
+            assert!(received.total > 1);
+            assert!(quote(received.total - 1).unwrap().allowed);
+            // The transaction fee takes everything which would be received
+            let nothing = quote(received.total).unwrap();
+            assert_eq!(nothing.total, 0);
+            assert!(!nothing.allowed);
+            // The transaction fee exceeds what would be received
+            assert!(quote(received.total + 1).is_err());
+        }
+    }
+
+    fn quote(action: QuoteAction, total: NanoErg, reserve_ratio_before: u64) -> Quote {
+        Quote {
+            action,
+            amount: 10,
+            feeless_amount: 0,
+            protocol_fee: 0,
+            implementor_fee: 0,
+            transaction_fee: 0,
+            min_box_overhead: 0,
+            value_in_base: 0,
+            total,
+            reserve_ratio_before,
+            reserve_ratio_after: 0,
+            effective_price: 0,
+            allowed: true,
+        }
+    }
+
+    fn slippage(result: Result<()>) -> Option<(u64, u64)> {
+        match result {
+            Err(ProtocolError::SlippageExceeded { expected, actual }) => Some((expected, actual)),
+            Ok(()) => None,
+            Err(e) => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn max_total_cost() {
+        let q = quote(QuoteAction::MintStableCoin, 1000, 0);
+        assert_eq!(slippage(q.verify_max_total_cost(None)), None);
+        assert_eq!(slippage(q.verify_max_total_cost(Some(1000))), None);
+        assert_eq!(slippage(q.verify_max_total_cost(Some(1001))), None);
+        assert_eq!(
+            slippage(q.verify_max_total_cost(Some(999))),
+            Some((999, 1000))
+        );
+    }
+
+    #[test]
+    fn min_total_received() {
+        let q = quote(QuoteAction::RedeemStableCoin, 1000, 0);
+        assert_eq!(slippage(q.verify_min_total_received(None)), None);
+        assert_eq!(slippage(q.verify_min_total_received(Some(1000))), None);
+        assert_eq!(slippage(q.verify_min_total_received(Some(999))), None);
+        assert_eq!(
+            slippage(q.verify_min_total_received(Some(1001))),
+            Some((1001, 1000))
+        );
+    }
+
+    #[test]
+    fn reserve_ratio_drift_is_measured_from_the_quoted_ratio() {
+        let q = quote(QuoteAction::MintReserveCoin, 1000, 450);
+        assert_eq!(slippage(q.verify_reserve_ratio_drift(None, None)), None);
+        assert_eq!(
+            slippage(q.verify_reserve_ratio_drift(Some(400), None)),
+            None
+        );
+        // The drift is within the limit in either direction
+        assert_eq!(
+            slippage(q.verify_reserve_ratio_drift(Some(440), Some(10))),
+            None
+        );
+        assert_eq!(
+            slippage(q.verify_reserve_ratio_drift(Some(460), Some(10))),
+            None
+        );
+        assert_eq!(
+            slippage(q.verify_reserve_ratio_drift(Some(450), Some(0))),
+            None
+        );
+        // The error holds the quoted and the current reserve ratio
+        assert_eq!(
+            slippage(q.verify_reserve_ratio_drift(Some(439), Some(10))),
+            Some((439, 450))
+        );
+        assert_eq!(
+            slippage(q.verify_reserve_ratio_drift(Some(461), Some(10))),
+            Some((461, 450))
+        );
+        // The after-Action reserve ratio of the `Quote` does not count
+        let mut moved = q;
+        moved.reserve_ratio_after = 900;
+        assert_eq!(
+            slippage(moved.verify_reserve_ratio_drift(Some(450), Some(0))),
+            None
+        );
+    }
+
+    #[test]
+    fn reserve_ratio_drift_requires_the_quoted_ratio() {
+        let q = quote(QuoteAction::MintReserveCoin, 1000, 450);
+        match q.verify_reserve_ratio_drift(None, Some(10)) {
+            Err(ProtocolError::InvalidInputValue(_)) => (),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+}
@@ -1,8 +1,9 @@
 // This file specifies an output builder for the Receipt stage.
 use crate::bank::BankBox;
+use crate::equations::{checked_add, checked_sub, to_long};
 use crate::error::ProtocolError;
 use crate::input_boxes::{ReserveCoinBox, StableCoinBox};
-use crate::parameters::{MIN_BOX_VALUE, RESERVECOIN_TOKEN_ID, STABLECOIN_TOKEN_ID};
+use crate::parameters::ProtocolConfig;
 use ergo_headless_dapp_framework::{
     create_candidate, find_and_sum_other_tokens, WrapBox, WrappedBox,
 };
@@ -25,30 +26,43 @@ impl ReceiptBox {
         user_address: &P2PKAddressString,
         current_height: BlockHeight,
         transaction_fee: NanoErg,
-        implementor_fee: NanoErg,
+        config: &ProtocolConfig,
         reservecoin_value_in_base: NanoErg,
         _bank_box: &BankBox,
         input_ergs_total: NanoErg,
     ) -> Result<ErgoBoxCandidate, ProtocolError> {
         // Define the ReserveCoin token
-        let rb_reservecoin_token = new_reservecoin_token(amount_to_mint)?;
+        let rb_reservecoin_token = new_reservecoin_token(amount_to_mint, config)?;
         // Define the Receipt Box tokens
         let rb_tokens = vec![rb_reservecoin_token];
 
+        // The fee paid to the implementor for minting
+        let implementor_fee = config
+            .fee_schedule()
+            .implementor_fee(reservecoin_value_in_base)?;
+        // The input nanoErgs minus the value of the ReserveCoins being minted,
+        // the fees, and the min box value of the Bank box
+        let rb_value = [
+            reservecoin_value_in_base,
+            transaction_fee,
+            implementor_fee,
+            config.min_box_value,
+        ]
+        .iter()
+        .try_fold(input_ergs_total, |acc, v| {
+            checked_sub(acc, *v, "Receipt box value")
+        })?;
+
         // Specify the registers in the Receipt box
         let rb_registers_vec = vec![
-            (amount_to_mint as i64).into(),
-            (reservecoin_value_in_base as i64).into(),
+            to_long(amount_to_mint, "Receipt token amount")?.into(),
+            to_long(reservecoin_value_in_base, "Receipt nanoErg amount")?.into(),
         ];
 
         // Create the Receipt box candidate
         let candidate = create_candidate(
-            input_ergs_total
-                - reservecoin_value_in_base
-                - transaction_fee
-                - implementor_fee
-                - MIN_BOX_VALUE,
-            &user_address,
+            rb_value,
+            user_address,
             &rb_tokens,
             &rb_registers_vec,
             current_height,
@@ -63,30 +77,43 @@ impl ReceiptBox {
         user_address: &P2PKAddressString,
         current_height: BlockHeight,
         transaction_fee: NanoErg,
-        implementor_fee: NanoErg,
+        config: &ProtocolConfig,
         stablecoin_value_in_base: NanoErg,
         _bank_box: &BankBox,
         input_ergs_total: NanoErg,
     ) -> Result<ErgoBoxCandidate, ProtocolError> {
         // Define the StableCoin token
-        let rb_stablecoin_token = new_stablecoin_token(amount_to_mint)?;
+        let rb_stablecoin_token = new_stablecoin_token(amount_to_mint, config)?;
         // Define the Receipt Box tokens
         let rb_tokens = vec![rb_stablecoin_token];
 
+        // The fee paid to the implementor for minting
+        let implementor_fee = config
+            .fee_schedule()
+            .implementor_fee(stablecoin_value_in_base)?;
+        // The input nanoErgs minus the value of the StableCoins being minted,
+        // the fees, and the min box value of the Bank box
+        let rb_value = [
+            stablecoin_value_in_base,
+            transaction_fee,
+            implementor_fee,
+            config.min_box_value,
+        ]
+        .iter()
+        .try_fold(input_ergs_total, |acc, v| {
+            checked_sub(acc, *v, "Receipt box value")
+        })?;
+
         // Specify the registers in the Receipt box
         let rb_registers_vec = vec![
-            (amount_to_mint as i64).into(),
-            (stablecoin_value_in_base as i64).into(),
+            to_long(amount_to_mint, "Receipt token amount")?.into(),
+            to_long(stablecoin_value_in_base, "Receipt nanoErg amount")?.into(),
         ];
 
         // Create the Receipt box candidate
         let candidate = create_candidate(
-            input_ergs_total
-                - stablecoin_value_in_base
-                - transaction_fee
-                - implementor_fee
-                - MIN_BOX_VALUE,
-            &user_address,
+            rb_value,
+            user_address,
             &rb_tokens,
             &rb_registers_vec,
             current_height,
@@ -103,24 +130,35 @@ impl ReceiptBox {
         transaction_fee: NanoErg,
         reservecoin_value_in_base: NanoErg,
         bank_box: &BankBox,
-        rc_boxes: &Vec<ReserveCoinBox>,
+        rc_boxes: &[ReserveCoinBox],
         no_bank_inputs: &Vec<ErgoBox>,
-        implementor_fee: NanoErg,
+        config: &ProtocolConfig,
     ) -> Result<ErgoBoxCandidate, ProtocolError> {
         // Find how many nanoErgs are inside of the ReserveCoin boxes
-        let rc_boxes_value = ReserveCoinBox::sum_nano_ergs_value(&rc_boxes);
+        let rc_boxes_value = ReserveCoinBox::sum_nano_ergs_value(rc_boxes);
         // Find how many StableCoins are inside of the StableCoin boxes
-        let rc_boxes_total_rc = ReserveCoinBox::sum_token_amount(rc_boxes);
+        let rc_boxes_total_rc = ReserveCoinBox::sum_token_amount(rc_boxes, config);
 
+        // The fee paid to the implementor for redeeming
+        let implementor_fee = config
+            .fee_schedule()
+            .implementor_fee(reservecoin_value_in_base)?;
         // The amount of nanoErgs in the rc_boxes + the value of the
         // ReserveCoins being redeemed - the transaction fee
-        let rb_value =
-            rc_boxes_value + reservecoin_value_in_base - transaction_fee - implementor_fee;
+        let rb_value = checked_sub(
+            checked_add(
+                rc_boxes_value,
+                reservecoin_value_in_base,
+                "Receipt box value",
+            )?,
+            checked_add(transaction_fee, implementor_fee, "Receipt box fees")?,
+            "Receipt box value",
+        )?;
 
         // Specify the registers in the Receipt box
         let rb_registers_vec = vec![
-            (0 - amount_to_redeem as i64).into(),
-            (0 - reservecoin_value_in_base as i64).into(),
+            (-to_long(amount_to_redeem, "Receipt token amount")?).into(),
+            (-to_long(reservecoin_value_in_base, "Receipt nanoErg amount")?).into(),
         ];
 
         // Define the tokens
@@ -130,17 +168,17 @@ impl ReceiptBox {
         if rc_boxes_total_rc > amount_to_redeem {
             // Define the StableCoin token
             let amount = rc_boxes_total_rc - amount_to_redeem;
-            let new_rc_token = new_reservecoin_token(amount)?;
+            let new_rc_token = new_reservecoin_token(amount, config)?;
             rb_tokens.push(new_rc_token)
         }
         // Find all other tokens held in user-provided input boxes
         let mut other_tokens =
-            find_and_sum_other_tokens(&vec![bank_box.tokens()[1].clone()], &no_bank_inputs);
+            find_and_sum_other_tokens(&vec![bank_box.tokens()[1].clone()], no_bank_inputs);
         rb_tokens.append(&mut other_tokens);
 
         let candidate = create_candidate(
             rb_value,
-            &user_address,
+            user_address,
             &rb_tokens,
             &rb_registers_vec,
             current_height,
@@ -157,24 +195,35 @@ impl ReceiptBox {
         transaction_fee: NanoErg,
         stablecoin_value_in_base: NanoErg,
         bank_box: &BankBox,
-        sc_boxes: &Vec<StableCoinBox>,
+        sc_boxes: &[StableCoinBox],
         no_bank_inputs: &Vec<ErgoBox>,
-        implementor_fee: NanoErg,
+        config: &ProtocolConfig,
     ) -> Result<ErgoBoxCandidate, ProtocolError> {
         // Find how many nanoErgs are inside of the StableCoin boxes
-        let sc_boxes_value = StableCoinBox::sum_nano_ergs_value(&sc_boxes);
+        let sc_boxes_value = StableCoinBox::sum_nano_ergs_value(sc_boxes);
         // Find how many StableCoins are inside of the StableCoin boxes
-        let sc_boxes_total_sc = StableCoinBox::sum_token_amount(sc_boxes);
+        let sc_boxes_total_sc = StableCoinBox::sum_token_amount(sc_boxes, config);
 
+        // The fee paid to the implementor for redeeming
+        let implementor_fee = config
+            .fee_schedule()
+            .implementor_fee(stablecoin_value_in_base)?;
         // The amount of nanoErgs in the rc_boxes + the value of the
         // ReserveCoins being redeemed - the transaction fee
-        let rb_value =
-            sc_boxes_value + stablecoin_value_in_base - implementor_fee - transaction_fee;
+        let rb_value = checked_sub(
+            checked_add(
+                sc_boxes_value,
+                stablecoin_value_in_base,
+                "Receipt box value",
+            )?,
+            checked_add(transaction_fee, implementor_fee, "Receipt box fees")?,
+            "Receipt box value",
+        )?;
 
         // Specify the registers in the Receipt box
         let rb_registers_vec = vec![
-            (0 - amount_to_redeem as i64).into(),
-            (0 - stablecoin_value_in_base as i64).into(),
+            (-to_long(amount_to_redeem, "Receipt token amount")?).into(),
+            (-to_long(stablecoin_value_in_base, "Receipt nanoErg amount")?).into(),
         ];
 
         // Define the tokens
@@ -184,17 +233,17 @@ impl ReceiptBox {
         if sc_boxes_total_sc > amount_to_redeem {
             // Define the StableCoin token
             let amount = sc_boxes_total_sc - amount_to_redeem;
-            let new_sc_token = new_stablecoin_token(amount)?;
+            let new_sc_token = new_stablecoin_token(amount, config)?;
             rb_tokens.push(new_sc_token)
         }
         // Find all other tokens held in user-provided input boxes
         let mut other_tokens =
-            find_and_sum_other_tokens(&vec![bank_box.tokens()[0].clone()], &no_bank_inputs);
+            find_and_sum_other_tokens(&vec![bank_box.tokens()[0].clone()], no_bank_inputs);
         rb_tokens.append(&mut other_tokens);
 
         let candidate = create_candidate(
             rb_value,
-            &user_address,
+            user_address,
             &rb_tokens,
             &rb_registers_vec,
             current_height,
@@ -204,11 +253,11 @@ impl ReceiptBox {
 }
 
 // Creates a new StableCoin token with a custom amount
-fn new_stablecoin_token(amount: u64) -> Result<Token, ProtocolError> {
-    Ok(build_token(STABLECOIN_TOKEN_ID, amount)?)
+fn new_stablecoin_token(amount: u64, config: &ProtocolConfig) -> Result<Token, ProtocolError> {
+    Ok(build_token(&config.stablecoin_token_id, amount)?)
 }
 
 // Creates a new ReserveCoin token with a custom amount
-fn new_reservecoin_token(amount: u64) -> Result<Token, ProtocolError> {
-    Ok(build_token(RESERVECOIN_TOKEN_ID, amount)?)
+fn new_reservecoin_token(amount: u64, config: &ProtocolConfig) -> Result<Token, ProtocolError> {
+    Ok(build_token(&config.reservecoin_token_id, amount)?)
 }
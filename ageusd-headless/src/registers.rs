@@ -0,0 +1,118 @@
+// This file holds the typed decoding of the registers of the protocol's
+// boxes. Anyone can send a box holding a ballot token (or any box to the
+// Update/Bank contract addresses), so registers are decoded fallibly. The
+// `BoxSpec` predicates use the same `RegisterSpec`s, meaning malformed boxes
+// are rejected before their registers are ever read.
+use crate::error::{ProtocolError, Result};
+use ergo_headless_dapp_framework::encoding::{unwrap_hex_encoded_string, unwrap_long};
+use ergo_lib::chain::ergo_box::ErgoBox;
+
+/// The length in bytes of a blake2b256 hash or a box id
+pub const HASH_LENGTH: usize = 32;
+
+/// The type of value held within a register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterType {
+    /// A `Coll[Byte]` of `HASH_LENGTH` bytes
+    Hash,
+    /// A `Coll[Byte]` of `HASH_LENGTH` bytes, or an empty `Coll[Byte]`
+    HashOrEmpty,
+    /// A `Long` which is not negative
+    NonNegativeLong,
+}
+
+/// The expected type of a single register of a box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterSpec {
+    /// A description of what the register holds, used in errors
+    pub name: &'static str,
+    /// The type of value held within the register
+    pub register_type: RegisterType,
+}
+
+impl RegisterSpec {
+    pub fn new(name: &'static str, register_type: RegisterType) -> RegisterSpec {
+        RegisterSpec {
+            name,
+            register_type,
+        }
+    }
+}
+
+/// Verify that the registers of the box, starting from R4, hold exactly
+/// the values described by `specs`
+pub fn verify_registers(b: &ErgoBox, specs: &[RegisterSpec]) -> Result<()> {
+    let num_registers = b.additional_registers.get_ordered_values().len();
+    if num_registers != specs.len() {
+        return Err(ProtocolError::InvalidRegisters(format!(
+            "Expected {} registers, but the box holds {}.",
+            specs.len(),
+            num_registers
+        )));
+    }
+    for (index, spec) in specs.iter().enumerate() {
+        match spec.register_type {
+            RegisterType::Hash => decode_hash(b, index, spec.name, false).map(|_| ())?,
+            RegisterType::HashOrEmpty => decode_hash(b, index, spec.name, true).map(|_| ())?,
+            RegisterType::NonNegativeLong => {
+                decode_non_negative_long(b, index, spec.name).map(|_| ())?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decode the `Coll[Byte]` hash held in the register at `index` (0 == R4)
+/// as a hex string. If `allow_empty` an empty `Coll[Byte]` is accepted too.
+pub fn decode_hash(b: &ErgoBox, index: usize, name: &str, allow_empty: bool) -> Result<String> {
+    let registers = b.additional_registers.get_ordered_values();
+    let constant = registers
+        .get(index)
+        .ok_or_else(|| missing_register(index, name))?;
+    let hash = unwrap_hex_encoded_string(constant).map_err(|_| {
+        ProtocolError::InvalidRegisters(format!(
+            "R{} ({}) does not hold a `Coll[Byte]`.",
+            index + 4,
+            name
+        ))
+    })?;
+    if hash.len() == HASH_LENGTH * 2 || (allow_empty && hash.is_empty()) {
+        return Ok(hash);
+    }
+    Err(ProtocolError::InvalidRegisters(format!(
+        "R{} ({}) holds {} bytes instead of {}.",
+        index + 4,
+        name,
+        hash.len() / 2,
+        HASH_LENGTH
+    )))
+}
+
+/// Decode the `Long` held in the register at `index` (0 == R4), which
+/// must not be negative
+pub fn decode_non_negative_long(b: &ErgoBox, index: usize, name: &str) -> Result<u64> {
+    let registers = b.additional_registers.get_ordered_values();
+    let constant = registers
+        .get(index)
+        .ok_or_else(|| missing_register(index, name))?;
+    let value = unwrap_long(constant).map_err(|_| {
+        ProtocolError::InvalidRegisters(format!(
+            "R{} ({}) does not hold a `Long`.",
+            index + 4,
+            name
+        ))
+    })?;
+    if value < 0 {
+        return Err(ProtocolError::InvalidRegisters(format!(
+            "R{} ({}) holds a negative value: {}",
+            index + 4,
+            name,
+            value
+        )));
+    }
+    Ok(value as u64)
+}
+
+fn missing_register(index: usize, name: &str) -> ProtocolError {
+    ProtocolError::InvalidRegisters(format!("R{} ({}) is missing.", index + 4, name))
+}
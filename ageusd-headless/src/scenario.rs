@@ -0,0 +1,245 @@
+// This file holds the price shock scenario analysis of the Bank box. Given the
+// current reserves and circulating coins, it solves for the oracle rates at
+// which the reserve ratio falls to a target (ie. the min reserve ratio or
+// 100%) and at which ReserveCoin equity is wiped out, and tabulates the state
+// of the protocol over a range of hypothetical oracle rates. A higher oracle
+// rate (nanoErgs per StableCoin) means a lower ERG/USD price.
+use crate::bank::BankBox;
+use crate::equations::{reserve_ratio_bps, BPS_PER_PERCENT};
+use crate::error::{ProtocolError, Result};
+use crate::parameters::ProtocolConfig;
+use ergo_headless_dapp_framework::{ErgUsdOraclePoolBox, NanoErg};
+use serde::Serialize;
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+/// The maximum number of oracle rates a sweep may be evaluated at
+pub const MAX_SWEEP_STEPS: u64 = 10_000;
+
+/// The column names of `ScenarioReport::to_csv`
+const CSV_HEADER: &str = "oracle_rate,reserve_ratio_bps,stablecoin_nominal_price,reservecoin_nominal_price,liabilities,equity";
+
+/// The state of the protocol at a hypothetical oracle rate
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ScenarioPoint {
+    /// The hypothetical nanoErg price of a single StableCoin
+    pub oracle_rate: NanoErg,
+    /// The reserve ratio in basis points
+    pub reserve_ratio_bps: u64,
+    /// The StableCoin nominal price
+    pub stablecoin_nominal_price: NanoErg,
+    /// The ReserveCoin nominal price
+    pub reservecoin_nominal_price: NanoErg,
+    /// The liabilities covering the circulating StableCoins
+    pub liabilities: NanoErg,
+    /// The equity backing ReserveCoins
+    pub equity: NanoErg,
+}
+
+/// The oracle rates at which the protocol crosses its risk thresholds.
+/// A threshold is `None` if no StableCoins are circulating, in which case
+/// it is never crossed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PriceShockThresholds {
+    /// The current nanoErg price of a single StableCoin
+    pub oracle_rate: NanoErg,
+    /// The min reserve ratio of the `ProtocolConfig`
+    pub min_reserve_ratio: u64,
+    /// The highest oracle rate at which the reserve ratio is at or above
+    /// the min reserve ratio
+    pub min_reserve_ratio_rate: Option<NanoErg>,
+    /// The highest oracle rate at which the reserve ratio is at or above 100%
+    pub full_backing_rate: Option<NanoErg>,
+    /// The lowest oracle rate at which ReserveCoins have no equity
+    pub zero_equity_rate: Option<NanoErg>,
+}
+
+/// The thresholds of the protocol alongside its state over a range of
+/// hypothetical oracle rates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScenarioReport {
+    pub thresholds: PriceShockThresholds,
+    pub points: Vec<ScenarioPoint>,
+}
+
+impl ScenarioReport {
+    /// Serialize the `ScenarioReport` into a JSON string
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| ProtocolError::Other(e.to_string()))
+    }
+
+    /// Serialize the points of the `ScenarioReport` into CSV, one row per
+    /// oracle rate
+    pub fn to_csv(&self) -> String {
+        let mut csv = CSV_HEADER.to_string();
+        for point in &self.points {
+            csv.push_str(&format!(
+                "\n{},{},{},{},{},{}",
+                point.oracle_rate,
+                point.reserve_ratio_bps,
+                point.stablecoin_nominal_price,
+                point.reservecoin_nominal_price,
+                point.liabilities,
+                point.equity
+            ));
+        }
+        csv
+    }
+}
+
+/// WASM-supported scenario analysis methods related to `BankStage`
+#[wasm_bindgen]
+impl BankBox {
+    /// The highest oracle rate at which the reserve ratio is at or above
+    /// `target_reserve_ratio` percent. `None` if no StableCoins are
+    /// circulating or the target is 0, as any rate satisfies it.
+    #[wasm_bindgen]
+    pub fn oracle_rate_at_reserve_ratio(
+        &self,
+        target_reserve_ratio: u64,
+        config: &ProtocolConfig,
+    ) -> Option<NanoErg> {
+        self.oracle_rate_at_reserve_ratio_bps(
+            target_reserve_ratio.saturating_mul(BPS_PER_PERCENT),
+            config,
+        )
+    }
+
+    /// The highest oracle rate at which the reserve ratio is at or above
+    /// `target_reserve_ratio_bps` basis points. `None` if no StableCoins are
+    /// circulating or the target is 0, as any rate satisfies it.
+    #[wasm_bindgen]
+    pub fn oracle_rate_at_reserve_ratio_bps(
+        &self,
+        target_reserve_ratio_bps: u64,
+        config: &ProtocolConfig,
+    ) -> Option<NanoErg> {
+        let circulating_stablecoins = self.num_circulating_stablecoins();
+        if circulating_stablecoins == 0 || target_reserve_ratio_bps == 0 {
+            return None;
+        }
+        // The reserve ratio is at or above the target while
+        // `reserves * 100 * 100 >= target_bps * circulating * rate`
+        let rate = (self.base_reserves(config) as u128 * 100 * BPS_PER_PERCENT as u128)
+            / (target_reserve_ratio_bps as u128 * circulating_stablecoins as u128);
+        Some(u64::try_from(rate).unwrap_or(u64::MAX))
+    }
+
+    /// The lowest oracle rate at which the liabilities consume the entire
+    /// base reserves, leaving ReserveCoins with no equity. `None` if no
+    /// StableCoins are circulating.
+    #[wasm_bindgen]
+    pub fn oracle_rate_at_zero_equity(&self, config: &ProtocolConfig) -> Option<NanoErg> {
+        let circulating_stablecoins = self.num_circulating_stablecoins() as u128;
+        if circulating_stablecoins == 0 {
+            return None;
+        }
+        // Rounded up so that the liabilities reach the base reserves
+        let base_reserves = self.base_reserves(config) as u128;
+        let rate = match base_reserves {
+            0 => 0,
+            _ => (base_reserves - 1) / circulating_stablecoins + 1,
+        };
+        Some(u64::try_from(rate).unwrap_or(u64::MAX))
+    }
+
+    /// The state of the protocol if the oracle rate were `oracle_rate`
+    #[wasm_bindgen]
+    pub fn scenario_at_rate(&self, oracle_rate: NanoErg, config: &ProtocolConfig) -> ScenarioPoint {
+        ScenarioPoint {
+            oracle_rate,
+            reserve_ratio_bps: reserve_ratio_bps(
+                self.base_reserves(config),
+                self.num_circulating_stablecoins(),
+                oracle_rate,
+            ),
+            stablecoin_nominal_price: self.stablecoin_nominal_price_at_rate(oracle_rate, config),
+            reservecoin_nominal_price: self.reservecoin_nominal_price_at_rate(oracle_rate, config),
+            liabilities: self.liabilities_at_rate(oracle_rate, config),
+            equity: self.equity_at_rate(oracle_rate, config),
+        }
+    }
+
+    /// A `ScenarioReport` of `steps` evenly spaced oracle rates from
+    /// `from_rate` to `to_rate` (inclusive) serialized as JSON
+    #[wasm_bindgen]
+    pub fn w_price_shock_report_json(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        from_rate: NanoErg,
+        to_rate: NanoErg,
+        steps: u64,
+        config: &ProtocolConfig,
+    ) -> std::result::Result<String, JsValue> {
+        let rates = sweep_rates(from_rate, to_rate, steps)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+        self.price_shock_report(oracle_box, &rates, config)
+            .to_json()
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+}
+
+/// Scenario analysis methods related to `BankStage`
+impl BankBox {
+    /// The oracle rates at which the protocol crosses its risk thresholds
+    pub fn price_shock_thresholds(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> PriceShockThresholds {
+        let min_reserve_ratio = config.min_reserve_ratio;
+        PriceShockThresholds {
+            oracle_rate: self.oracle_rate(oracle_box, config),
+            min_reserve_ratio,
+            min_reserve_ratio_rate: self.oracle_rate_at_reserve_ratio(min_reserve_ratio, config),
+            full_backing_rate: self.oracle_rate_at_reserve_ratio(100, config),
+            zero_equity_rate: self.oracle_rate_at_zero_equity(config),
+        }
+    }
+
+    /// The state of the protocol at each of the provided oracle rates
+    pub fn price_shock_sweep(
+        &self,
+        oracle_rates: &[NanoErg],
+        config: &ProtocolConfig,
+    ) -> Vec<ScenarioPoint> {
+        oracle_rates
+            .iter()
+            .map(|rate| self.scenario_at_rate(*rate, config))
+            .collect()
+    }
+
+    /// The risk thresholds of the protocol alongside its state at each of
+    /// the provided oracle rates
+    pub fn price_shock_report(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        oracle_rates: &[NanoErg],
+        config: &ProtocolConfig,
+    ) -> ScenarioReport {
+        ScenarioReport {
+            thresholds: self.price_shock_thresholds(oracle_box, config),
+            points: self.price_shock_sweep(oracle_rates, config),
+        }
+    }
+}
+
+/// `steps` evenly spaced oracle rates from `from_rate` to `to_rate`
+/// (inclusive). `from_rate` may be above `to_rate` to sweep downwards.
+/// `steps` must be within `1..=MAX_SWEEP_STEPS`.
+pub fn sweep_rates(from_rate: NanoErg, to_rate: NanoErg, steps: u64) -> Result<Vec<NanoErg>> {
+    if steps == 0 || steps > MAX_SWEEP_STEPS {
+        return Err(ProtocolError::InvalidInputValue(format!(
+            "A sweep requires between 1 and {} steps, but {} were requested.",
+            MAX_SWEEP_STEPS, steps
+        )));
+    }
+    if steps == 1 {
+        return Ok(vec![from_rate]);
+    }
+    let span = to_rate as i128 - from_rate as i128;
+    Ok((0..steps)
+        .map(|step| (from_rate as i128 + span * step as i128 / (steps as i128 - 1)) as u64)
+        .collect())
+}
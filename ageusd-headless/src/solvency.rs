@@ -0,0 +1,135 @@
+// This file holds the `SolvencyStatus`, a report on how well the Bank box
+// reserves back the StableCoins currently in circulation. When reserves fall
+// below 100% the Bank contract prices StableCoins at
+// `min(rate, liabilities / circulating)`, meaning holders redeem at a
+// haircut relative to the oracle rate.
+use crate::bank::BankBox;
+use crate::equations::mul_div;
+use crate::fees::BASIS_POINTS;
+use crate::parameters::ProtocolConfig;
+use ergo_headless_dapp_framework::{ErgUsdOraclePoolBox, NanoErg};
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+/// The solvency state of the protocol
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolvencyState {
+    /// The reserve ratio is at or above the min reserve ratio
+    FullyBacked,
+    /// StableCoins are fully backed, but the reserve ratio is below the
+    /// min reserve ratio
+    BelowMinReserveRatio,
+    /// The base reserves exactly cover the StableCoin liabilities, leaving
+    /// ReserveCoins with zero equity
+    NoReserveCoinEquity,
+    /// The base reserves are below 100% of the StableCoin liabilities.
+    /// StableCoins are redeemed at a haircut and ReserveCoins have no equity.
+    Undercollateralized,
+}
+
+/// Rust methods related to `SolvencyState`
+impl SolvencyState {
+    /// A human readable name of the state
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolvencyState::FullyBacked => "Fully Backed",
+            SolvencyState::BelowMinReserveRatio => "Below Min Reserve Ratio",
+            SolvencyState::NoReserveCoinEquity => "No ReserveCoin Equity",
+            SolvencyState::Undercollateralized => "Undercollateralized (Below 100%)",
+        }
+    }
+}
+
+/// A report on the solvency of the protocol
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolvencyStatus {
+    /// The solvency state of the protocol
+    pub state: SolvencyState,
+    /// The current reserve ratio of the Bank box
+    pub reserve_ratio: u64,
+    /// The min reserve ratio of the `ProtocolConfig`
+    pub min_reserve_ratio: u64,
+    /// The base reserves held in the Bank box
+    pub base_reserves: NanoErg,
+    /// The nanoErgs required to redeem every StableCoin at the oracle rate
+    pub liabilities_at_oracle_rate: NanoErg,
+    /// The nanoErgs missing from the base reserves to cover
+    /// `liabilities_at_oracle_rate`
+    pub shortfall: NanoErg,
+    /// The equity backing ReserveCoins
+    pub equity: NanoErg,
+    /// The oracle rate of a single StableCoin
+    pub oracle_rate: NanoErg,
+    /// The price a single StableCoin is redeemed at
+    pub stablecoin_price: NanoErg,
+    /// The nanoErgs lost per StableCoin when redeeming at
+    /// `stablecoin_price` rather than the oracle rate
+    pub haircut_per_coin: NanoErg,
+    /// The haircut relative to the oracle rate in basis points
+    pub haircut_bps: u64,
+}
+
+/// Rust methods related to `SolvencyStatus`
+impl SolvencyStatus {
+    /// Whether StableCoins can be redeemed at the full oracle rate
+    pub fn is_fully_collateralized(&self) -> bool {
+        self.state != SolvencyState::Undercollateralized
+    }
+}
+
+/// WASM-supported solvency methods related to `BankStage`
+#[wasm_bindgen]
+impl BankBox {
+    /// Acquire a report on the solvency of the protocol, including the
+    /// haircut applied when redeeming StableCoins versus the oracle rate
+    #[wasm_bindgen]
+    pub fn solvency_status(
+        &self,
+        oracle_box: &ErgUsdOraclePoolBox,
+        config: &ProtocolConfig,
+    ) -> SolvencyStatus {
+        let min_reserve_ratio = config.min_reserve_ratio;
+        let oracle_rate = self.oracle_rate(oracle_box, config);
+        let base_reserves = self.base_reserves(config);
+        let reserve_ratio = self.current_reserve_ratio(oracle_box, config);
+        let equity = self.equity(oracle_box, config);
+
+        // The nanoErgs required to cover every StableCoin at the oracle rate
+        let reserves_needed = self.num_circulating_stablecoins() as u128 * oracle_rate as u128;
+        let liabilities_at_oracle_rate = u64::try_from(reserves_needed).unwrap_or(u64::MAX);
+        let shortfall = u64::try_from(reserves_needed.saturating_sub(base_reserves as u128))
+            .unwrap_or(u64::MAX);
+
+        // The haircut StableCoin holders take when redeeming
+        let stablecoin_price = self.stablecoin_nominal_price(oracle_box, config);
+        let haircut_per_coin = oracle_rate.saturating_sub(stablecoin_price);
+        let haircut_bps =
+            mul_div(haircut_per_coin, BASIS_POINTS, oracle_rate, "haircut").unwrap_or(0);
+
+        let state = if (base_reserves as u128) < reserves_needed {
+            SolvencyState::Undercollateralized
+        } else if equity == 0 {
+            SolvencyState::NoReserveCoinEquity
+        } else if reserve_ratio < min_reserve_ratio {
+            SolvencyState::BelowMinReserveRatio
+        } else {
+            SolvencyState::FullyBacked
+        };
+
+        SolvencyStatus {
+            state,
+            reserve_ratio,
+            min_reserve_ratio,
+            base_reserves,
+            liabilities_at_oracle_rate,
+            shortfall,
+            equity,
+            oracle_rate,
+            stablecoin_price,
+            haircut_per_coin,
+            haircut_bps,
+        }
+    }
+}
@@ -1,25 +1,34 @@
 // This file holds logic for Actions related to updating the StableCoin
-// protocol and the definitions of boxes related to voting/updating.
+// protocol, the definitions of boxes related to voting/updating, and the
+// local registry of proposed updates.
 use crate::bank::BankBox;
+use crate::equations::{checked_add, checked_sub};
 use crate::error::ProtocolError;
 use crate::input_boxes::*;
-use crate::parameters::{UPDATE_BALLOT_TOKEN_ID, UPDATE_NFT_ID};
+use crate::parameters::{ContractVersion, ProtocolConfig};
 use crate::protocol::StableCoinProtocol;
+use crate::registers::{decode_hash, verify_registers, RegisterSpec, RegisterType};
+use crate::voting::{address_hash, stale_ballots, VoteTally};
 use ergo_headless_dapp_framework::encoding::{
-    hash_and_serialize_p2s, serialize_hex_encoded_string, serialize_p2s_from_ergo_tree,
-    unwrap_hex_encoded_string,
+    build_token, deserialize_p2s_to_ergo_tree, hash_and_serialize_p2s,
+    serialize_hex_encoded_string, serialize_p2s_from_ergo_tree, unwrap_hex_encoded_string,
 };
 use ergo_headless_dapp_framework::{
-    create_candidate, HeadlessDappError, TokensChangeBox, TxFeeBox, WASMBox, WrapBox, WrappedBox,
+    create_candidate, HeadlessDappError, TokensChangeBox, TxAssemblerSpecBuilder, TxFeeBox,
+    WASMBox, WrapBox, WrappedBox,
 };
 use ergo_headless_dapp_framework::{BlockHeight, NanoErg, P2PKAddressString, P2SAddressString};
 pub use ergo_headless_dapp_framework::{
     BoxSpec, ExplorerFindable, SpecBox, SpecifiedBox, TokenSpec,
 };
-use ergo_lib::chain::ergo_box::{ErgoBox, ErgoBoxCandidate};
+use ergo_lib::chain::ergo_box::{BoxValue, ErgoBox, ErgoBoxCandidate, NonMandatoryRegisters};
 use ergo_lib::chain::transaction::unsigned::UnsignedTransaction;
-use ergo_lib::chain::transaction::UnsignedInput;
+use ergo_lib::chain::transaction::{TxId, UnsignedInput};
+use ergo_lib_wasm::box_coll::ErgoBoxes;
 use ergo_lib_wasm::ergo_box::ErgoBox as WErgoBox;
+use ergo_lib_wasm::transaction::UnsignedTransaction as WUnsignedTransaction;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 use wasm_bindgen::prelude::*;
 
 /// A box which represents a cast vote for updating the protocol
@@ -30,36 +39,106 @@ pub struct BallotBox {
 }
 
 impl SpecifiedBox for BallotBox {
-    /// A `BoxSpec` that checks that the box is a valid BallotBox
+    /// A `BoxSpec` that checks that the box is a valid BallotBox of the
+    /// default (mainnet) deployment. See `BallotBox::box_spec_for`.
+    ///
+    /// Beware: the derived `BallotBox::new`, `BallotBox::w_new` and
+    /// `process_explorer_response` use this spec, so they reject the boxes of
+    /// every other deployment (testnet, devnet or any custom `ProtocolConfig`).
+    /// Use `BallotBox::new_with_config`, `BallotBox::w_new_with_config` and
+    /// `BallotBox::box_spec_for` instead.
     fn box_spec() -> BoxSpec {
-        let tok_1_spec = Some(TokenSpec::new(1..u64::MAX, UPDATE_BALLOT_TOKEN_ID));
-        let tok_specs = vec![tok_1_spec];
-        // Add registers specs once implemented (just on type)
-        BoxSpec::new_predicated(None, None, vec![], tok_specs, Some(Self::predicate))
+        Self::box_spec_for(&ProtocolConfig::default())
     }
 }
 
 // Methods for acquiring the state of the BallotBox
+#[wasm_bindgen]
 impl BallotBox {
-    // The hash of the address which is being voted for in the Ballot Box
+    /// Wrap an `ErgoBox` which is a valid BallotBox of the deployment
+    /// described by `config`
+    #[wasm_bindgen]
+    pub fn w_new_with_config(
+        wrapped_box: WErgoBox,
+        config: &ProtocolConfig,
+    ) -> Result<BallotBox, JsValue> {
+        let ergo_box: ErgoBox = wrapped_box.into();
+        BallotBox::new_with_config(&ergo_box, config)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    // The hash of the address which is being voted for in the Ballot Box.
+    // Using unwrap because the `BoxSpec` predicate guarantees the register
+    // holds a valid hash.
+    #[wasm_bindgen]
     pub fn address_hash_voted_for(&self) -> String {
-        unwrap_hex_encoded_string(&self.registers()[0]).unwrap()
+        self.try_address_hash_voted_for().unwrap()
     }
 
-    // The box id of the Update Box when the vote was cast
+    // The box id of the Update Box when the vote was cast.
+    // Using unwrap because the `BoxSpec` predicate guarantees the register
+    // holds a valid box id.
+    #[wasm_bindgen]
     pub fn update_box_id(&self) -> String {
-        unwrap_hex_encoded_string(&self.registers()[1]).unwrap()
+        self.try_update_box_id().unwrap()
     }
 
+    #[wasm_bindgen]
     pub fn voting_power(&self) -> u64 {
         self.tokens()[0].amount.into()
     }
+
+    // Whether the vote was cast against an older Update Box, meaning the
+    // Update contract no longer counts it
+    #[wasm_bindgen]
+    pub fn is_stale(&self, update_box: &UpdateBox) -> bool {
+        self.has_vote() && self.update_box_id() != update_box.box_id()
+    }
 }
 
 impl BallotBox {
-    /// A simple predicate to check the registers
+    /// A `BoxSpec` that checks that the box is a valid BallotBox holding
+    /// the ballot tokens of the deployment described by `config`
+    pub fn box_spec_for(config: &ProtocolConfig) -> BoxSpec {
+        let tok_1_spec = Some(TokenSpec::new(1..u64::MAX, &config.update_ballot_token_id));
+        let tok_specs = vec![tok_1_spec];
+        BoxSpec::new_predicated(None, None, vec![], tok_specs, Some(Self::predicate))
+    }
+
+    /// Wrap an `ErgoBox` which is a valid BallotBox of the deployment
+    /// described by `config`
+    pub fn new_with_config(
+        b: &ErgoBox,
+        config: &ProtocolConfig,
+    ) -> Result<BallotBox, ProtocolError> {
+        BallotBox::box_spec_for(config).verify_box(b)?;
+        Ok(BallotBox {
+            ergo_box: b.clone(),
+        })
+    }
+
+    /// The specs of R4/R5 of a Ballot box. Both are empty if the vote has
+    /// been retracted (or never cast).
+    pub fn register_specs() -> Vec<RegisterSpec> {
+        vec![
+            RegisterSpec::new("address hash voted for", RegisterType::HashOrEmpty),
+            RegisterSpec::new("Update box id", RegisterType::HashOrEmpty),
+        ]
+    }
+
+    /// A predicate to check the types of the registers
     pub fn predicate(b: &ErgoBox) -> bool {
-        b.additional_registers.get_ordered_values().len() == 2
+        verify_registers(b, &Self::register_specs()).is_ok()
+    }
+
+    /// The hash of the address which is being voted for in the Ballot Box
+    pub fn try_address_hash_voted_for(&self) -> Result<String, ProtocolError> {
+        decode_hash(&self.get_box(), 0, "address hash voted for", true)
+    }
+
+    /// The box id of the Update Box when the vote was cast
+    pub fn try_update_box_id(&self) -> Result<String, ProtocolError> {
+        decode_hash(&self.get_box(), 1, "Update box id", true)
     }
 
     /// Verify that the `BallotBox` is voting for a given P2S address
@@ -96,7 +175,7 @@ impl BallotBox {
 
                 // Creating the output Ballot box candidate
                 let output_ballot_candidate = create_candidate(
-                    input_box.value.as_u64().clone(),
+                    *input_box.value.as_u64(),
                     &serialize_p2s_from_ergo_tree(input_box.ergo_tree),
                     &input_box.tokens,
                     &registers_vec,
@@ -108,6 +187,117 @@ impl BallotBox {
         }
         Err(ProtocolError::Other("Failed to create `Vote For Update` Action Tx due to failed serialization of registers.".to_string()))
     }
+
+    /// Create an `ErgoBoxCandidate` for the output Ballot box of the
+    /// `Refresh Votes` action, which holds the same vote as the `BallotBox`
+    /// but cast against the current `UpdateBox`
+    pub fn create_refreshed_vote_candidate(
+        &self,
+        update_box: &UpdateBox,
+        current_height: BlockHeight,
+    ) -> Result<ErgoBoxCandidate, ProtocolError> {
+        let box_id_constant = serialize_hex_encoded_string(&update_box.box_id())
+            .map_err(|_| ProtocolError::InvalidRegisterValues())?;
+        // Keep R4 (the address hash voted for) and set R5 to the box id
+        let registers_vec = vec![self.registers()[0].clone(), box_id_constant];
+        let candidate = create_candidate(
+            self.nano_ergs(),
+            &self.holder_address(),
+            &self.tokens(),
+            &registers_vec,
+            current_height,
+        )?;
+        Ok(candidate)
+    }
+
+    /// Whether the `BallotBox` currently holds a vote. Ballots whose vote
+    /// was retracted hold empty registers.
+    pub fn has_vote(&self) -> bool {
+        !self.address_hash_voted_for().is_empty()
+    }
+
+    /// Create an `ErgoBoxCandidate` for an output Ballot box which holds
+    /// `num_ballot_tokens` ballot tokens. The vote held in the registers of
+    /// this box is kept if `keep_vote` is true, otherwise R4/R5 are cleared.
+    pub fn create_ballot_candidate(
+        &self,
+        nano_ergs: NanoErg,
+        holder_address: &P2PKAddressString,
+        num_ballot_tokens: u64,
+        keep_vote: bool,
+        current_height: BlockHeight,
+    ) -> Result<ErgoBoxCandidate, ProtocolError> {
+        // Reuse the token ID of the input box rather than that of a config,
+        // so the output always holds the same ballot token
+        let ballot_token_id: String = self.tokens()[0].token_id.0.clone().into();
+        let ballot_token = build_token(&ballot_token_id, num_ballot_tokens)?;
+        let registers_vec = if keep_vote {
+            self.registers()
+        } else {
+            // Set R4/R5 to empty byte collections so the box is still
+            // recognized as a `BallotBox`, but never counted as a vote
+            let empty_constant = serialize_hex_encoded_string(&String::new())
+                .map_err(|_| ProtocolError::InvalidRegisterValues())?;
+            vec![empty_constant.clone(), empty_constant]
+        };
+        let candidate = create_candidate(
+            nano_ergs,
+            holder_address,
+            &vec![ballot_token],
+            &registers_vec,
+            current_height,
+        )?;
+        Ok(candidate)
+    }
+
+    /// The address of the holder of the `BallotBox`
+    pub fn holder_address(&self) -> P2PKAddressString {
+        serialize_p2s_from_ergo_tree(self.get_box().ergo_tree)
+    }
+
+    /// Create a placeholder box holding `num_ballot_tokens` ballot tokens
+    /// with no vote cast, guarded by the `holder_address`.
+    /// This is useful for using with protocols as a placeholder so that
+    /// an assembler spec can be created (and this placeholder box thrown out
+    /// and replaced with the user's actual input box from the assembler)
+    pub fn create_placeholder_box(
+        num_ballot_tokens: u64,
+        holder_address: &P2PKAddressString,
+        config: &ProtocolConfig,
+    ) -> Option<BallotBox> {
+        let ergo_tree = deserialize_p2s_to_ergo_tree(holder_address.clone()).ok()?;
+        let box_value = BoxValue::new(config.min_box_value).ok()?;
+        let token = build_token(&config.update_ballot_token_id, num_ballot_tokens).ok()?;
+        let empty_constant = serialize_hex_encoded_string(&String::new()).ok()?;
+        let registers = NonMandatoryRegisters::from_ordered_values(vec![
+            empty_constant.clone(),
+            empty_constant,
+        ])
+        .ok()?;
+        let placeholder_box = ErgoBox::new(
+            box_value,
+            ergo_tree,
+            vec![token],
+            registers,
+            0,
+            TxId::zero(),
+            0,
+        );
+        BallotBox::new_with_config(&placeholder_box, config).ok()
+    }
+
+    /// Converts from the WASM wrapper `ErgoBoxes`, skipping any box which is
+    /// not a valid `BallotBox`.
+    pub fn filter_from_ergo_boxes(
+        ergo_boxes: &ErgoBoxes,
+        config: &ProtocolConfig,
+    ) -> Vec<BallotBox> {
+        let unwrapped_boxes: Vec<ErgoBox> = ergo_boxes.clone().into();
+        unwrapped_boxes
+            .iter()
+            .filter_map(|b| BallotBox::new_with_config(b, config).ok())
+            .collect()
+    }
 }
 
 /// The box which holds the Update NFT & the address to be used to
@@ -119,16 +309,79 @@ pub struct UpdateBox {
 }
 
 impl SpecifiedBox for UpdateBox {
-    /// A `BoxSpec` that checks that the box is a valid UpdateBox
+    /// A `BoxSpec` that checks that the box is a valid UpdateBox of the
+    /// default (mainnet) deployment. See `UpdateBox::box_spec_for`.
+    ///
+    /// Beware: the derived `UpdateBox::new`, `UpdateBox::w_new` and
+    /// `process_explorer_response` use this spec, so they reject the boxes of
+    /// every other deployment (testnet, devnet or any custom `ProtocolConfig`).
+    /// Use `UpdateBox::new_with_config`, `UpdateBox::w_new_with_config` and
+    /// `UpdateBox::box_spec_for` instead.
     fn box_spec() -> BoxSpec {
-        let tok_1_spec = Some(TokenSpec::new(1..2, UPDATE_NFT_ID));
-        let tok_specs = vec![tok_1_spec];
-        // Add registers specs once implemented (just on type)
-        BoxSpec::new(None, None, vec![], tok_specs)
+        Self::box_spec_for(&ProtocolConfig::default())
+    }
+}
+
+/// WASM-supported methods related to `UpdateBox`
+#[wasm_bindgen]
+impl UpdateBox {
+    /// Wrap an `ErgoBox` which is a valid UpdateBox of the deployment
+    /// described by `config`
+    #[wasm_bindgen]
+    pub fn w_new_with_config(
+        wrapped_box: WErgoBox,
+        config: &ProtocolConfig,
+    ) -> Result<UpdateBox, JsValue> {
+        let ergo_box: ErgoBox = wrapped_box.into();
+        UpdateBox::new_with_config(&ergo_box, config)
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))
+    }
+
+    /// The hash of the address which is currently held in R4 of the Update Box.
+    /// Using unwrap because the `BoxSpec` predicate guarantees the register
+    /// holds a valid hash.
+    #[wasm_bindgen]
+    pub fn address_hash(&self) -> String {
+        self.try_address_hash().unwrap()
     }
 }
 
 impl UpdateBox {
+    /// A `BoxSpec` that checks that the box is a valid UpdateBox holding
+    /// the Update NFT of the deployment described by `config`
+    pub fn box_spec_for(config: &ProtocolConfig) -> BoxSpec {
+        let tok_1_spec = Some(TokenSpec::new(1..2, &config.update_nft_id));
+        let tok_specs = vec![tok_1_spec];
+        BoxSpec::new_predicated(None, None, vec![], tok_specs, Some(Self::predicate))
+    }
+
+    /// Wrap an `ErgoBox` which is a valid UpdateBox of the deployment
+    /// described by `config`
+    pub fn new_with_config(
+        b: &ErgoBox,
+        config: &ProtocolConfig,
+    ) -> Result<UpdateBox, ProtocolError> {
+        UpdateBox::box_spec_for(config).verify_box(b)?;
+        Ok(UpdateBox {
+            ergo_box: b.clone(),
+        })
+    }
+
+    /// The specs of the registers of the Update box
+    pub fn register_specs() -> Vec<RegisterSpec> {
+        vec![RegisterSpec::new("address hash", RegisterType::Hash)]
+    }
+
+    /// A predicate to check the types of the registers
+    pub fn predicate(b: &ErgoBox) -> bool {
+        verify_registers(b, &Self::register_specs()).is_ok()
+    }
+
+    /// The hash of the address which is currently held in R4 of the Update Box
+    pub fn try_address_hash(&self) -> Result<String, ProtocolError> {
+        decode_hash(&self.get_box(), 0, "address hash", false)
+    }
+
     /// Create an `ErgoBoxCandidate` for the output Update box for the
     /// `Collect Votes` Action
     pub fn create_collect_votes_candidate(
@@ -146,7 +399,7 @@ impl UpdateBox {
 
             // Creating the output Ballot box candidate
             let output_ballot_candidate = create_candidate(
-                input_box.value.as_u64().clone(),
+                *input_box.value.as_u64(),
                 &update_input_box.p2s_address(),
                 &input_box.tokens,
                 &registers_vec,
@@ -179,6 +432,129 @@ impl UpdateBox {
     }
 }
 
+/// A proposed update of the protocol. Ballot/Update boxes only hold the
+/// hash of the proposed address, so proposals are what map those hashes
+/// back to something voters can read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Proposal {
+    /// The P2S address of the proposed new Bank contract
+    pub address: P2SAddressString,
+    /// The blake2b256 hash of the address' ErgoTree
+    pub address_hash: String,
+    /// A short title of the proposal
+    pub title: String,
+    /// A description of what the update changes
+    #[serde(default)]
+    pub description: String,
+    /// The contract version of the proposed Bank contract, if known
+    #[serde(default)]
+    pub contract_version: Option<ContractVersion>,
+}
+
+/// A local registry of proposed updates of the protocol
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProposalRegistry {
+    pub proposals: Vec<Proposal>,
+}
+
+impl ProposalRegistry {
+    /// Create a new empty `ProposalRegistry`
+    pub fn new() -> ProposalRegistry {
+        ProposalRegistry::default()
+    }
+
+    /// Register a new proposal for the provided P2S address. Errors if the
+    /// address is invalid or has already been registered.
+    pub fn register(
+        &mut self,
+        address: &P2SAddressString,
+        title: &str,
+        description: &str,
+        contract_version: Option<ContractVersion>,
+    ) -> Result<&Proposal, ProtocolError> {
+        let address_hash = address_hash(address)?;
+        if let Some(existing) = self.resolve_hash(&address_hash) {
+            return Err(ProtocolError::InvalidProposalRegistry(format!(
+                "The address has already been proposed as `{}`.",
+                existing.title
+            )));
+        }
+        self.proposals.push(Proposal {
+            address: address.clone(),
+            address_hash,
+            title: title.to_string(),
+            description: description.to_string(),
+            contract_version,
+        });
+        Ok(&self.proposals[self.proposals.len() - 1])
+    }
+
+    /// Find the proposal whose address has the provided hash
+    pub fn resolve_hash(&self, address_hash: &str) -> Option<&Proposal> {
+        self.proposals
+            .iter()
+            .find(|p| p.address_hash == address_hash)
+    }
+
+    /// Find the proposal for the provided P2S address or address hash
+    pub fn find(&self, address_or_hash: &str) -> Option<&Proposal> {
+        self.proposals
+            .iter()
+            .find(|p| p.address == address_or_hash)
+            .or_else(|| self.resolve_hash(address_or_hash))
+    }
+
+    /// Find the proposal which the `BallotBox` is voting for
+    pub fn resolve_ballot(&self, ballot_box: &BallotBox) -> Option<&Proposal> {
+        self.resolve_hash(&ballot_box.address_hash_voted_for())
+    }
+
+    /// Find the proposal whose address is held in R4 of the `UpdateBox`
+    pub fn resolve_update_box(&self, update_box: &UpdateBox) -> Option<&Proposal> {
+        self.resolve_hash(&update_box.address_hash())
+    }
+
+    /// The title of the proposal with the provided address hash alongside
+    /// the hash, or just the hash if no such proposal is registered
+    pub fn describe_hash(&self, address_hash: &str) -> String {
+        match self.resolve_hash(address_hash) {
+            Some(proposal) => format!("{} ({})", proposal.title, address_hash),
+            None => address_hash.to_string(),
+        }
+    }
+
+    /// Parse a `ProposalRegistry` from a JSON string
+    pub fn from_json(json: &str) -> Result<ProposalRegistry, ProtocolError> {
+        serde_json::from_str(json)
+            .map_err(|e| ProtocolError::InvalidProposalRegistry(e.to_string()))
+    }
+
+    /// Serialize the `ProposalRegistry` into a JSON string
+    pub fn to_json(&self) -> Result<String, ProtocolError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| ProtocolError::InvalidProposalRegistry(e.to_string()))
+    }
+
+    /// Read a `ProposalRegistry` from a local JSON file. If the file does
+    /// not exist an empty registry is returned.
+    pub fn load_from_file(path: &Path) -> Result<ProposalRegistry, ProtocolError> {
+        if !path.exists() {
+            return Ok(ProposalRegistry::new());
+        }
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ProtocolError::InvalidProposalRegistry(format!("{}: {}", path.display(), e))
+        })?;
+        ProposalRegistry::from_json(&contents)
+    }
+
+    /// Write the `ProposalRegistry` to a local JSON file
+    pub fn save_to_file(&self, path: &Path) -> Result<(), ProtocolError> {
+        std::fs::write(path, self.to_json()?).map_err(|e| {
+            ProtocolError::InvalidProposalRegistry(format!("{}: {}", path.display(), e))
+        })
+    }
+}
+
 /// Implement Update Actions on the `StableCoinProtocol`
 impl StableCoinProtocol {
     /// Action: Vote For Update
@@ -192,45 +568,76 @@ impl StableCoinProtocol {
         ergs_box_for_fee: ErgsBox,
         user_address: P2PKAddressString,
     ) -> Result<UnsignedTransaction, ProtocolError> {
-        // Number of nanoErgs left over from the input after paying for fee
-        let left_over_nano_ergs = ergs_box_for_fee.get_box().value.as_u64() - transaction_fee;
-
-        // Defining inputs
-        let tx_inputs = vec![
-            user_ballot_box.get_box().into(),
-            ergs_box_for_fee.get_box().into(),
-        ];
-
-        // Create the output Ballot Box candidate
-        let output_ballot_candidate = user_ballot_box.create_vote_for_update_candidate(
+        self.action_vote_for_update_multiple(
             &address_to_vote_for,
+            &vec![user_ballot_box],
             &update_box,
-            &user_ballot_box,
             current_height,
+            transaction_fee,
+            &ergs_box_for_fee,
+            &user_address,
+        )
+    }
+
+    /// Action: Vote For Update (Multiple Ballot Boxes)
+    /// Casts the same vote with every one of the `ballot_boxes` in a single
+    /// transaction. The Ballot boxes may be held by different addresses,
+    /// each output Ballot box is held by the same address as its input and
+    /// is placed at the same index. The transaction must be signed by the
+    /// holders of all of the Ballot boxes and of the `ergs_box_for_fee`,
+    /// meaning it can also be built on behalf of a delegate for them to sign.
+    pub fn action_vote_for_update_multiple(
+        &self,
+        address_to_vote_for: &P2SAddressString,
+        ballot_boxes: &Vec<BallotBox>,
+        update_box: &UpdateBox,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: &P2PKAddressString,
+    ) -> Result<UnsignedTransaction, ProtocolError> {
+        if ballot_boxes.is_empty() {
+            return Err(ProtocolError::InsufficientNumberOfBoxes());
+        }
+        // Number of nanoErgs left over from the input after paying for fee
+        let left_over_nano_ergs = checked_sub(
+            ergs_box_for_fee.nano_ergs(),
+            transaction_fee,
+            "left over nanoErgs",
         )?;
 
+        // Defining inputs
+        let mut tx_inputs: Vec<UnsignedInput> =
+            ballot_boxes.iter().map(|bb| bb.get_box().into()).collect();
+        tx_inputs.push(ergs_box_for_fee.get_box().into());
+
+        // Create the output Ballot Box candidates
+        let mut output_candidates = vec![];
+        for bb in ballot_boxes {
+            output_candidates.push(bb.create_vote_for_update_candidate(
+                address_to_vote_for,
+                update_box,
+                bb,
+                current_height,
+            )?);
+        }
+
         let change_box_candidate = TokensChangeBox::output_candidate_filtered(
-            &vec![user_ballot_box.tokens()[0].clone()],
+            &vec![ballot_boxes[0].tokens()[0].clone()],
             &vec![ergs_box_for_fee.get_box()],
             left_over_nano_ergs,
-            &user_address,
+            user_address,
             current_height,
         )?;
+        output_candidates.push(change_box_candidate);
 
         // Create the Transaction Fee box candidate
         let transaction_fee_box_candidate =
             TxFeeBox::output_candidate(transaction_fee, current_height)?;
+        output_candidates.push(transaction_fee_box_candidate);
 
         // Creating the UnsignedTransaction
-        let unsigned_tx = UnsignedTransaction::new(
-            tx_inputs,
-            vec![],
-            vec![
-                output_ballot_candidate,
-                change_box_candidate,
-                transaction_fee_box_candidate,
-            ],
-        );
+        let unsigned_tx = UnsignedTransaction::new(tx_inputs, vec![], output_candidates);
 
         Ok(unsigned_tx)
     }
@@ -247,19 +654,17 @@ impl StableCoinProtocol {
         user_address: &P2PKAddressString,
     ) -> Result<UnsignedTransaction, ProtocolError> {
         // Number of nanoErgs left over from the input after paying for fee
-        let left_over_nano_ergs = ergs_box_for_fee.get_box().value.as_u64() - transaction_fee;
+        let left_over_nano_ergs = checked_sub(
+            ergs_box_for_fee.nano_ergs(),
+            transaction_fee,
+            "left over nanoErgs",
+        )?;
 
         // Verify that the Collect Votes Action can be issued
-        // by checking if the Update Box already has the same
-        // value in R4.
-        let current_update_box_r4 = update_box.registers()[0].clone();
-        if let Ok(address_hash_constant) = hash_and_serialize_p2s(address_voted_for) {
-            if address_hash_constant == current_update_box_r4 {
-                return Err(ProtocolError::Other(
-                    "The Update Box already has the same address collected in R4.".to_string(),
-                ));
-            }
-        }
+        // by checking that the Update Box does not already have the same
+        // value in R4, and that the votes reach quorum.
+        VoteTally::new(ballot_boxes, update_box, &self.config)
+            .verify_collectable(address_voted_for)?;
 
         // Defining inputs
         let tx_inputs: Vec<UnsignedInput> = vec![
@@ -280,8 +685,8 @@ impl StableCoinProtocol {
 
         // Create the output Update Box candidate
         let output_update_candidate = update_box.create_collect_votes_candidate(
-            &address_voted_for,
-            &update_box,
+            address_voted_for,
+            update_box,
             current_height,
         )?;
 
@@ -322,7 +727,11 @@ impl StableCoinProtocol {
         user_address: &P2PKAddressString,
     ) -> Result<UnsignedTransaction, ProtocolError> {
         // Number of nanoErgs left over from the input after paying for fee
-        let left_over_nano_ergs = ergs_box_for_fee.get_box().value.as_u64() - transaction_fee;
+        let left_over_nano_ergs = checked_sub(
+            ergs_box_for_fee.nano_ergs(),
+            transaction_fee,
+            "left over nanoErgs",
+        )?;
 
         // Defining inputs
         let tx_inputs: Vec<UnsignedInput> = vec![
@@ -362,4 +771,847 @@ impl StableCoinProtocol {
 
         Ok(unsigned_tx)
     }
+
+    /// Action: Refresh Votes
+    /// Re-casts the votes of the stale `ballot_boxes` (those cast against an
+    /// older Update box) against the current `UpdateBox`, keeping the same
+    /// address voted for. Ballot boxes which are not stale are not spent.
+    pub fn action_refresh_votes(
+        &self,
+        ballot_boxes: &[BallotBox],
+        update_box: &UpdateBox,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: &P2PKAddressString,
+    ) -> Result<UnsignedTransaction, ProtocolError> {
+        let stale_ballot_boxes = stale_ballots(ballot_boxes, update_box);
+        if stale_ballot_boxes.is_empty() {
+            return Err(ProtocolError::Other(
+                "None of the Ballot boxes hold a stale vote to refresh.".to_string(),
+            ));
+        }
+        // Number of nanoErgs left over from the input after paying for fee
+        let left_over_nano_ergs = checked_sub(
+            ergs_box_for_fee.nano_ergs(),
+            transaction_fee,
+            "left over nanoErgs",
+        )?;
+
+        // Defining inputs
+        let mut tx_inputs: Vec<UnsignedInput> = stale_ballot_boxes
+            .iter()
+            .map(|bb| bb.get_box().into())
+            .collect();
+        tx_inputs.push(ergs_box_for_fee.get_box().into());
+
+        // Create the refreshed output Ballot Box candidates
+        let mut output_candidates = vec![];
+        for bb in &stale_ballot_boxes {
+            output_candidates.push(bb.create_refreshed_vote_candidate(update_box, current_height)?);
+        }
+
+        let change_box_candidate = TokensChangeBox::output_candidate_filtered(
+            &vec![stale_ballot_boxes[0].tokens()[0].clone()],
+            &vec![ergs_box_for_fee.get_box()],
+            left_over_nano_ergs,
+            user_address,
+            current_height,
+        )?;
+        output_candidates.push(change_box_candidate);
+
+        // Create the Transaction Fee box candidate
+        let transaction_fee_box_candidate =
+            TxFeeBox::output_candidate(transaction_fee, current_height)?;
+        output_candidates.push(transaction_fee_box_candidate);
+
+        // Creating the UnsignedTransaction
+        let unsigned_tx = UnsignedTransaction::new(tx_inputs, vec![], output_candidates);
+
+        Ok(unsigned_tx)
+    }
+
+    /// Action: Retract Vote
+    /// Clears R4/R5 of the user's Ballot box so that it no longer counts
+    /// towards any update.
+    pub fn action_retract_vote(
+        &self,
+        user_ballot_box: &BallotBox,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: &P2PKAddressString,
+    ) -> Result<UnsignedTransaction, ProtocolError> {
+        if !user_ballot_box.has_vote() {
+            return Err(ProtocolError::Other(
+                "The Ballot box does not hold a vote to retract.".to_string(),
+            ));
+        }
+        // Number of nanoErgs left over from the input after paying for fee
+        let left_over_nano_ergs = checked_sub(
+            ergs_box_for_fee.nano_ergs(),
+            transaction_fee,
+            "left over nanoErgs",
+        )?;
+
+        // Defining inputs
+        let tx_inputs = vec![
+            user_ballot_box.get_box().into(),
+            ergs_box_for_fee.get_box().into(),
+        ];
+
+        // Create the output Ballot Box candidate with cleared registers
+        let output_ballot_candidate = user_ballot_box.create_ballot_candidate(
+            user_ballot_box.nano_ergs(),
+            &user_ballot_box.holder_address(),
+            user_ballot_box.voting_power(),
+            false,
+            current_height,
+        )?;
+
+        let change_box_candidate = TokensChangeBox::output_candidate_filtered(
+            &vec![user_ballot_box.tokens()[0].clone()],
+            &vec![ergs_box_for_fee.get_box()],
+            left_over_nano_ergs,
+            user_address,
+            current_height,
+        )?;
+
+        // Create the Transaction Fee box candidate
+        let transaction_fee_box_candidate =
+            TxFeeBox::output_candidate(transaction_fee, current_height)?;
+
+        // Creating the UnsignedTransaction
+        let unsigned_tx = UnsignedTransaction::new(
+            tx_inputs,
+            vec![],
+            vec![
+                output_ballot_candidate,
+                change_box_candidate,
+                transaction_fee_box_candidate,
+            ],
+        );
+
+        Ok(unsigned_tx)
+    }
+
+    /// Action: Transfer Ballot
+    /// Transfers `amount` ballot tokens from the user's Ballot box to a new
+    /// Ballot box held by `new_holder_address`, with no vote cast. If only
+    /// part of the ballot tokens are transferred the rest remain in a
+    /// Ballot box of the user which keeps its vote, meaning this Action also
+    /// splits a Ballot box when `new_holder_address` is the user's own.
+    pub fn action_transfer_ballot(
+        &self,
+        user_ballot_box: &BallotBox,
+        amount: u64,
+        new_holder_address: &P2PKAddressString,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: &P2PKAddressString,
+    ) -> Result<UnsignedTransaction, ProtocolError> {
+        let voting_power = user_ballot_box.voting_power();
+        if amount == 0 || amount > voting_power {
+            return Err(ProtocolError::InvalidInputValue(format!(
+                "Cannot transfer {} ballot tokens from a Ballot box holding {}.",
+                amount, voting_power
+            )));
+        }
+        let is_partial_transfer = amount < voting_power;
+
+        // A partial transfer creates a new box which must hold the min box
+        // value, which is paid for out of the ergs box for the fee
+        let new_ballot_nano_ergs = if is_partial_transfer {
+            self.min_box_value()
+        } else {
+            user_ballot_box.nano_ergs()
+        };
+        let required_nano_ergs = if is_partial_transfer {
+            checked_add(transaction_fee, new_ballot_nano_ergs, "required nanoErgs")?
+        } else {
+            transaction_fee
+        };
+        // Number of nanoErgs left over from the input after paying for fee
+        let left_over_nano_ergs = checked_sub(
+            ergs_box_for_fee.nano_ergs(),
+            required_nano_ergs,
+            "left over nanoErgs",
+        )?;
+
+        // Defining inputs
+        let tx_inputs = vec![
+            user_ballot_box.get_box().into(),
+            ergs_box_for_fee.get_box().into(),
+        ];
+
+        // Create the output Ballot Box candidate for the new holder
+        let mut output_candidates = vec![user_ballot_box.create_ballot_candidate(
+            new_ballot_nano_ergs,
+            new_holder_address,
+            amount,
+            false,
+            current_height,
+        )?];
+
+        // Create the output Ballot Box candidate holding the remaining tokens
+        if is_partial_transfer {
+            output_candidates.push(user_ballot_box.create_ballot_candidate(
+                user_ballot_box.nano_ergs(),
+                &user_ballot_box.holder_address(),
+                voting_power - amount,
+                true,
+                current_height,
+            )?);
+        }
+
+        let change_box_candidate = TokensChangeBox::output_candidate_filtered(
+            &vec![user_ballot_box.tokens()[0].clone()],
+            &vec![ergs_box_for_fee.get_box()],
+            left_over_nano_ergs,
+            user_address,
+            current_height,
+        )?;
+        output_candidates.push(change_box_candidate);
+
+        // Create the Transaction Fee box candidate
+        let transaction_fee_box_candidate =
+            TxFeeBox::output_candidate(transaction_fee, current_height)?;
+        output_candidates.push(transaction_fee_box_candidate);
+
+        // Creating the UnsignedTransaction
+        let unsigned_tx = UnsignedTransaction::new(tx_inputs, vec![], output_candidates);
+
+        Ok(unsigned_tx)
+    }
+
+    /// Action: Merge Ballots
+    /// Merges several Ballot boxes with the same holder into a single
+    /// Ballot box. The vote is kept only if every Ballot box holds the same
+    /// vote, otherwise the registers of the merged box are cleared.
+    pub fn action_merge_ballots(
+        &self,
+        user_ballot_boxes: &Vec<BallotBox>,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: &P2PKAddressString,
+    ) -> Result<UnsignedTransaction, ProtocolError> {
+        if user_ballot_boxes.len() < 2 {
+            return Err(ProtocolError::InsufficientNumberOfBoxes());
+        }
+        let first_ballot_box = &user_ballot_boxes[0];
+        let holder_address = first_ballot_box.holder_address();
+        if user_ballot_boxes
+            .iter()
+            .any(|bb| bb.holder_address() != holder_address)
+        {
+            return Err(ProtocolError::InvalidInputValue(
+                "Only Ballot boxes with the same holder can be merged.".to_string(),
+            ));
+        }
+
+        // Number of nanoErgs left over from the input after paying for fee
+        let left_over_nano_ergs = checked_sub(
+            ergs_box_for_fee.nano_ergs(),
+            transaction_fee,
+            "left over nanoErgs",
+        )?;
+
+        // Sum the ballot tokens & nanoErgs of the Ballot boxes
+        let mut total_voting_power = 0;
+        let mut total_nano_ergs = 0;
+        for bb in user_ballot_boxes {
+            total_voting_power = checked_add(
+                total_voting_power,
+                bb.voting_power(),
+                "merged ballot tokens",
+            )?;
+            total_nano_ergs = checked_add(total_nano_ergs, bb.nano_ergs(), "merged nanoErgs")?;
+        }
+        let keep_vote = first_ballot_box.has_vote()
+            && user_ballot_boxes
+                .iter()
+                .all(|bb| bb.registers() == first_ballot_box.registers());
+
+        // Defining inputs
+        let mut tx_inputs: Vec<UnsignedInput> = user_ballot_boxes
+            .iter()
+            .map(|bb| bb.get_box().into())
+            .collect();
+        tx_inputs.push(ergs_box_for_fee.get_box().into());
+
+        // Create the merged output Ballot Box candidate
+        let output_ballot_candidate = first_ballot_box.create_ballot_candidate(
+            total_nano_ergs,
+            &holder_address,
+            total_voting_power,
+            keep_vote,
+            current_height,
+        )?;
+
+        let change_box_candidate = TokensChangeBox::output_candidate_filtered(
+            &vec![first_ballot_box.tokens()[0].clone()],
+            &vec![ergs_box_for_fee.get_box()],
+            left_over_nano_ergs,
+            user_address,
+            current_height,
+        )?;
+
+        // Create the Transaction Fee box candidate
+        let transaction_fee_box_candidate =
+            TxFeeBox::output_candidate(transaction_fee, current_height)?;
+
+        // Creating the UnsignedTransaction
+        let unsigned_tx = UnsignedTransaction::new(
+            tx_inputs,
+            vec![],
+            vec![
+                output_ballot_candidate,
+                change_box_candidate,
+                transaction_fee_box_candidate,
+            ],
+        );
+
+        Ok(unsigned_tx)
+    }
+}
+
+/// WASM-supported Update Actions on the `StableCoinProtocol`
+#[wasm_bindgen]
+impl StableCoinProtocol {
+    #[wasm_bindgen]
+    /// Action: Vote For Update.
+    /// This is the WASM Tx Assembler wrapper function for said Action.
+    pub fn w_assembler_vote_for_update(
+        &self,
+        address_to_vote_for: P2SAddressString,
+        num_ballot_tokens: u64,
+        update_box: &UpdateBox,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        total_input_nano_ergs: NanoErg,
+    ) -> std::result::Result<String, JsValue> {
+        // Creating a placeholder box which holds the user's ballot tokens so
+        // the `UnsignedTransaction` can be created and then converted and
+        // used for an Assembler spec.
+        let user_ballot_box =
+            BallotBox::create_placeholder_box(num_ballot_tokens, &user_address, &self.config)
+                .ok_or_else(|| JsValue::from_str("Failed to create a placeholder Ballot box."))?;
+        let ergs_box_for_fee = placeholder_ergs_box(total_input_nano_ergs)?;
+        let unsigned_tx = self
+            .action_vote_for_update(
+                address_to_vote_for,
+                user_ballot_box,
+                update_box.clone(),
+                current_height,
+                transaction_fee,
+                ergs_box_for_fee,
+                user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+        Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
+    }
+
+    #[wasm_bindgen]
+    /// Action: Collect Votes.
+    /// This is the WASM Tx Assembler wrapper function for said Action.
+    pub fn w_assembler_collect_votes(
+        &self,
+        address_voted_for: P2SAddressString,
+        ballot_boxes: &ErgoBoxes,
+        update_box: &UpdateBox,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        total_input_nano_ergs: NanoErg,
+    ) -> std::result::Result<String, JsValue> {
+        let ballot_boxes = BallotBox::filter_from_ergo_boxes(ballot_boxes, &self.config);
+        let ergs_box_for_fee = placeholder_ergs_box(total_input_nano_ergs)?;
+        let unsigned_tx = self
+            .action_collect_votes(
+                &address_voted_for,
+                &ballot_boxes,
+                update_box,
+                current_height,
+                transaction_fee,
+                &ergs_box_for_fee,
+                &user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+        Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
+    }
+
+    #[wasm_bindgen]
+    /// Action: Update Protocol.
+    /// This is the WASM Tx Assembler wrapper function for said Action.
+    pub fn w_assembler_update_protocol(
+        &self,
+        update_address: P2SAddressString,
+        update_box: &UpdateBox,
+        bank_box: &BankBox,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        total_input_nano_ergs: NanoErg,
+    ) -> std::result::Result<String, JsValue> {
+        let ergs_box_for_fee = placeholder_ergs_box(total_input_nano_ergs)?;
+        let unsigned_tx = self
+            .action_update_protocol(
+                &update_address,
+                update_box,
+                bank_box,
+                current_height,
+                transaction_fee,
+                &ergs_box_for_fee,
+                &user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+        Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
+    }
+
+    #[wasm_bindgen]
+    /// Action: Vote For Update.
+    /// This is the WASM wrapper function for said Action.
+    pub fn w_action_vote_for_update(
+        &self,
+        address_to_vote_for: P2SAddressString,
+        user_ballot_box: &BallotBox,
+        update_box: &UpdateBox,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: P2PKAddressString,
+    ) -> std::result::Result<WUnsignedTransaction, JsValue> {
+        let unsigned_tx = self
+            .action_vote_for_update(
+                address_to_vote_for,
+                user_ballot_box.clone(),
+                update_box.clone(),
+                current_height,
+                transaction_fee,
+                ergs_box_for_fee.clone(),
+                user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        Ok(unsigned_tx.into())
+    }
+
+    #[wasm_bindgen]
+    /// Action: Vote For Update (Multiple Ballot Boxes).
+    /// This is the WASM wrapper function for said Action. Boxes within
+    /// `ballot_boxes` which are not valid `BallotBox`es are skipped.
+    pub fn w_action_vote_for_update_multiple(
+        &self,
+        address_to_vote_for: P2SAddressString,
+        ballot_boxes: &ErgoBoxes,
+        update_box: &UpdateBox,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: P2PKAddressString,
+    ) -> std::result::Result<WUnsignedTransaction, JsValue> {
+        let ballot_boxes = BallotBox::filter_from_ergo_boxes(ballot_boxes, &self.config);
+        let unsigned_tx = self
+            .action_vote_for_update_multiple(
+                &address_to_vote_for,
+                &ballot_boxes,
+                update_box,
+                current_height,
+                transaction_fee,
+                ergs_box_for_fee,
+                &user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        Ok(unsigned_tx.into())
+    }
+
+    #[wasm_bindgen]
+    /// Action: Refresh Votes.
+    /// This is the WASM wrapper function for said Action. Boxes within
+    /// `ballot_boxes` which are not valid `BallotBox`es are skipped.
+    pub fn w_action_refresh_votes(
+        &self,
+        ballot_boxes: &ErgoBoxes,
+        update_box: &UpdateBox,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: P2PKAddressString,
+    ) -> std::result::Result<WUnsignedTransaction, JsValue> {
+        let ballot_boxes = BallotBox::filter_from_ergo_boxes(ballot_boxes, &self.config);
+        let unsigned_tx = self
+            .action_refresh_votes(
+                &ballot_boxes,
+                update_box,
+                current_height,
+                transaction_fee,
+                ergs_box_for_fee,
+                &user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        Ok(unsigned_tx.into())
+    }
+
+    #[wasm_bindgen]
+    /// Action: Collect Votes.
+    /// This is the WASM wrapper function for said Action. Boxes within
+    /// `ballot_boxes` which are not valid `BallotBox`es are skipped.
+    pub fn w_action_collect_votes(
+        &self,
+        address_voted_for: P2SAddressString,
+        ballot_boxes: &ErgoBoxes,
+        update_box: &UpdateBox,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: P2PKAddressString,
+    ) -> std::result::Result<WUnsignedTransaction, JsValue> {
+        let ballot_boxes = BallotBox::filter_from_ergo_boxes(ballot_boxes, &self.config);
+        let unsigned_tx = self
+            .action_collect_votes(
+                &address_voted_for,
+                &ballot_boxes,
+                update_box,
+                current_height,
+                transaction_fee,
+                ergs_box_for_fee,
+                &user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        Ok(unsigned_tx.into())
+    }
+
+    #[wasm_bindgen]
+    /// Action: Update Protocol.
+    /// This is the WASM wrapper function for said Action.
+    pub fn w_action_update_protocol(
+        &self,
+        update_address: P2SAddressString,
+        update_box: &UpdateBox,
+        bank_box: &BankBox,
+        current_height: BlockHeight,
+        transaction_fee: NanoErg,
+        ergs_box_for_fee: &ErgsBox,
+        user_address: P2PKAddressString,
+    ) -> std::result::Result<WUnsignedTransaction, JsValue> {
+        let unsigned_tx = self
+            .action_update_protocol(
+                &update_address,
+                update_box,
+                bank_box,
+                current_height,
+                transaction_fee,
+                ergs_box_for_fee,
+                &user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+
+        Ok(unsigned_tx.into())
+    }
+
+    #[wasm_bindgen]
+    /// Action: Retract Vote.
+    /// This is the WASM Tx Assembler wrapper function for said Action.
+    pub fn w_assembler_retract_vote(
+        &self,
+        user_ballot_box: &BallotBox,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        total_input_nano_ergs: NanoErg,
+    ) -> std::result::Result<String, JsValue> {
+        let ergs_box_for_fee = placeholder_ergs_box(total_input_nano_ergs)?;
+        let unsigned_tx = self
+            .action_retract_vote(
+                user_ballot_box,
+                current_height,
+                transaction_fee,
+                &ergs_box_for_fee,
+                &user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+        Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
+    }
+
+    #[wasm_bindgen]
+    /// Action: Transfer Ballot.
+    /// This is the WASM Tx Assembler wrapper function for said Action.
+    pub fn w_assembler_transfer_ballot(
+        &self,
+        user_ballot_box: &BallotBox,
+        amount: u64,
+        new_holder_address: P2PKAddressString,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        total_input_nano_ergs: NanoErg,
+    ) -> std::result::Result<String, JsValue> {
+        let ergs_box_for_fee = placeholder_ergs_box(total_input_nano_ergs)?;
+        let unsigned_tx = self
+            .action_transfer_ballot(
+                user_ballot_box,
+                amount,
+                &new_holder_address,
+                current_height,
+                transaction_fee,
+                &ergs_box_for_fee,
+                &user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+        Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
+    }
+
+    #[wasm_bindgen]
+    /// Action: Merge Ballots.
+    /// This is the WASM Tx Assembler wrapper function for said Action.
+    pub fn w_assembler_merge_ballots(
+        &self,
+        user_ballot_boxes: &ErgoBoxes,
+        user_address: P2PKAddressString,
+        transaction_fee: NanoErg,
+        current_height: BlockHeight,
+        total_input_nano_ergs: NanoErg,
+    ) -> std::result::Result<String, JsValue> {
+        let ballot_boxes = BallotBox::filter_from_ergo_boxes(user_ballot_boxes, &self.config);
+        let ergs_box_for_fee = placeholder_ergs_box(total_input_nano_ergs)?;
+        let unsigned_tx = self
+            .action_merge_ballots(
+                &ballot_boxes,
+                current_height,
+                transaction_fee,
+                &ergs_box_for_fee,
+                &user_address,
+            )
+            .map_err(|e| JsValue::from_str(&format! {"{:?}", e}))?;
+        Ok(TxAssemblerSpecBuilder::new(unsigned_tx).build_assembler_spec(transaction_fee))
+    }
+}
+
+/// Creating a placeholder box which holds an amount of nanoErgs equal to
+/// `total_input_nano_ergs` so the `UnsignedTransaction` can be created
+/// and then converted and used for an Assembler spec.
+fn placeholder_ergs_box(total_input_nano_ergs: NanoErg) -> std::result::Result<ErgsBox, JsValue> {
+    TxAssemblerSpecBuilder::create_placeholder_ergs_box(total_input_nano_ergs)
+        .ok_or_else(|| JsValue::from_str("Failed to create a placeholder box for the fee."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ergo_lib::ast::Constant;
+    use ergo_lib::ergo_tree::ErgoTree;
+    use ergo_lib::serialization::SigmaSerializable;
+
+    /// The P2S address guarding the Ballot boxes of the user
+    const USER_ADDRESS: &str = "2iHkR7CWvD1R4j1yZg5bkeDRQavjAaVPeTDFGGLZduHyfWMuYpmhHocX8GJoaieTx78FntzJbCBVL6rf96ocJoZdmWBL2fci7NqWgAirppPQmZ7fN9V6z13Ay6brPriBKYqLp1bT2Fk4FkFLCfdPpe";
+    const HEIGHT: BlockHeight = 500_000;
+    const TX_FEE: NanoErg = 1_000_000;
+
+    fn config() -> ProtocolConfig {
+        ProtocolConfig {
+            update_nft_id: "aa".repeat(32),
+            update_ballot_token_id: "bb".repeat(32),
+            ..ProtocolConfig::template()
+        }
+    }
+
+    fn protocol() -> StableCoinProtocol {
+        StableCoinProtocol::new_with_config(&config())
+    }
+
+    fn user_address() -> P2PKAddressString {
+        USER_ADDRESS.to_string()
+    }
+
+    /// The address of another holder, guarded by the user's contract with
+    /// its first `Int` constant changed from 0 to 1
+    fn other_address() -> P2PKAddressString {
+        let mut bytes = deserialize_p2s_to_ergo_tree(user_address())
+            .unwrap()
+            .sigma_serialize_bytes();
+        assert_eq!(bytes[2..4], [0x04, 0x00]);
+        bytes[3] = 0x02;
+        serialize_p2s_from_ergo_tree(ErgoTree::sigma_parse_bytes(bytes).unwrap())
+    }
+
+    /// The registers of a Ballot box voting for the candidate `vote`,
+    /// or of one with no vote cast
+    fn vote_registers(vote: Option<&str>) -> Vec<Constant> {
+        let (address_hash, update_box_id) = match vote {
+            Some(vote) => (vote.repeat(32), "dd".repeat(32)),
+            None => (String::new(), String::new()),
+        };
+        vec![
+            serialize_hex_encoded_string(&address_hash).unwrap(),
+            serialize_hex_encoded_string(&update_box_id).unwrap(),
+        ]
+    }
+
+    /// A Ballot box of the holder holding `voting_power` ballot tokens.
+    /// Boxes of distinct `index` have distinct ids.
+    fn ballot(
+        voting_power: u64,
+        vote: Option<&str>,
+        holder: &P2PKAddressString,
+        index: u16,
+    ) -> BallotBox {
+        let config = config();
+        let candidate = create_candidate(
+            config.min_box_value,
+            holder,
+            &vec![build_token(&config.update_ballot_token_id, voting_power).unwrap()],
+            &vote_registers(vote),
+            0,
+        )
+        .unwrap();
+        let b = ErgoBox::from_box_candidate(&candidate, TxId::zero(), index);
+        BallotBox::new_with_config(&b, &config).unwrap()
+    }
+
+    fn ergs_box() -> ErgsBox {
+        TxAssemblerSpecBuilder::create_placeholder_ergs_box(100_000_000).unwrap()
+    }
+
+    /// The holder, number of ballot tokens, nanoErgs & registers of an
+    /// output Ballot box
+    fn ballot_output(
+        candidate: &ErgoBoxCandidate,
+    ) -> (P2PKAddressString, u64, NanoErg, Vec<Constant>) {
+        (
+            serialize_p2s_from_ergo_tree(candidate.ergo_tree.clone()),
+            candidate.tokens[0].amount.into(),
+            *candidate.value.as_u64(),
+            candidate.additional_registers.get_ordered_values().clone(),
+        )
+    }
+
+    fn invalid_input(result: Result<UnsignedTransaction, ProtocolError>) {
+        match result {
+            Err(ProtocolError::InvalidInputValue(_)) => (),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn retract_vote_clears_the_registers() {
+        let bb = ballot(5, Some("cc"), &user_address(), 0);
+        let tx = protocol()
+            .action_retract_vote(&bb, HEIGHT, TX_FEE, &ergs_box(), &user_address())
+            .unwrap();
+        assert_eq!(tx.output_candidates.len(), 3);
+        assert_eq!(
+            ballot_output(&tx.output_candidates[0]),
+            (user_address(), 5, bb.nano_ergs(), vote_registers(None))
+        );
+
+        // A Ballot box without a vote has nothing to retract
+        let retracted = ballot(5, None, &user_address(), 0);
+        assert!(protocol()
+            .action_retract_vote(&retracted, HEIGHT, TX_FEE, &ergs_box(), &user_address())
+            .is_err());
+    }
+
+    #[test]
+    fn transfer_ballot() {
+        let bb = ballot(5, Some("cc"), &user_address(), 0);
+        let transfer = |amount| {
+            protocol().action_transfer_ballot(
+                &bb,
+                amount,
+                &other_address(),
+                HEIGHT,
+                TX_FEE,
+                &ergs_box(),
+                &user_address(),
+            )
+        };
+
+        // Transferring every ballot token moves the box to the new holder
+        let tx = transfer(5).unwrap();
+        assert_eq!(tx.output_candidates.len(), 3);
+        assert_eq!(
+            ballot_output(&tx.output_candidates[0]),
+            (other_address(), 5, bb.nano_ergs(), vote_registers(None))
+        );
+
+        // Transferring part of them splits the box, keeping the vote of the
+        // user's remaining ballot tokens
+        let tx = transfer(2).unwrap();
+        assert_eq!(tx.output_candidates.len(), 4);
+        assert_eq!(
+            ballot_output(&tx.output_candidates[0]),
+            (
+                other_address(),
+                2,
+                config().min_box_value,
+                vote_registers(None)
+            )
+        );
+        assert_eq!(
+            ballot_output(&tx.output_candidates[1]),
+            (
+                user_address(),
+                3,
+                bb.nano_ergs(),
+                vote_registers(Some("cc"))
+            )
+        );
+
+        invalid_input(transfer(0));
+        invalid_input(transfer(6));
+    }
+
+    #[test]
+    fn merge_ballots() {
+        let merge = |ballot_boxes: Vec<BallotBox>| {
+            protocol().action_merge_ballots(
+                &ballot_boxes,
+                HEIGHT,
+                TX_FEE,
+                &ergs_box(),
+                &user_address(),
+            )
+        };
+        let nano_ergs = 2 * config().min_box_value;
+
+        // Ballot boxes holding the same vote keep it
+        let tx = merge(vec![
+            ballot(2, Some("cc"), &user_address(), 0),
+            ballot(3, Some("cc"), &user_address(), 1),
+        ])
+        .unwrap();
+        assert_eq!(tx.inputs.len(), 3);
+        assert_eq!(
+            ballot_output(&tx.output_candidates[0]),
+            (user_address(), 5, nano_ergs, vote_registers(Some("cc")))
+        );
+
+        // Ballot boxes holding different votes, or no vote, clear it
+        for other_vote in &[Some("ee"), None] {
+            let tx = merge(vec![
+                ballot(2, Some("cc"), &user_address(), 0),
+                ballot(3, *other_vote, &user_address(), 1),
+            ])
+            .unwrap();
+            assert_eq!(
+                ballot_output(&tx.output_candidates[0]),
+                (user_address(), 5, nano_ergs, vote_registers(None))
+            );
+        }
+
+        invalid_input(merge(vec![
+            ballot(2, Some("cc"), &user_address(), 0),
+            ballot(3, Some("cc"), &other_address(), 1),
+        ]));
+        match merge(vec![ballot(2, Some("cc"), &user_address(), 0)]) {
+            Err(ProtocolError::InsufficientNumberOfBoxes()) => (),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
 }
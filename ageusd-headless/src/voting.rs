@@ -0,0 +1,321 @@
+// This file holds the `VoteTally`, a summary of the votes cast via
+// `BallotBox`es for updating the protocol. The Update contract only accepts
+// a `Collect Votes` Action if the ballots voting for the new address with
+// the current Update box id hold at least `min_votes` ballot tokens.
+use crate::error::{ProtocolError, Result};
+use crate::parameters::ProtocolConfig;
+use crate::update::{BallotBox, UpdateBox};
+use ergo_headless_dapp_framework::encoding::{hash_and_serialize_p2s, unwrap_hex_encoded_string};
+use ergo_headless_dapp_framework::{P2SAddressString, WrappedBox};
+
+/// The votes cast for a single candidate update address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateVotes {
+    /// The blake2b256 hash of the candidate address' ErgoTree
+    pub address_hash: String,
+    /// The total voting power of the ballots voting for the candidate
+    pub voting_power: u64,
+    /// The number of ballots voting for the candidate
+    pub num_ballots: usize,
+}
+
+/// A tally of every `BallotBox` against the current `UpdateBox`
+#[derive(Debug, Clone)]
+pub struct VoteTally {
+    /// The box id of the Update box the tally is against
+    pub update_box_id: String,
+    /// The address hash currently held in R4 of the Update box
+    pub current_address_hash: String,
+    /// The voting power required to collect the votes for a candidate
+    pub min_votes: u64,
+    /// The votes per candidate, ordered from most to least voting power
+    pub candidates: Vec<CandidateVotes>,
+    /// Ballots whose vote was cast against an older Update box id and
+    /// therefore no longer count
+    pub stale_ballots: Vec<BallotBox>,
+    /// The voting power of all ballots which count towards a candidate
+    pub total_voting_power: u64,
+    /// The voting power of the stale ballots
+    pub stale_voting_power: u64,
+}
+
+impl VoteTally {
+    /// Tally the `BallotBox`es against the `UpdateBox` using the `min_votes`
+    /// of `config`
+    pub fn new(
+        ballot_boxes: &[BallotBox],
+        update_box: &UpdateBox,
+        config: &ProtocolConfig,
+    ) -> VoteTally {
+        let update_box_id = update_box.box_id();
+        let mut candidates: Vec<CandidateVotes> = vec![];
+        let mut stale_ballots = vec![];
+        for bb in ballot_boxes {
+            // Ballots whose vote was retracted count towards nothing
+            if !bb.has_vote() {
+                continue;
+            }
+            if bb.is_stale(update_box) {
+                stale_ballots.push(bb.clone());
+                continue;
+            }
+            let address_hash = bb.address_hash_voted_for();
+            match candidates
+                .iter_mut()
+                .find(|c| c.address_hash == address_hash)
+            {
+                Some(candidate) => {
+                    candidate.voting_power =
+                        candidate.voting_power.saturating_add(bb.voting_power());
+                    candidate.num_ballots += 1;
+                }
+                None => candidates.push(CandidateVotes {
+                    address_hash,
+                    voting_power: bb.voting_power(),
+                    num_ballots: 1,
+                }),
+            }
+        }
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.voting_power));
+
+        let total_voting_power = candidates
+            .iter()
+            .fold(0u64, |acc, c| acc.saturating_add(c.voting_power));
+        let stale_voting_power = stale_ballots
+            .iter()
+            .fold(0u64, |acc, bb| acc.saturating_add(bb.voting_power()));
+
+        VoteTally {
+            update_box_id,
+            current_address_hash: update_box.address_hash(),
+            min_votes: config.min_votes,
+            candidates,
+            stale_ballots,
+            total_voting_power,
+            stale_voting_power,
+        }
+    }
+
+    /// The candidate with the most voting power
+    pub fn leading_candidate(&self) -> Option<&CandidateVotes> {
+        self.candidates.first()
+    }
+
+    /// The voting power of the ballots voting for the address hash
+    pub fn votes_for_hash(&self, address_hash: &str) -> u64 {
+        self.candidates
+            .iter()
+            .find(|c| c.address_hash == address_hash)
+            .map(|c| c.voting_power)
+            .unwrap_or(0)
+    }
+
+    /// The voting power of the ballots voting for the address
+    pub fn votes_for_address(&self, address: &P2SAddressString) -> Result<u64> {
+        Ok(self.votes_for_hash(&address_hash(address)?))
+    }
+
+    /// The voting power still required for the address to reach quorum
+    pub fn votes_needed(&self, address: &P2SAddressString) -> Result<u64> {
+        Ok(self
+            .min_votes
+            .saturating_sub(self.votes_for_address(address)?))
+    }
+
+    /// Whether the votes for the address reach quorum
+    pub fn quorum_reached(&self, address: &P2SAddressString) -> Result<bool> {
+        Ok(self.votes_for_address(address)? >= self.min_votes)
+    }
+
+    /// Verify that a `Collect Votes` Action for the address would be
+    /// accepted by the Update contract
+    pub fn verify_collectable(&self, address: &P2SAddressString) -> Result<()> {
+        let address_hash = address_hash(address)?;
+        if address_hash == self.current_address_hash {
+            return Err(ProtocolError::Other(
+                "The Update Box already has the same address collected in R4.".to_string(),
+            ));
+        }
+        let votes = self.votes_for_hash(&address_hash);
+        if votes < self.min_votes {
+            return Err(ProtocolError::QuorumNotReached {
+                votes,
+                required: self.min_votes,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The `BallotBox`es whose vote was cast against an older Update box than
+/// `update_box`, and which must be re-cast to be counted again
+pub fn stale_ballots(ballot_boxes: &[BallotBox], update_box: &UpdateBox) -> Vec<BallotBox> {
+    ballot_boxes
+        .iter()
+        .filter(|bb| bb.is_stale(update_box))
+        .cloned()
+        .collect()
+}
+
+/// The `BallotBox`es which were invalidated when the Update box with the id
+/// `previous_update_box_id` was spent, ie. those whose vote was cast
+/// against it
+pub fn ballots_invalidated_by(
+    ballot_boxes: &[BallotBox],
+    previous_update_box_id: &str,
+) -> Vec<BallotBox> {
+    ballot_boxes
+        .iter()
+        .filter(|bb| bb.has_vote() && bb.update_box_id() == previous_update_box_id)
+        .cloned()
+        .collect()
+}
+
+/// The hex-encoded blake2b256 hash of the address' ErgoTree, as held in
+/// the registers of the Update/Ballot boxes
+pub fn address_hash(address: &P2SAddressString) -> Result<String> {
+    Ok(unwrap_hex_encoded_string(&hash_and_serialize_p2s(
+        address,
+    )?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ergo_headless_dapp_framework::create_candidate;
+    use ergo_headless_dapp_framework::encoding::{build_token, serialize_hex_encoded_string};
+    use ergo_lib::chain::ergo_box::ErgoBox;
+    use ergo_lib::chain::transaction::TxId;
+
+    /// The P2S address which the ballots vote for
+    const ADDRESS: &str = "2iHkR7CWvD1R4j1yZg5bkeDRQavjAaVPeTDFGGLZduHyfWMuYpmhHocX8GJoaieTx78FntzJbCBVL6rf96ocJoZdmWBL2fci7NqWgAirppPQmZ7fN9V6z13Ay6brPriBKYqLp1bT2Fk4FkFLCfdPpe";
+
+    fn config() -> ProtocolConfig {
+        ProtocolConfig {
+            update_nft_id: "aa".repeat(32),
+            update_ballot_token_id: "bb".repeat(32),
+            ..ProtocolConfig::template()
+        }
+    }
+
+    /// A box holding `amount` of the token, with the registers given as hex
+    /// encoded `Coll[Byte]`s. Boxes of distinct `index` have distinct ids.
+    fn ergo_box(token_id: &str, amount: u64, registers: &[String], index: u16) -> ErgoBox {
+        let registers = registers
+            .iter()
+            .map(|r| serialize_hex_encoded_string(r).unwrap())
+            .collect();
+        let candidate = create_candidate(
+            1000000,
+            &ADDRESS.to_string(),
+            &vec![build_token(token_id, amount).unwrap()],
+            &registers,
+            0,
+        )
+        .unwrap();
+        ErgoBox::from_box_candidate(&candidate, TxId::zero(), index)
+    }
+
+    /// An Update box holding `address_hash` in R4
+    fn update_box(address_hash: &str, index: u16) -> UpdateBox {
+        let config = config();
+        let b = ergo_box(&config.update_nft_id, 1, &[address_hash.to_string()], index);
+        UpdateBox::new_with_config(&b, &config).unwrap()
+    }
+
+    /// A Ballot box voting for `ADDRESS` against the Update box, or with
+    /// its vote retracted
+    fn ballot(voting_power: u64, update_box: Option<&UpdateBox>, index: u16) -> BallotBox {
+        let config = config();
+        let registers = match update_box {
+            Some(ub) => vec![address_hash(&ADDRESS.to_string()).unwrap(), ub.box_id()],
+            None => vec![String::new(), String::new()],
+        };
+        let b = ergo_box(
+            &config.update_ballot_token_id,
+            voting_power,
+            &registers,
+            100 + index,
+        );
+        BallotBox::new_with_config(&b, &config).unwrap()
+    }
+
+    fn verify(ballots: &[BallotBox], update_box: &UpdateBox) -> Result<()> {
+        VoteTally::new(ballots, update_box, &config()).verify_collectable(&ADDRESS.to_string())
+    }
+
+    fn quorum_not_reached(result: Result<()>) -> (u64, u64) {
+        match result {
+            Err(ProtocolError::QuorumNotReached { votes, required }) => (votes, required),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn collectable_once_min_votes_are_reached() {
+        let min_votes = config().min_votes;
+        assert!(min_votes > 1);
+        let ub = update_box(&"cc".repeat(32), 0);
+
+        let short = vec![ballot(1, Some(&ub), 0), ballot(min_votes - 2, Some(&ub), 1)];
+        assert_eq!(
+            quorum_not_reached(verify(&short, &ub)),
+            (min_votes - 1, min_votes)
+        );
+
+        let reached = vec![ballot(1, Some(&ub), 0), ballot(min_votes - 1, Some(&ub), 1)];
+        assert!(verify(&reached, &ub).is_ok());
+        let tally = VoteTally::new(&reached, &ub, &config());
+        assert_eq!(tally.total_voting_power, min_votes);
+        assert_eq!(tally.leading_candidate().unwrap().num_ballots, 2);
+        assert_eq!(tally.votes_needed(&ADDRESS.to_string()).unwrap(), 0);
+    }
+
+    #[test]
+    fn stale_ballots_are_not_counted() {
+        let min_votes = config().min_votes;
+        let older = update_box(&"cc".repeat(32), 0);
+        let ub = update_box(&"cc".repeat(32), 1);
+        assert_ne!(older.box_id(), ub.box_id());
+
+        let ballots = vec![ballot(min_votes, Some(&older), 0), ballot(1, Some(&ub), 1)];
+        assert_eq!(quorum_not_reached(verify(&ballots, &ub)), (1, min_votes));
+        let tally = VoteTally::new(&ballots, &ub, &config());
+        assert_eq!(tally.stale_ballots.len(), 1);
+        assert_eq!(tally.stale_voting_power, min_votes);
+        assert_eq!(tally.total_voting_power, 1);
+
+        // The same ballots count against the Update box they were cast on
+        assert!(verify(&ballots, &older).is_ok());
+    }
+
+    #[test]
+    fn retracted_ballots_are_not_counted() {
+        let min_votes = config().min_votes;
+        let ub = update_box(&"cc".repeat(32), 0);
+
+        let ballots = vec![
+            ballot(min_votes, None, 0),
+            ballot(min_votes - 1, Some(&ub), 1),
+        ];
+        assert_eq!(
+            quorum_not_reached(verify(&ballots, &ub)),
+            (min_votes - 1, min_votes)
+        );
+        let tally = VoteTally::new(&ballots, &ub, &config());
+        assert!(tally.stale_ballots.is_empty());
+        assert_eq!(tally.stale_voting_power, 0);
+        assert_eq!(tally.total_voting_power, min_votes - 1);
+    }
+
+    #[test]
+    fn already_collected_addresses_are_not_collectable() {
+        let min_votes = config().min_votes;
+        let ub = update_box(&address_hash(&ADDRESS.to_string()).unwrap(), 0);
+        let ballots = vec![ballot(min_votes, Some(&ub), 0)];
+        match verify(&ballots, &ub) {
+            Err(ProtocolError::Other(_)) => (),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+}